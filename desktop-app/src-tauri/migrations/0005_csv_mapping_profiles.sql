@@ -0,0 +1,12 @@
+-- Saved CSV column mappings, one per distributor
+-- Each distributor exports its own layout, so the mapping confirmed on the first
+-- import is reused for every later file from that distributor
+
+CREATE TABLE IF NOT EXISTS csv_mapping_profiles (
+    distributor_id TEXT PRIMARY KEY,
+    column_mapping TEXT NOT NULL, -- JSON CsvColumnMapping
+    headers TEXT NOT NULL, -- JSON array of the headers the mapping was saved against
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (distributor_id) REFERENCES distributors(distributor_id)
+);
@@ -0,0 +1,59 @@
+-- Allow a file to be re-imported on purpose, superseding the earlier import
+-- file_hash can no longer be UNIQUE, so only one active import per hash is enforced instead.
+-- SQLite can't alter constraints, so csv_imports is rebuilt. csv_import_errors references
+-- csv_imports and is rebuilt around it.
+
+CREATE TABLE csv_import_errors_backup AS SELECT * FROM csv_import_errors;
+DROP TABLE csv_import_errors;
+
+CREATE TABLE csv_imports_new (
+    import_id TEXT PRIMARY KEY,
+    restaurant_id TEXT NOT NULL,
+    distributor_id TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    file_hash TEXT NOT NULL, -- SHA-256 of the normalized file content
+    file_size INTEGER NOT NULL,
+    row_count INTEGER,
+    imported_count INTEGER,
+    failed_count INTEGER,
+    status TEXT NOT NULL CHECK (status IN ('pending', 'processing', 'completed', 'failed', 'superseded')),
+    error_message TEXT,
+    imported_by TEXT, -- Name of the user who ran the import
+    superseded_by TEXT, -- import_id of the re-import that replaced this one
+    imported_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(restaurant_id),
+    FOREIGN KEY (distributor_id) REFERENCES distributors(distributor_id)
+);
+
+INSERT INTO csv_imports_new (
+    import_id, restaurant_id, distributor_id, file_name, file_hash, file_size,
+    row_count, imported_count, failed_count, status, error_message, imported_at
+)
+SELECT
+    import_id, restaurant_id, distributor_id, file_name, file_hash, file_size,
+    row_count, imported_count, failed_count, status, error_message, imported_at
+FROM csv_imports;
+
+DROP TABLE csv_imports;
+ALTER TABLE csv_imports_new RENAME TO csv_imports;
+
+CREATE TABLE csv_import_errors (
+    error_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    import_id TEXT NOT NULL,
+    row_number INTEGER NOT NULL,
+    error_type TEXT NOT NULL, -- 'parsing', 'validation', 'product_not_found', etc.
+    error_message TEXT NOT NULL,
+    row_data TEXT, -- JSON of the problematic row
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (import_id) REFERENCES csv_imports(import_id)
+);
+
+INSERT INTO csv_import_errors SELECT * FROM csv_import_errors_backup;
+DROP TABLE csv_import_errors_backup;
+
+CREATE INDEX IF NOT EXISTS idx_csv_imports_restaurant ON csv_imports(restaurant_id, imported_at DESC);
+CREATE INDEX IF NOT EXISTS idx_import_errors_import ON csv_import_errors(import_id);
+
+-- Prevent duplicate imports, ignoring imports that failed or were superseded
+CREATE UNIQUE INDEX IF NOT EXISTS idx_csv_imports_active_hash ON csv_imports(file_hash)
+    WHERE status IN ('pending', 'processing', 'completed');
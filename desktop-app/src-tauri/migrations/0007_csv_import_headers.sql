@@ -0,0 +1,2 @@
+-- Keep each import's header row so rejected rows can be exported in their original layout
+ALTER TABLE csv_imports ADD COLUMN headers TEXT; -- JSON array
@@ -0,0 +1,8 @@
+-- Sniff the delimiter and encoding by default. Nothing in the app could change 0001's ','
+-- and 'UTF-8' seeds until now, so every install is still on them.
+UPDATE app_settings SET value = 'auto', updated_at = CURRENT_TIMESTAMP
+WHERE key IN ('csv_delimiter', 'csv_encoding');
+
+-- Per-distributor overrides of the global csv_delimiter / csv_encoding settings
+ALTER TABLE csv_mapping_profiles ADD COLUMN delimiter TEXT;
+ALTER TABLE csv_mapping_profiles ADD COLUMN encoding TEXT;
@@ -0,0 +1,27 @@
+-- Pack sizes in distributor files that disagree with the synced distributor spec,
+-- kept for review instead of trusting either side
+CREATE TABLE IF NOT EXISTS spec_change_candidates (
+    candidate_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    import_id TEXT NOT NULL, -- Import that last saw this pack size
+    spec_id TEXT NOT NULL,
+    catalog_product_id TEXT NOT NULL,
+    distributor_id TEXT NOT NULL,
+    distributor_item_code TEXT,
+    pack_size_text TEXT NOT NULL, -- As written in the file, e.g. "4/1 GAL"
+    -- Parsed from the file
+    case_packs INTEGER NOT NULL,
+    pack_size REAL NOT NULL,
+    pack_unit_of_measure TEXT NOT NULL,
+    total_preferred_units REAL, -- NULL when the unit can't be converted to the preferred measurement
+    -- The synced spec at the time
+    spec_case_packs INTEGER NOT NULL,
+    spec_pack_size REAL NOT NULL,
+    spec_pack_unit_of_measure TEXT NOT NULL,
+    spec_total_preferred_units REAL NOT NULL,
+    status TEXT DEFAULT 'pending' CHECK (status IN ('pending', 'dismissed')),
+    detected_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (import_id) REFERENCES csv_imports(import_id),
+    UNIQUE(spec_id, pack_size_text)
+);
+
+CREATE INDEX IF NOT EXISTS idx_spec_change_candidates_distributor ON spec_change_candidates(distributor_id, status);
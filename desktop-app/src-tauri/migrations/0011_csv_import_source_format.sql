@@ -0,0 +1,3 @@
+-- Record what kind of file each import read, and which sheet for workbooks
+ALTER TABLE csv_imports ADD COLUMN source_format TEXT DEFAULT 'csv'; -- 'csv', 'xlsx' or 'xls'
+ALTER TABLE csv_imports ADD COLUMN sheet_name TEXT;
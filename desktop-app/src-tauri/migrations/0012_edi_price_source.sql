@@ -0,0 +1,36 @@
+-- Prices can now come from EDI 832 catalogs and 810 invoices. SQLite can't alter a CHECK,
+-- so the table is rebuilt; unit_price is a generated column and is recomputed rather than copied.
+
+CREATE TABLE local_current_prices_new (
+    price_id TEXT PRIMARY KEY,
+    restaurant_id TEXT NOT NULL,
+    catalog_product_id TEXT NOT NULL,
+    distributor_id TEXT NOT NULL,
+    case_price REAL NOT NULL,
+    total_preferred_units REAL NOT NULL,
+    unit_price REAL GENERATED ALWAYS AS (case_price / total_preferred_units) STORED,
+    effective_date DATE NOT NULL,
+    source_type TEXT NOT NULL CHECK (source_type IN ('csv_import', 'manual_entry', 'edi_832', 'edi_810')),
+    source_file_name TEXT,
+    source_file_hash TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(restaurant_id),
+    FOREIGN KEY (catalog_product_id) REFERENCES products(catalog_product_id),
+    FOREIGN KEY (distributor_id) REFERENCES distributors(distributor_id),
+    UNIQUE(restaurant_id, catalog_product_id, distributor_id, effective_date)
+);
+
+INSERT INTO local_current_prices_new (
+    price_id, restaurant_id, catalog_product_id, distributor_id, case_price, total_preferred_units,
+    effective_date, source_type, source_file_name, source_file_hash, created_at
+)
+SELECT
+    price_id, restaurant_id, catalog_product_id, distributor_id, case_price, total_preferred_units,
+    effective_date, source_type, source_file_name, source_file_hash, created_at
+FROM local_current_prices;
+
+DROP TABLE local_current_prices;
+ALTER TABLE local_current_prices_new RENAME TO local_current_prices;
+
+CREATE INDEX IF NOT EXISTS idx_local_prices_lookup ON local_current_prices(restaurant_id, catalog_product_id, distributor_id, effective_date DESC);
+CREATE INDEX IF NOT EXISTS idx_local_prices_date ON local_current_prices(restaurant_id, effective_date);
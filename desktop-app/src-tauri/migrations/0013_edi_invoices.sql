@@ -0,0 +1,37 @@
+-- Electronic invoices (EDI 810); their line prices are written as prices dated by the invoice
+CREATE TABLE IF NOT EXISTS edi_invoices (
+    invoice_id TEXT PRIMARY KEY,
+    import_id TEXT NOT NULL, -- csv_imports row that wrote the line prices
+    restaurant_id TEXT NOT NULL,
+    distributor_id TEXT NOT NULL,
+    invoice_number TEXT NOT NULL,
+    invoice_date DATE NOT NULL,
+    purchase_order_number TEXT,
+    seller_name TEXT,
+    ship_to_name TEXT,
+    total_amount REAL,
+    line_count INTEGER NOT NULL,
+    unmatched_count INTEGER NOT NULL, -- Lines whose item code has no distributor spec
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (import_id) REFERENCES csv_imports(import_id),
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(restaurant_id),
+    FOREIGN KEY (distributor_id) REFERENCES distributors(distributor_id),
+    UNIQUE(distributor_id, invoice_number)
+);
+
+CREATE TABLE IF NOT EXISTS edi_invoice_lines (
+    line_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    invoice_id TEXT NOT NULL,
+    line_number INTEGER NOT NULL,
+    item_code TEXT,
+    description TEXT,
+    quantity REAL,
+    unit_of_measure TEXT,
+    unit_price REAL,
+    pack_size TEXT,
+    catalog_product_id TEXT, -- NULL when no distributor spec has this item code
+    FOREIGN KEY (invoice_id) REFERENCES edi_invoices(invoice_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_edi_invoices_restaurant ON edi_invoices(restaurant_id, invoice_date DESC);
+CREATE INDEX IF NOT EXISTS idx_edi_invoice_lines_invoice ON edi_invoice_lines(invoice_id);
@@ -0,0 +1,10 @@
+-- Distributor export presets, reloaded from the preset JSON files at startup
+CREATE TABLE IF NOT EXISTS import_presets (
+    preset_id TEXT PRIMARY KEY,
+    distributor_code TEXT NOT NULL, -- Matches distributors.distributor_code
+    definition TEXT NOT NULL, -- JSON ImportPreset
+    source_file TEXT NOT NULL,
+    loaded_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_presets_distributor ON import_presets(distributor_code);
@@ -0,0 +1,6 @@
+-- Watched import folder. An empty folder turns the watcher off; an empty restaurant
+-- means the only restaurant on this machine.
+INSERT OR IGNORE INTO app_settings (key, value) VALUES
+    ('import_watch_folder', ''),
+    ('import_watch_restaurant_id', ''),
+    ('import_watch_interval_secs', '30');
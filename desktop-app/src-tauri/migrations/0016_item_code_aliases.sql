@@ -0,0 +1,14 @@
+-- Confirmed matches between how a distributor's file names an item and a synced
+-- distributor spec, so the next import of that file matches without asking again
+CREATE TABLE IF NOT EXISTS item_code_aliases (
+    alias_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    distributor_id TEXT NOT NULL,
+    alias_type TEXT NOT NULL CHECK (alias_type IN ('item_code', 'description')),
+    alias TEXT NOT NULL, -- The file's item code, or its normalized description when it has none
+    spec_id TEXT NOT NULL,
+    catalog_product_id TEXT NOT NULL,
+    confirmed_by TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (distributor_id) REFERENCES distributors(distributor_id),
+    UNIQUE(distributor_id, alias_type, alias)
+);
@@ -0,0 +1,49 @@
+-- Imported prices that look wrong against the product's recent prices are held here
+-- until someone approves or rejects them, instead of being written straight away
+
+CREATE TABLE IF NOT EXISTS quarantined_prices (
+    quarantine_id TEXT PRIMARY KEY,
+    import_id TEXT NOT NULL,
+    restaurant_id TEXT NOT NULL,
+    catalog_product_id TEXT NOT NULL,
+    distributor_id TEXT NOT NULL,
+    spec_id TEXT NOT NULL,
+    row_number INTEGER NOT NULL,
+    case_price REAL NOT NULL,
+    total_preferred_units REAL NOT NULL,
+    unit_price REAL NOT NULL,
+    reference_unit_price REAL, -- Median of the recent prices it was compared with
+    change_percent REAL,
+    anomaly_type TEXT NOT NULL CHECK (anomaly_type IN ('price_jump', 'price_drop', 'zero_price', 'pack_size_swing')),
+    reason TEXT NOT NULL,
+    effective_date DATE NOT NULL,
+    source_type TEXT NOT NULL,
+    source_file_name TEXT,
+    source_file_hash TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected')),
+    reviewed_by TEXT,
+    reviewed_at TIMESTAMP,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (import_id) REFERENCES csv_imports(import_id),
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(restaurant_id),
+    FOREIGN KEY (catalog_product_id) REFERENCES products(catalog_product_id),
+    FOREIGN KEY (distributor_id) REFERENCES distributors(distributor_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_quarantined_prices_pending ON quarantined_prices(restaurant_id, status);
+CREATE INDEX IF NOT EXISTS idx_quarantined_prices_import ON quarantined_prices(import_id);
+
+-- How far a unit price may move from recent prices before it is held, per product category.
+-- The 'default' row applies to categories without their own.
+CREATE TABLE IF NOT EXISTS price_anomaly_thresholds (
+    category_id TEXT PRIMARY KEY,
+    max_increase_percent REAL NOT NULL CHECK (max_increase_percent > 0),
+    max_decrease_percent REAL NOT NULL CHECK (max_decrease_percent > 0 AND max_decrease_percent <= 100),
+    history_days INTEGER NOT NULL DEFAULT 90 CHECK (history_days > 0),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO price_anomaly_thresholds (category_id, max_increase_percent, max_decrease_percent, history_days)
+VALUES ('default', 50.0, 40.0, 90);
+
+ALTER TABLE csv_imports ADD COLUMN quarantined_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,10 @@
+-- Catch-weight items (mostly proteins) are priced per pound while each case weighs a
+-- different amount. Their prices keep a nominal case weight in total_preferred_units and
+-- the case price that weight works out to, so unit_price is the quoted per-pound price.
+
+ALTER TABLE local_current_prices ADD COLUMN pricing_mode TEXT NOT NULL DEFAULT 'case'
+    CHECK (pricing_mode IN ('case', 'catch_weight'));
+
+-- Before-image for rollback, and the mode of prices held for review
+ALTER TABLE csv_import_price_changes ADD COLUMN previous_pricing_mode TEXT;
+ALTER TABLE quarantined_prices ADD COLUMN pricing_mode TEXT NOT NULL DEFAULT 'case';
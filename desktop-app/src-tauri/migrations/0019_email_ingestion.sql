@@ -0,0 +1,19 @@
+-- Distributor sales reps (synced from cloud). Their email addresses tell which distributor
+-- sent a price sheet that arrives as an email attachment.
+CREATE TABLE IF NOT EXISTS distributor_sales_contacts (
+    contact_id TEXT PRIMARY KEY,
+    restaurant_id TEXT, -- NULL for a contact shared by every restaurant
+    distributor_id TEXT NOT NULL,
+    contact_name TEXT NOT NULL,
+    email TEXT,
+    is_primary BOOLEAN NOT NULL DEFAULT 0,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    synced_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_sales_contacts_email ON distributor_sales_contacts(email COLLATE NOCASE);
+
+-- Message-ID of the email an imported attachment came from, so the same email isn't ingested twice
+ALTER TABLE csv_imports ADD COLUMN email_message_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_csv_imports_email ON csv_imports(restaurant_id, email_message_id);
@@ -0,0 +1,41 @@
+-- Which distributor a restaurant prefers for a product (synced from cloud, editable here).
+-- Without always_use_preferred the preference only breaks unit_price ties.
+CREATE TABLE IF NOT EXISTS product_preferences (
+    restaurant_id TEXT NOT NULL,
+    catalog_product_id TEXT NOT NULL,
+    preferred_distributor_id TEXT,
+    always_use_preferred BOOLEAN NOT NULL DEFAULT 0,
+    notes TEXT,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    synced_at TIMESTAMP, -- NULL until it has come down from the cloud
+    PRIMARY KEY (restaurant_id, catalog_product_id),
+    CHECK (always_use_preferred = 0 OR preferred_distributor_id IS NOT NULL)
+);
+
+-- Preference and substitution group edits are queued for upload. SQLite can't alter a CHECK,
+-- so the sync queue is rebuilt.
+CREATE TABLE pending_sync_events_new (
+    event_id TEXT PRIMARY KEY,
+    event_type TEXT NOT NULL CHECK (event_type IN ('price_upload', 'csv_import', 'preference_update', 'substitution_update')),
+    restaurant_id TEXT NOT NULL,
+    payload TEXT NOT NULL, -- JSON data to sync
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    scheduled_for TIMESTAMP NOT NULL, -- Next Monday 12:01 AM for prices, right away for preferences and groups
+    sync_attempts INTEGER DEFAULT 0,
+    last_attempt_at TIMESTAMP,
+    last_error TEXT,
+    FOREIGN KEY (restaurant_id) REFERENCES restaurants(restaurant_id)
+);
+
+INSERT INTO pending_sync_events_new (
+    event_id, event_type, restaurant_id, payload, created_at, scheduled_for, sync_attempts, last_attempt_at, last_error
+)
+SELECT
+    event_id, event_type, restaurant_id, payload, created_at, scheduled_for, sync_attempts, last_attempt_at, last_error
+FROM pending_sync_events;
+
+DROP TABLE pending_sync_events;
+ALTER TABLE pending_sync_events_new RENAME TO pending_sync_events;
+
+CREATE INDEX IF NOT EXISTS idx_pending_sync_scheduled ON pending_sync_events(scheduled_for, sync_attempts);
+CREATE INDEX IF NOT EXISTS idx_pending_sync_restaurant ON pending_sync_events(restaurant_id);
@@ -0,0 +1,23 @@
+-- Products a restaurant treats as interchangeable ("Tomatoes - Any Size"), synced from cloud.
+-- Every member shares the group's measurement_type so their unit prices compare.
+CREATE TABLE IF NOT EXISTS substitution_groups (
+    group_id TEXT PRIMARY KEY,
+    restaurant_id TEXT NOT NULL,
+    group_name TEXT NOT NULL,
+    measurement_type TEXT NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT 1,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    synced_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_substitution_groups_restaurant ON substitution_groups(restaurant_id);
+
+CREATE TABLE IF NOT EXISTS substitution_group_members (
+    group_id TEXT NOT NULL,
+    catalog_product_id TEXT NOT NULL, -- No foreign key: products are replaced by the catalog sync
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (group_id, catalog_product_id),
+    FOREIGN KEY (group_id) REFERENCES substitution_groups(group_id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_substitution_members_product ON substitution_group_members(catalog_product_id);
@@ -0,0 +1,21 @@
+-- How close two unit prices must be to count as a tie, per restaurant; a tie goes to the
+-- preferred distributor. Either a percentage of the lower unit price or an amount per
+-- preferred unit. Restaurants without a row only tie on equal prices.
+CREATE TABLE IF NOT EXISTS winner_settings (
+    restaurant_id TEXT PRIMARY KEY,
+    tie_tolerance_type TEXT NOT NULL DEFAULT 'percent' CHECK (tie_tolerance_type IN ('percent', 'amount')),
+    tie_tolerance_value REAL NOT NULL DEFAULT 0 CHECK (tie_tolerance_value >= 0),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- How old a price may get before it is stale, per restaurant and product category. The
+-- 'default' row applies to categories without their own; with no row prices never go stale.
+-- A stale price is either excluded from winning or competes flagged as stale.
+CREATE TABLE IF NOT EXISTS price_age_limits (
+    restaurant_id TEXT NOT NULL,
+    category_id TEXT NOT NULL,
+    max_age_days INTEGER NOT NULL CHECK (max_age_days > 0),
+    stale_action TEXT NOT NULL DEFAULT 'flag' CHECK (stale_action IN ('exclude', 'flag')),
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (restaurant_id, category_id)
+);
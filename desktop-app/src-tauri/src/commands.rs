@@ -1,9 +1,11 @@
+use crate::csv_import::ImportControl;
 use crate::db::models::*;
 use crate::state::{AppState, ProductSyncState, AuthState};
 use crate::sync;
-use tauri::{State, AppHandle};
+use tauri::{State, AppHandle, Emitter};
 use uuid::Uuid;
 use chrono::Utc;
+use std::path::PathBuf;
 
 // Basic ping command for testing IPC
 #[tauri::command]
@@ -134,6 +136,201 @@ pub async fn get_current_prices(
     }
 }
 
+// Current prices for a restaurant with the winning distributor for each product marked
+#[tauri::command]
+pub async fn get_winning_prices(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<PriceWithDetails>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::winning_prices(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to calculate winning prices: {}", e))
+}
+
+// Why a product's winning price won: every candidate, the deciding rule, and what was left out
+#[tauri::command]
+pub async fn explain_winner(
+    restaurant_id: String,
+    catalog_product_id: String,
+    state: State<'_, AppState>,
+) -> Result<WinnerExplanation, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::explain::explain_winner(&pool, &restaurant_id, &catalog_product_id).await
+        .map_err(|e| format!("Failed to explain winner: {}", e))
+}
+
+// A restaurant's tie tolerance for the winner calculation
+#[tauri::command]
+pub async fn get_winner_settings(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<WinnerSettings, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::settings::get_settings(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to get winner settings: {}", e))
+}
+
+// Set how close unit prices must be to tie: "percent" of the lower price or an "amount" per unit
+#[tauri::command]
+pub async fn save_tie_tolerance(
+    restaurant_id: String,
+    tie_tolerance_type: String,
+    tie_tolerance_value: f64,
+    state: State<'_, AppState>,
+) -> Result<WinnerSettings, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::settings::save_tie_tolerance(&pool, &restaurant_id, &tie_tolerance_type, tie_tolerance_value)
+        .await
+        .map_err(|e| format!("Failed to save tie tolerance: {}", e))
+}
+
+// Max price ages per product category, the default one first
+#[tauri::command]
+pub async fn get_price_age_limits(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<PriceAgeLimit>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::settings::list_age_limits(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to get price age limits: {}", e))
+}
+
+// Set the max price age for a category, or for "default", and whether to "exclude" or "flag" stale prices
+#[tauri::command]
+pub async fn save_price_age_limit(
+    restaurant_id: String,
+    category_id: String,
+    max_age_days: i32,
+    stale_action: String,
+    state: State<'_, AppState>,
+) -> Result<PriceAgeLimit, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::settings::save_age_limit(&pool, &restaurant_id, &category_id, max_age_days, &stale_action)
+        .await
+        .map_err(|e| format!("Failed to save price age limit: {}", e))
+}
+
+// Remove a category's max price age
+#[tauri::command]
+pub async fn delete_price_age_limit(
+    restaurant_id: String,
+    category_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::settings::delete_age_limit(&pool, &restaurant_id, &category_id).await
+        .map_err(|e| format!("Failed to delete price age limit: {}", e))
+}
+
+// Distributor preferences for a restaurant's products, flagging edits not uploaded yet
+#[tauri::command]
+pub async fn get_product_preferences(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<ProductPreference>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::preferences::list_preferences(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to get product preferences: {}", e))
+}
+
+// Set a product's preferred distributor; the edit is queued for upload
+#[tauri::command]
+pub async fn save_product_preference(
+    restaurant_id: String,
+    catalog_product_id: String,
+    preferred_distributor_id: Option<String>,
+    always_use_preferred: bool,
+    notes: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<ProductPreference, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::preferences::save_preference(
+        &pool,
+        &restaurant_id,
+        &catalog_product_id,
+        preferred_distributor_id.as_deref(),
+        always_use_preferred,
+        notes.as_deref(),
+    )
+    .await
+    .map_err(|e| format!("Failed to save product preference: {}", e))
+}
+
+// Remove a product's preference; the removal is queued for upload
+#[tauri::command]
+pub async fn delete_product_preference(
+    restaurant_id: String,
+    catalog_product_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::preferences::delete_preference(&pool, &restaurant_id, &catalog_product_id).await
+        .map_err(|e| format!("Failed to delete product preference: {}", e))
+}
+
+// A restaurant's substitution groups with their member products
+#[tauri::command]
+pub async fn get_substitution_groups(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SubstitutionGroupWithMembers>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::substitutions::list_groups(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to get substitution groups: {}", e))
+}
+
+// Add a product to a substitution group; it must share the group's measurement_type
+#[tauri::command]
+pub async fn add_substitution_group_member(
+    restaurant_id: String,
+    group_id: String,
+    catalog_product_id: String,
+    state: State<'_, AppState>,
+) -> Result<SubstitutionGroupWithMembers, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::substitutions::add_member(&pool, &restaurant_id, &group_id, &catalog_product_id).await
+        .map_err(|e| format!("Failed to add product to substitution group: {}", e))
+}
+
+// Take a product out of a substitution group
+#[tauri::command]
+pub async fn remove_substitution_group_member(
+    restaurant_id: String,
+    group_id: String,
+    catalog_product_id: String,
+    state: State<'_, AppState>,
+) -> Result<SubstitutionGroupWithMembers, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::pricing::substitutions::remove_member(&pool, &restaurant_id, &group_id, &catalog_product_id).await
+        .map_err(|e| format!("Failed to remove product from substitution group: {}", e))
+}
+
 // Get distributors
 #[tauri::command]
 pub async fn get_distributors(
@@ -155,6 +352,586 @@ pub async fn get_distributors(
     }
 }
 
+// Import a distributor CSV price sheet, emitting `import-progress` events as batches are written
+#[tauri::command]
+pub async fn import_csv_file(
+    app_handle: AppHandle,
+    file_path: String,
+    restaurant_id: String,
+    distributor_id: String,
+    options: Option<ImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<PriceImportResult, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let control = ImportControl::new(move |progress| {
+        app_handle.emit("import-progress", progress).ok();
+    });
+    state.add_running_import(control.clone()).await;
+    
+    let result = crate::csv_import::import_csv_file_with_control(
+        &pool,
+        &PathBuf::from(file_path),
+        &restaurant_id,
+        &distributor_id,
+        options.unwrap_or_default(),
+        &control,
+    )
+    .await;
+    state.remove_running_import(&control).await;
+    
+    result.map_err(|e| format!("Import failed: {}", e))
+}
+
+// Stop a running import; the rows it already wrote are undone
+#[tauri::command]
+pub async fn cancel_import(
+    import_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if state.cancel_running_import(&import_id).await {
+        Ok(())
+    } else {
+        Err(format!("Import {} is not running", import_id))
+    }
+}
+
+// Import an EDI 810 invoice file, recording each invoice and writing its line prices
+#[tauri::command]
+pub async fn import_edi_invoice(
+    file_path: String,
+    restaurant_id: String,
+    distributor_id: String,
+    options: Option<ImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<Vec<InvoiceImportResult>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::invoices::import_invoice_file(
+        &pool,
+        &PathBuf::from(file_path),
+        &restaurant_id,
+        &distributor_id,
+        options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| format!("Invoice import failed: {}", e))
+}
+
+// List received EDI invoices for a restaurant
+#[tauri::command]
+pub async fn get_edi_invoices(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<EdiInvoice>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::invoices::list_invoices(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to get invoices: {}", e))
+}
+
+// Lines of one EDI invoice, with the product each item code matched
+#[tauri::command]
+pub async fn get_edi_invoice_lines(
+    invoice_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<EdiInvoiceLine>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::invoices::list_invoice_lines(&pool, &invoice_id).await
+        .map_err(|e| format!("Failed to get invoice lines: {}", e))
+}
+
+// The watched import folder, its restaurant and how often it is scanned
+#[tauri::command]
+pub async fn get_import_watch_settings(
+    state: State<'_, AppState>,
+) -> Result<ImportWatchSettings, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::watcher::get_watch_settings(&pool).await
+        .map_err(|e| format!("Failed to get import folder settings: {}", e))
+}
+
+// Watch a folder for price files, or stop watching with no folder
+#[tauri::command]
+pub async fn set_import_watch_settings(
+    folder: Option<String>,
+    restaurant_id: Option<String>,
+    interval_secs: u64,
+    state: State<'_, AppState>,
+) -> Result<ImportWatchSettings, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::watcher::save_watch_settings(&pool, folder.as_deref(), restaurant_id.as_deref(), interval_secs)
+        .await
+        .map_err(|e| format!("Failed to save import folder settings: {}", e))
+}
+
+// Import the CSV and Excel attachments of a saved email (.eml) or mailbox export (mbox).
+// Without a distributor_id, each email's distributor is worked out from its sender.
+#[tauri::command]
+pub async fn import_email_file(
+    file_path: String,
+    restaurant_id: String,
+    distributor_id: Option<String>,
+    options: Option<ImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<Vec<EmailImportResult>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::email::import_email_file(
+        &pool,
+        &PathBuf::from(file_path),
+        &restaurant_id,
+        distributor_id.as_deref(),
+        options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| format!("Email import failed: {}", e))
+}
+
+// Distributor sales contacts for a restaurant, used to recognise emailed price sheets
+#[tauri::command]
+pub async fn get_distributor_sales_contacts(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<DistributorSalesContact>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::email::list_sales_contacts(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to get sales contacts: {}", e))
+}
+
+// Dry-run a CSV import and return what would change for each row
+#[tauri::command]
+pub async fn preview_csv_import(
+    file_path: String,
+    restaurant_id: String,
+    distributor_id: String,
+    options: Option<ImportOptions>,
+    state: State<'_, AppState>,
+) -> Result<ImportPreview, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::preview_csv_import(
+        &pool,
+        &PathBuf::from(file_path),
+        &restaurant_id,
+        &distributor_id,
+        options.unwrap_or_default(),
+    )
+    .await
+    .map_err(|e| format!("Preview failed: {}", e))
+}
+
+// List the rows an import rejected and why
+#[tauri::command]
+pub async fn get_import_errors(
+    import_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CsvImportError>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::journal::list_errors(&pool, &import_id).await
+        .map_err(|e| format!("Failed to get import errors: {}", e))
+}
+
+// Export an import's rejected rows as a CSV with a Reason column, so they can be fixed and re-imported
+#[tauri::command]
+pub async fn export_rejected_rows(
+    import_id: String,
+    output_path: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::journal::export_rejected_rows(&pool, &import_id, &PathBuf::from(output_path)).await
+        .map_err(|e| format!("Failed to export rejected rows: {}", e))
+}
+
+// Import history for a restaurant, newest first
+#[tauri::command]
+pub async fn get_import_history(
+    restaurant_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<CsvImport>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::history::list_imports(&pool, &restaurant_id).await
+        .map_err(|e| format!("Failed to get import history: {}", e))
+}
+
+// Undo a completed import, restoring the prices it overwrote
+#[tauri::command]
+pub async fn rollback_import(
+    import_id: String,
+    state: State<'_, AppState>,
+) -> Result<RollbackResult, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::rollback::rollback_import(&pool, &import_id).await
+        .map_err(|e| format!("Rollback failed: {}", e))
+}
+
+// Find an earlier import of the same file content, so the UI can offer to re-import
+#[tauri::command]
+pub async fn find_duplicate_import(
+    file_path: String,
+    state: State<'_, AppState>,
+) -> Result<Option<CsvImport>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::find_duplicate_import(&pool, &PathBuf::from(file_path))
+        .await
+        .map_err(|e| format!("Failed to check import history: {}", e))
+}
+
+// Suggest an effective date for a price sheet and explain where it came from
+#[tauri::command]
+pub async fn detect_effective_date(
+    file_path: String,
+) -> Result<EffectiveDateSuggestion, String> {
+    crate::csv_import::suggest_effective_date(&PathBuf::from(file_path))
+        .map_err(|e| format!("Failed to detect effective date: {}", e))
+}
+
+// Detect which columns hold item code, price, etc. before importing a CSV or Excel file
+#[tauri::command]
+pub async fn detect_csv_columns(
+    file_path: String,
+    distributor_id: Option<String>,
+    sheet: Option<String>,
+    header_row: Option<i32>,
+    state: State<'_, AppState>,
+) -> Result<CsvMappingPreview, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::preview_column_mapping(
+        &pool,
+        &PathBuf::from(file_path),
+        distributor_id.as_deref(),
+        sheet.as_deref(),
+        header_row,
+    )
+    .await
+    .map_err(|e| format!("Failed to read file headers: {}", e))
+}
+
+// Sheet names of an Excel workbook so the user can pick the one with prices
+#[tauri::command]
+pub async fn list_workbook_sheets(
+    file_path: String,
+) -> Result<Vec<String>, String> {
+    crate::csv_import::list_sheets(&PathBuf::from(file_path))
+        .map_err(|e| format!("Failed to read workbook: {}", e))
+}
+
+// List saved CSV column mappings for the Settings page
+#[tauri::command]
+pub async fn list_mapping_profiles(
+    state: State<'_, AppState>,
+) -> Result<Vec<CsvMappingProfile>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::profiles::list_profiles(&pool).await
+        .map_err(|e| format!("Failed to get mapping profiles: {}", e))
+}
+
+// Save or update a distributor's CSV column mapping, optionally pinning its delimiter and encoding
+#[tauri::command]
+pub async fn save_mapping_profile(
+    distributor_id: String,
+    mapping: CsvColumnMapping,
+    headers: Vec<String>,
+    delimiter: Option<String>,
+    encoding: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<CsvMappingProfile, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::profiles::save_profile(
+        &pool,
+        &distributor_id,
+        &mapping,
+        &headers,
+        delimiter.as_deref(),
+        encoding.as_deref(),
+    )
+    .await
+        .map_err(|e| format!("Failed to save mapping profile: {}", e))
+}
+
+// The delimiter and encoding used for CSV files without a distributor override
+#[tauri::command]
+pub async fn get_csv_dialect_settings(
+    state: State<'_, AppState>,
+) -> Result<CsvDialectSettings, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::dialect::get_dialect_settings(&pool).await
+        .map_err(|e| format!("Failed to get CSV settings: {}", e))
+}
+
+// Set the delimiter ("auto", ",", ";", "tab", "|") and encoding ("auto" or a label like "windows-1252")
+#[tauri::command]
+pub async fn save_csv_dialect_settings(
+    delimiter: String,
+    encoding: String,
+    state: State<'_, AppState>,
+) -> Result<CsvDialectSettings, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::dialect::save_dialect_settings(&pool, &delimiter, &encoding).await
+        .map_err(|e| format!("Failed to save CSV settings: {}", e))
+}
+
+// Delete a distributor's saved CSV column mapping
+#[tauri::command]
+pub async fn delete_mapping_profile(
+    distributor_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::profiles::delete_profile(&pool, &distributor_id).await
+        .map_err(|e| format!("Failed to delete mapping profile: {}", e))
+}
+
+// Built-in import presets for distributor exports
+#[tauri::command]
+pub async fn list_import_presets(
+    state: State<'_, AppState>,
+) -> Result<Vec<ImportPreset>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::presets::list_presets(&pool).await
+        .map_err(|e| format!("Failed to get import presets: {}", e))
+}
+
+// Re-read the preset files, picking up presets added since startup
+#[tauri::command]
+pub async fn reload_import_presets(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<ImportPreset>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let dirs = crate::csv_import::presets::preset_dirs(&app_handle);
+    crate::csv_import::presets::load_presets(&pool, &dirs).await
+        .map_err(|e| format!("Failed to load import presets: {}", e))?;
+    
+    crate::csv_import::presets::list_presets(&pool).await
+        .map_err(|e| format!("Failed to get import presets: {}", e))
+}
+
+// Pack sizes from imported files that disagree with the synced distributor specs
+#[tauri::command]
+pub async fn get_spec_change_candidates(
+    distributor_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SpecChangeCandidate>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::spec_changes::list_candidates(&pool, distributor_id.as_deref()).await
+        .map_err(|e| format!("Failed to get spec change candidates: {}", e))
+}
+
+// Stop flagging a pack size that has been reviewed
+#[tauri::command]
+pub async fn dismiss_spec_change_candidate(
+    candidate_id: i64,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::spec_changes::dismiss_candidate(&pool, candidate_id).await
+        .map_err(|e| format!("Failed to dismiss spec change candidate: {}", e))
+}
+
+// Rank products for a price line that didn't match by item code
+#[tauri::command]
+pub async fn suggest_product_matches(
+    distributor_id: String,
+    description: String,
+    pack_size: Option<String>,
+    unit: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ProductMatchCandidate>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    let pack = pack_size
+        .as_deref()
+        .and_then(|text| crate::csv_import::pack_size::parse_pack_size(text, unit.as_deref()));
+    let mut conn = pool.acquire().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::matching::suggest_matches(&mut conn, &distributor_id, &description, pack.as_ref()).await
+        .map_err(|e| format!("Failed to suggest product matches: {}", e))
+}
+
+// Remember the product a user picked for a line, so later imports match it automatically
+#[tauri::command]
+pub async fn confirm_product_match(
+    distributor_id: String,
+    item_code: Option<String>,
+    description: Option<String>,
+    spec_id: String,
+    state: State<'_, AppState>,
+) -> Result<ItemCodeAlias, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::matching::confirm_match(
+        &pool,
+        &distributor_id,
+        item_code.as_deref(),
+        description.as_deref(),
+        &spec_id,
+    )
+    .await
+    .map_err(|e| format!("Failed to confirm product match: {}", e))
+}
+
+// Confirmed matches remembered for a distributor
+#[tauri::command]
+pub async fn get_item_code_aliases(
+    distributor_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<ItemCodeAlias>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::matching::list_aliases(&pool, &distributor_id).await
+        .map_err(|e| format!("Failed to get item code aliases: {}", e))
+}
+
+// Forget a confirmed match
+#[tauri::command]
+pub async fn delete_item_code_alias(
+    alias_id: i64,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::matching::delete_alias(&pool, alias_id).await
+        .map_err(|e| format!("Failed to delete item code alias: {}", e))
+}
+
+// Prices imports held back as likely mistakes; pass status "pending" for the ones awaiting review
+#[tauri::command]
+pub async fn get_quarantined_prices(
+    restaurant_id: String,
+    status: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<QuarantinedPrice>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::quarantine::list_quarantined(&pool, &restaurant_id, status.as_deref()).await
+        .map_err(|e| format!("Failed to get quarantined prices: {}", e))
+}
+
+// Accept a held price: it is written and queued for upload like the rest of its import
+#[tauri::command]
+pub async fn approve_quarantined_price(
+    quarantine_id: String,
+    state: State<'_, AppState>,
+) -> Result<QuarantineReviewResult, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::quarantine::approve_price(&pool, &quarantine_id).await
+        .map_err(|e| format!("Failed to approve price: {}", e))
+}
+
+// Discard a held price, keeping the current one
+#[tauri::command]
+pub async fn reject_quarantined_price(
+    quarantine_id: String,
+    state: State<'_, AppState>,
+) -> Result<QuarantineReviewResult, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::quarantine::reject_price(&pool, &quarantine_id).await
+        .map_err(|e| format!("Failed to reject price: {}", e))
+}
+
+// Anomaly thresholds per product category, the default ones first
+#[tauri::command]
+pub async fn get_price_anomaly_thresholds(
+    state: State<'_, AppState>,
+) -> Result<Vec<PriceAnomalyThresholds>, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::quarantine::list_thresholds(&pool).await
+        .map_err(|e| format!("Failed to get price anomaly thresholds: {}", e))
+}
+
+// Set the anomaly thresholds for a category, or for "default"
+#[tauri::command]
+pub async fn save_price_anomaly_thresholds(
+    category_id: String,
+    max_increase_percent: f64,
+    max_decrease_percent: f64,
+    history_days: i32,
+    state: State<'_, AppState>,
+) -> Result<PriceAnomalyThresholds, String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::quarantine::save_thresholds(
+        &pool,
+        &category_id,
+        max_increase_percent,
+        max_decrease_percent,
+        history_days,
+    )
+    .await
+    .map_err(|e| format!("Failed to save price anomaly thresholds: {}", e))
+}
+
+// Make a category use the default thresholds again
+#[tauri::command]
+pub async fn delete_price_anomaly_thresholds(
+    category_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let pool = state.get_db().await
+        .map_err(|e| format!("Database error: {}", e))?;
+    
+    crate::csv_import::quarantine::delete_thresholds(&pool, &category_id).await
+        .map_err(|e| format!("Failed to delete price anomaly thresholds: {}", e))
+}
+
 // Initialize demo data (for development)
 #[tauri::command]
 pub async fn init_demo_data(
@@ -0,0 +1,289 @@
+use crate::db::{
+    get_app_setting,
+    models::{CsvDialectSettings, CsvFileFormat, CsvMappingProfile},
+    set_app_setting, DbPool,
+};
+use crate::error::AppError;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use std::collections::HashMap;
+
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+// Lines sampled when sniffing the delimiter, enough to reach the table below a preamble
+const SNIFF_LINES: usize = 50;
+
+// How a CSV file is encoded and split into fields
+#[derive(Debug, Clone, Copy)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub encoding: &'static Encoding,
+}
+
+impl CsvDialect {
+    // Work everything out from the file itself
+    pub fn sniff(contents: &[u8]) -> Self {
+        resolve(contents, None, None)
+    }
+
+    // Decode to UTF-8, dropping any BOM. A BOM also wins over the configured encoding.
+    pub fn decode(&self, contents: &[u8]) -> String {
+        let (text, _, _) = self.encoding.decode(contents);
+        text.into_owned()
+    }
+
+    pub fn file_format(&self) -> CsvFileFormat {
+        CsvFileFormat {
+            delimiter: (self.delimiter as char).to_string(),
+            quote: (self.quote as char).to_string(),
+            encoding: self.encoding.name().to_string(),
+        }
+    }
+}
+
+// Pick the dialect for a file: the distributor profile's override first, then the
+// csv_delimiter / csv_encoding app settings, and sniff whatever is left on "auto"
+pub async fn resolve_dialect(
+    pool: &DbPool,
+    contents: &[u8],
+    profile: Option<&CsvMappingProfile>,
+) -> Result<CsvDialect, AppError> {
+    let delimiter = match profile.and_then(|p| p.delimiter.clone()) {
+        Some(delimiter) => Some(delimiter),
+        None => get_app_setting(pool, "csv_delimiter").await?,
+    };
+    let encoding = match profile.and_then(|p| p.encoding.clone()) {
+        Some(encoding) => Some(encoding),
+        None => get_app_setting(pool, "csv_encoding").await?,
+    };
+
+    Ok(resolve(
+        contents,
+        delimiter.as_deref().and_then(parse_delimiter),
+        encoding.as_deref().and_then(parse_encoding),
+    ))
+}
+
+// The global delimiter and encoding settings, "auto" where they are sniffed
+pub async fn get_dialect_settings(pool: &DbPool) -> Result<CsvDialectSettings, AppError> {
+    let setting = |value: Option<String>| value.filter(|v| !v.trim().is_empty()).unwrap_or_else(|| "auto".to_string());
+
+    Ok(CsvDialectSettings {
+        delimiter: setting(get_app_setting(pool, "csv_delimiter").await?),
+        encoding: setting(get_app_setting(pool, "csv_encoding").await?),
+    })
+}
+
+// Set the delimiter and encoding for CSV files whose distributor profile doesn't pin them
+pub async fn save_dialect_settings(
+    pool: &DbPool,
+    delimiter: &str,
+    encoding: &str,
+) -> Result<CsvDialectSettings, AppError> {
+    if delimiter != "auto" && parse_delimiter(delimiter).is_none() {
+        return Err(AppError::Validation(format!("Unsupported CSV delimiter '{}'", delimiter)));
+    }
+    if encoding != "auto" && parse_encoding(encoding).is_none() {
+        return Err(AppError::Validation(format!("Unknown text encoding '{}'", encoding)));
+    }
+
+    set_app_setting(pool, "csv_delimiter", delimiter).await?;
+    set_app_setting(pool, "csv_encoding", encoding).await?;
+    get_dialect_settings(pool).await
+}
+
+fn resolve(contents: &[u8], delimiter: Option<u8>, encoding: Option<&'static Encoding>) -> CsvDialect {
+    let encoding = encoding.unwrap_or_else(|| sniff_encoding(contents));
+    let (text, _, _) = encoding.decode(contents);
+    let quote = sniff_quote(&text);
+
+    CsvDialect {
+        delimiter: delimiter.unwrap_or_else(|| sniff_delimiter(&text, quote)),
+        quote,
+        encoding,
+    }
+}
+
+// "auto" and blank mean sniff; "tab" and "\t" are accepted for tab-separated files
+pub fn parse_delimiter(value: &str) -> Option<u8> {
+    match value {
+        "tab" | "\\t" | "\t" => Some(b'\t'),
+        "auto" | "" => None,
+        other if other.len() == 1 && other.is_ascii() => Some(other.as_bytes()[0]),
+        _ => None,
+    }
+}
+
+// Any WHATWG label ("UTF-8", "windows-1252", "latin1", "UTF-16LE"), or "auto"
+pub fn parse_encoding(value: &str) -> Option<&'static Encoding> {
+    match value.trim() {
+        "auto" | "" => None,
+        label => Encoding::for_label(label.as_bytes()),
+    }
+}
+
+// BOM first, then UTF-16 without a BOM (every other byte zero), then UTF-8 if it
+// decodes cleanly, otherwise Windows-1252 which is what Excel on Windows writes
+fn sniff_encoding(contents: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(contents) {
+        return encoding;
+    }
+
+    let sample = &contents[..contents.len().min(4096)];
+    let pairs = sample.len() / 2;
+    if pairs > 0 {
+        let zero_at = |offset: usize| {
+            sample.iter().skip(offset).step_by(2).filter(|b| **b == 0).count()
+        };
+        if zero_at(1) * 10 > pairs * 3 {
+            return UTF_16LE;
+        }
+        if zero_at(0) * 10 > pairs * 3 {
+            return UTF_16BE;
+        }
+    }
+
+    if std::str::from_utf8(contents).is_ok() {
+        UTF_8
+    } else {
+        WINDOWS_1252
+    }
+}
+
+// Double quotes unless fields are clearly wrapped in single quotes
+fn sniff_quote(text: &str) -> u8 {
+    let mut double = 0;
+    let mut single = 0;
+    let mut at_field_start = true;
+
+    for c in text.chars().take(64 * 1024) {
+        if at_field_start {
+            match c {
+                '"' => double += 1,
+                '\'' => single += 1,
+                _ => {}
+            }
+        }
+        at_field_start = c == '\n' || (c.is_ascii() && DELIMITERS.contains(&(c as u8)));
+    }
+
+    if single > double {
+        b'\''
+    } else {
+        b'"'
+    }
+}
+
+// The delimiter that splits the most sampled lines into the same number of fields. Each
+// delimiter's field count is the one most lines share, and counting starts at the first
+// line that has it (the header), so a title or "Prices effective" line above doesn't count.
+fn sniff_delimiter(text: &str, quote: u8) -> u8 {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+
+    let mut best = (b',', 0, 0);
+    for delimiter in DELIMITERS {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| count_outside_quotes(line, delimiter, quote))
+            .collect();
+
+        let Some(common) = most_common_count(&counts) else {
+            continue;
+        };
+        let header = counts.iter().position(|count| *count == common).unwrap_or_default();
+
+        let consistent = counts[header..].iter().filter(|count| **count == common).count();
+        if (consistent, common) > (best.1, best.2) {
+            best = (delimiter, consistent, common);
+        }
+    }
+
+    best.0
+}
+
+// The non-zero count most lines share, the larger one on a tie
+fn most_common_count(counts: &[usize]) -> Option<usize> {
+    let mut frequency: HashMap<usize, usize> = HashMap::new();
+    for count in counts.iter().filter(|count| **count > 0) {
+        *frequency.entry(*count).or_default() += 1;
+    }
+
+    frequency
+        .into_iter()
+        .max_by_key(|(count, lines)| (*lines, *count))
+        .map(|(count, _)| count)
+}
+
+fn count_outside_quotes(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+
+    for byte in line.bytes() {
+        if byte == quote {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delimiter(text: &str) -> char {
+        CsvDialect::sniff(text.as_bytes()).delimiter as char
+    }
+
+    #[test]
+    fn sniffs_common_delimiters() {
+        assert_eq!(delimiter("Item,Description,Price\n1,Tomato,18.50\n2,Onion,20.00\n"), ',');
+        assert_eq!(delimiter("Item;Description;Price\n1;Tomato;18,50\n2;Onion;20,00\n"), ';');
+        assert_eq!(delimiter("Item\tDescription\tPrice\n1\tTomato\t18.50\n"), '\t');
+        assert_eq!(delimiter("Item|Description|Price\n1|Tomato|18.50\n"), '|');
+    }
+
+    #[test]
+    fn ignores_title_lines_above_the_header() {
+        let text = "Sysco Price List\nPrices effective 10/05/2026, all items\n\
+                    Item;Description;Pack;Price\n1;Tomato;1/25 LB;18,50\n2;Onion;1/50 LB;20,00\n3;Leeks, trimmed;12 CT;15,00\n";
+        assert_eq!(delimiter(text), ';');
+
+        let text = "Price List\nItem\tDescription\tPrice\n1\tTomato\t18.50\n2\tOnion\t20.00\n";
+        assert_eq!(delimiter(text), '\t');
+    }
+
+    #[test]
+    fn ignores_delimiters_inside_quotes() {
+        let text = "Item;Description;Price\n1;\"Tomato, 6x6\";18,50\n2;\"Onion, yellow\";20,00\n";
+        assert_eq!(delimiter(text), ';');
+    }
+
+    #[test]
+    fn sniffs_encodings() {
+        let utf16: Vec<u8> = "Item,Price\n".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(CsvDialect::sniff(&utf16).encoding, UTF_16LE);
+        assert_eq!(CsvDialect::sniff(b"\xEF\xBB\xBFItem,Price\n1,2\n").encoding, UTF_8);
+        assert_eq!(CsvDialect::sniff(b"Item,Description\n1,Jalape\xF1o\n").encoding, WINDOWS_1252);
+
+        let dialect = CsvDialect::sniff(b"\xFF\xFEI\0t\0e\0m\0;\0P\0\n\0");
+        assert_eq!(dialect.encoding, UTF_16LE);
+        assert_eq!(dialect.decode(b"\xFF\xFEI\0t\0e\0m\0;\0P\0\n\0"), "Item;P\n");
+        assert_eq!(dialect.delimiter, b';');
+    }
+
+    #[test]
+    fn parses_settings() {
+        assert_eq!(parse_delimiter("tab"), Some(b'\t'));
+        assert_eq!(parse_delimiter("auto"), None);
+        assert_eq!(parse_delimiter(";"), Some(b';'));
+        assert_eq!(parse_encoding("latin1"), Some(WINDOWS_1252));
+        assert_eq!(parse_encoding("auto"), None);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::db::models::ImportErrorType;
+use crate::error::AppError;
+use chrono::NaiveDate;
+
+use super::{item_code, parse_date, parse_interchange, unit_name, Interchange};
+use crate::csv_import::units::{canonical_unit, measurement_type_of};
+use crate::csv_import::{parse_price, PriceRow, RowParseError};
+
+// Column names used for 832 items in the error journal and rejected-row exports
+pub const CATALOG_HEADERS: [&str; 5] = ["Item Code", "Description", "Pack/Size", "Unit", "Case Price"];
+
+// The price lines of an X12 832 price/sales catalog
+#[derive(Debug, Clone)]
+pub struct PriceCatalog {
+    pub sender_id: String,
+    pub document_date: Option<(NaiveDate, String)>, // Catalog-level date and where it came from
+    pub rows: Vec<PriceRow>,
+    pub errors: Vec<RowParseError>,
+}
+
+// One LIN loop while it is being read
+#[derive(Debug, Default)]
+struct CatalogItem {
+    number: i32,
+    item_code: Option<String>,
+    description: Option<String>,
+    pack_size: Option<String>,
+    unit: Option<String>,
+    prices: Vec<(String, Option<String>)>, // CTP03 price and CTP05 unit of measure
+}
+
+// Read item code, description, pack/size and case price from each LIN/PID/PO4/CTP loop
+pub fn parse_price_catalog(text: &str) -> Result<PriceCatalog, AppError> {
+    let interchange = parse_interchange(text)?;
+
+    match interchange.segment("ST").and_then(|st| st.element(1)) {
+        Some("832") => {}
+        Some(other) => {
+            return Err(AppError::Validation(format!(
+                "EDI transaction set {} is not a price catalog (832)",
+                other
+            )))
+        }
+        None => return Err(AppError::Validation("EDI file has no ST transaction set header".to_string())),
+    }
+
+    let mut catalog = PriceCatalog {
+        sender_id: interchange.sender_id.clone(),
+        document_date: document_date(&interchange),
+        rows: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    let mut item: Option<CatalogItem> = None;
+    let mut item_count = 0;
+
+    for segment in &interchange.segments {
+        match segment.id.as_str() {
+            "LIN" => {
+                if let Some(done) = item.take() {
+                    finish_item(&mut catalog, done);
+                }
+                item_count += 1;
+                item = Some(CatalogItem {
+                    number: item_count,
+                    item_code: item_code(segment, 2),
+                    ..Default::default()
+                });
+            }
+            "PID" => {
+                if let Some(item) = item.as_mut().filter(|item| item.description.is_none()) {
+                    item.description = segment.element(5).map(str::to_string);
+                }
+            }
+            "PO4" => {
+                if let Some(item) = item.as_mut() {
+                    let unit = segment.element(3).map(unit_name);
+                    item.pack_size = segment.element(2).map(|size| {
+                        let packs = segment.element(1).unwrap_or("1");
+                        match &unit {
+                            Some(unit) => format!("{}/{} {}", packs, size, unit),
+                            None => format!("{}/{}", packs, size),
+                        }
+                    });
+                    item.unit = unit;
+                }
+            }
+            "CTP" => {
+                if let (Some(item), Some(price)) = (item.as_mut(), segment.element(3)) {
+                    let unit = segment
+                        .element(5)
+                        .map(|uom| interchange.first_component(uom).to_string());
+                    item.prices.push((price.to_string(), unit));
+                }
+            }
+            "SE" => {
+                if let Some(done) = item.take() {
+                    finish_item(&mut catalog, done);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(done) = item.take() {
+        finish_item(&mut catalog, done);
+    }
+
+    Ok(catalog)
+}
+
+// DTM*007 (effective) before the first item, otherwise the functional group date
+fn document_date(interchange: &Interchange) -> Option<(NaiveDate, String)> {
+    let header = interchange.segments.iter().take_while(|segment| segment.id != "LIN");
+
+    for segment in header {
+        if segment.id == "DTM" && segment.element(1) == Some("007") {
+            if let Some(date) = segment.element(2).and_then(parse_date) {
+                return Some((date, "Catalog effective date (DTM*007)".to_string()));
+            }
+        }
+    }
+
+    interchange
+        .segment("GS")
+        .and_then(|gs| gs.element(4))
+        .and_then(parse_date)
+        .map(|date| (date, "Catalog creation date (GS04)".to_string()))
+}
+
+// Turn a finished LIN loop into a price row, or a row error when it has no item code or case price
+fn finish_item(catalog: &mut PriceCatalog, item: CatalogItem) {
+    // Prices per case, or with no unit given. Without one, a per-pound price is taken as a
+    // catch-weight price; per-each prices are not case prices.
+    let (case_price, price_unit) = match item
+        .prices
+        .iter()
+        .find(|(_, unit)| matches!(unit.as_deref(), None | Some("CA") | Some("CS")))
+    {
+        Some((price, _)) => (Some(price.clone()), None),
+        None => item
+            .prices
+            .iter()
+            .find_map(|(price, unit)| {
+                let unit = unit_name(unit.as_deref()?);
+                (measurement_type_of(&unit) == Some("weight"))
+                    .then(|| (Some(price.clone()), Some(canonical_unit(&unit))))
+            })
+            .unwrap_or((None, None)),
+    };
+
+    let raw = vec![
+        item.item_code.clone().unwrap_or_default(),
+        item.description.clone().unwrap_or_default(),
+        item.pack_size.clone().unwrap_or_default(),
+        item.unit.clone().unwrap_or_default(),
+        case_price.clone().unwrap_or_default(),
+    ];
+    let reject = |error_type, message: String| RowParseError {
+        row_number: item.number,
+        error_type,
+        message,
+        raw: raw.clone(),
+    };
+
+    let Some(item_code) = item.item_code.clone() else {
+        catalog.errors.push(reject(ImportErrorType::Validation, "LIN segment has no item number".to_string()));
+        return;
+    };
+
+    let case_price = match case_price.as_deref().map(parse_price) {
+        Some(Ok(price)) => price,
+        Some(Err(message)) => {
+            catalog.errors.push(reject(ImportErrorType::Parsing, message));
+            return;
+        }
+        None if !item.prices.is_empty() => {
+            let units: Vec<&str> = item.prices.iter().filter_map(|(_, unit)| unit.as_deref()).collect();
+            catalog.errors.push(reject(
+                ImportErrorType::Validation,
+                format!("no case price, only prices per {}", units.join(", ")),
+            ));
+            return;
+        }
+        None => {
+            catalog.errors.push(reject(ImportErrorType::Validation, "missing case price (CTP)".to_string()));
+            return;
+        }
+    };
+
+    catalog.rows.push(PriceRow {
+        row_number: item.number,
+        item_code: Some(item_code),
+        description: item.description,
+        case_price,
+        pack_size: item.pack_size,
+        unit: item.unit,
+        price_unit,
+        raw,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISA: &str = "ISA*00*          *00*          *ZZ*SYSCO          *ZZ*REST           *260914*1200*U*00401*000000001*0*P*>~\n";
+
+    fn catalog(body: &str) -> PriceCatalog {
+        parse_price_catalog(&format!("{}GS*SC*SYSCO*REST*20260914*1200*1*X*004010~\nST*832*0001~\n{}SE*9*0001~\n", ISA, body))
+            .unwrap()
+    }
+
+    #[test]
+    fn reads_items_and_case_prices() {
+        let catalog = catalog(
+            "DTM*007*20260915~\nLIN**UP*0001*VN*111~\nPID*F*08***TOMATOES 6X6~\nPO4*1*25*LB~\nCTP**RES*1.10*1*LB~\nCTP**RES*18.50*1*CA~\n\
+             LIN**VN*222~\nPO4*4*1*GA~\nCTP**RES*32.00~\n",
+        );
+
+        assert_eq!(catalog.sender_id, "SYSCO");
+        assert_eq!(catalog.document_date.map(|(date, _)| date), NaiveDate::from_ymd_opt(2026, 9, 15));
+        assert!(catalog.errors.is_empty());
+        assert_eq!(catalog.rows.len(), 2);
+
+        let tomatoes = &catalog.rows[0];
+        assert_eq!(tomatoes.item_code.as_deref(), Some("111"));
+        assert_eq!(tomatoes.description.as_deref(), Some("TOMATOES 6X6"));
+        assert_eq!(tomatoes.pack_size.as_deref(), Some("1/25 LB"));
+        assert_eq!(tomatoes.case_price, 18.50);
+        assert_eq!(tomatoes.price_unit, None);
+
+        let oil = &catalog.rows[1];
+        assert_eq!(oil.pack_size.as_deref(), Some("4/1 GAL"));
+        assert_eq!(oil.case_price, 32.00);
+    }
+
+    #[test]
+    fn takes_a_per_pound_price_as_catch_weight() {
+        let catalog = catalog("LIN**VN*333~\nPO4*1*40*LB~\nCTP**RES*4.25*1*LB~\n");
+        assert_eq!(catalog.rows[0].case_price, 4.25);
+        assert_eq!(catalog.rows[0].price_unit.as_deref(), Some("lb"));
+    }
+
+    #[test]
+    fn rejects_items_without_a_case_price() {
+        let catalog = catalog("LIN**VN*444~\nCTP**RES*0.50*1*EA~\nLIN**VN*555~\nLIN*~\nCTP**RES*5~\n");
+        assert!(catalog.rows.is_empty());
+
+        let messages: Vec<&str> = catalog.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["no case price, only prices per EA", "missing case price (CTP)", "LIN segment has no item number"]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_group_date() {
+        let catalog = catalog("LIN**VN*111~\nCTP**RES*18.50~\n");
+        assert_eq!(catalog.document_date.map(|(date, _)| date), NaiveDate::from_ymd_opt(2026, 9, 14));
+    }
+
+    #[test]
+    fn refuses_other_transaction_sets() {
+        let text = format!("{}GS*IN*SYSCO*REST*20260914*1200*1*X*004010~\nST*810*0001~\n", ISA);
+        assert!(parse_price_catalog(&text).is_err());
+    }
+}
@@ -0,0 +1,236 @@
+use crate::error::AppError;
+use chrono::NaiveDate;
+
+use super::{item_code, parse_date, parse_interchange, unit_name, Segment};
+use crate::csv_import::units::{canonical_unit, measurement_type_of};
+
+// Column names used for 810 lines in the error journal and rejected-row exports
+pub const INVOICE_HEADERS: [&str; 6] = ["Line", "Item Code", "Description", "Quantity", "Unit", "Unit Price"];
+
+// One invoice (ST..SE) of an X12 810 interchange
+#[derive(Debug, Clone)]
+pub struct Invoice {
+    pub invoice_number: String,
+    pub invoice_date: NaiveDate,
+    pub purchase_order_number: Option<String>,
+    pub seller_name: Option<String>,
+    pub ship_to_name: Option<String>,
+    pub total_amount: Option<f64>,
+    pub lines: Vec<InvoiceLine>,
+    pub document: String, // The invoice's own segments, hashed to recognise it when re-sent
+}
+
+#[derive(Debug, Clone)]
+pub struct InvoiceLine {
+    pub line_number: i32,
+    pub item_code: Option<String>,
+    pub description: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit_of_measure: Option<String>, // Our unit name, e.g. "CS" or "LB"
+    pub unit_price: Option<String>, // As written, parsed by the import
+    pub pack_size: Option<String>,
+}
+
+impl InvoiceLine {
+    // The line's values in INVOICE_HEADERS order
+    pub fn raw(&self) -> Vec<String> {
+        vec![
+            self.line_number.to_string(),
+            self.item_code.clone().unwrap_or_default(),
+            self.description.clone().unwrap_or_default(),
+            self.quantity.map(|q| q.to_string()).unwrap_or_default(),
+            self.unit_of_measure.clone().unwrap_or_default(),
+            self.unit_price.clone().unwrap_or_default(),
+        ]
+    }
+
+    // Invoiced by the case, so the unit price is a case price
+    pub fn is_case_priced(&self) -> bool {
+        matches!(self.unit_of_measure.as_deref(), None | Some("CS"))
+    }
+
+    // The weight unit of a line invoiced by weight, whose unit price is a catch-weight price
+    pub fn catch_weight_unit(&self) -> Option<String> {
+        let unit = self.unit_of_measure.as_deref()?;
+        (measurement_type_of(unit) == Some("weight")).then(|| canonical_unit(unit))
+    }
+}
+
+// Read each invoice's BIG header, N1 parties, IT1 lines with their PID/PO4 and the TDS total
+pub fn parse_invoices(text: &str) -> Result<Vec<Invoice>, AppError> {
+    let interchange = parse_interchange(text)?;
+    let mut invoices = Vec::new();
+    let mut current: Option<Vec<&Segment>> = None;
+
+    for segment in &interchange.segments {
+        match segment.id.as_str() {
+            "ST" => {
+                if segment.element(1) != Some("810") {
+                    return Err(AppError::Validation(format!(
+                        "EDI transaction set {} is not an invoice (810)",
+                        segment.element(1).unwrap_or("?")
+                    )));
+                }
+                current = Some(vec![segment]);
+            }
+            "SE" => {
+                if let Some(mut segments) = current.take() {
+                    segments.push(segment);
+                    invoices.push(read_invoice(&segments)?);
+                }
+            }
+            _ => {
+                if let Some(segments) = current.as_mut() {
+                    segments.push(segment);
+                }
+            }
+        }
+    }
+
+    if invoices.is_empty() {
+        return Err(AppError::Validation("EDI file contains no invoices".to_string()));
+    }
+
+    Ok(invoices)
+}
+
+fn read_invoice(segments: &[&Segment]) -> Result<Invoice, AppError> {
+    let big = segments
+        .iter()
+        .find(|segment| segment.id == "BIG")
+        .ok_or_else(|| AppError::Validation("EDI invoice has no BIG header".to_string()))?;
+
+    let invoice_number = big
+        .element(2)
+        .ok_or_else(|| AppError::Validation("EDI invoice has no invoice number (BIG02)".to_string()))?
+        .to_string();
+    let invoice_date = big.element(1).and_then(parse_date).ok_or_else(|| {
+        AppError::Validation(format!("EDI invoice {} has no valid invoice date (BIG01)", invoice_number))
+    })?;
+
+    let party = |codes: &[&str]| {
+        segments
+            .iter()
+            .find(|segment| segment.id == "N1" && segment.element(1).is_some_and(|code| codes.contains(&code)))
+            .and_then(|n1| n1.element(2))
+            .map(str::to_string)
+    };
+
+    // TDS01 is the invoice total in cents, without a decimal point
+    let total_amount = segments
+        .iter()
+        .find(|segment| segment.id == "TDS")
+        .and_then(|tds| tds.element(1))
+        .and_then(|amount| amount.parse::<f64>().ok())
+        .map(|cents| cents / 100.0);
+
+    let mut lines: Vec<InvoiceLine> = Vec::new();
+    for segment in segments {
+        match segment.id.as_str() {
+            "IT1" => lines.push(InvoiceLine {
+                line_number: segment
+                    .element(1)
+                    .and_then(|n| n.parse().ok())
+                    .unwrap_or(lines.len() as i32 + 1),
+                item_code: item_code(segment, 6),
+                description: None,
+                quantity: segment.element(2).and_then(|q| q.parse().ok()),
+                unit_of_measure: segment.element(3).map(unit_name),
+                unit_price: segment.element(4).map(str::to_string),
+                pack_size: None,
+            }),
+            "PID" => {
+                if let Some(line) = lines.last_mut().filter(|line| line.description.is_none()) {
+                    line.description = segment.element(5).map(str::to_string);
+                }
+            }
+            "PO4" => {
+                if let Some(line) = lines.last_mut() {
+                    line.pack_size = segment.element(2).map(|size| {
+                        let packs = segment.element(1).unwrap_or("1");
+                        match segment.element(3).map(unit_name) {
+                            Some(unit) => format!("{}/{} {}", packs, size, unit),
+                            None => format!("{}/{}", packs, size),
+                        }
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let document = segments
+        .iter()
+        .map(|segment| format!("{}*{}", segment.id, segment.elements.join("*")))
+        .collect::<Vec<_>>()
+        .join("~\n");
+
+    Ok(Invoice {
+        invoice_number,
+        invoice_date,
+        purchase_order_number: big.element(4).map(str::to_string),
+        seller_name: party(&["SE", "VN", "RE"]),
+        ship_to_name: party(&["ST", "BT"]),
+        total_amount,
+        lines,
+        document,
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // Interchange and group headers for wrapping test invoices; also used by the invoice import tests
+    pub(crate) const ISA: &str = "ISA*00*          *00*          *ZZ*SYSCO          *ZZ*REST           *260914*1200*U*00401*000000002*0*P*>~\nGS*IN*SYSCO*REST*20260920*1200*2*X*004010~\n";
+
+    #[test]
+    fn reads_headers_parties_lines_and_totals() {
+        let text = format!(
+            "{}ST*810*0001~\nBIG*20260918*INV100**PO55~\nN1*ST*Main Street Bistro~\nN1*SE*Sysco Boston~\n\
+             IT1*1*2*CA*19.25**UP*0001*VN*111~\nPID*F****TOMATOES~\nPO4*1*25*LB~\nIT1*2*10.5*LB*0.45**VN*222~\n\
+             TDS*4322~\nSE*10*0001~\nST*810*0002~\nBIG*20260919*INV101~\nIT1**1*EA*3.00**VN*333~\nSE*4*0002~\n",
+            ISA
+        );
+        let invoices = parse_invoices(&text).unwrap();
+        assert_eq!(invoices.len(), 2);
+
+        let invoice = &invoices[0];
+        assert_eq!(invoice.invoice_number, "INV100");
+        assert_eq!(invoice.invoice_date, NaiveDate::from_ymd_opt(2026, 9, 18).unwrap());
+        assert_eq!(invoice.purchase_order_number.as_deref(), Some("PO55"));
+        assert_eq!(invoice.seller_name.as_deref(), Some("Sysco Boston"));
+        assert_eq!(invoice.ship_to_name.as_deref(), Some("Main Street Bistro"));
+        assert_eq!(invoice.total_amount, Some(43.22));
+
+        let tomatoes = &invoice.lines[0];
+        assert_eq!(tomatoes.item_code.as_deref(), Some("111"));
+        assert_eq!(tomatoes.description.as_deref(), Some("TOMATOES"));
+        assert_eq!(tomatoes.pack_size.as_deref(), Some("1/25 LB"));
+        assert_eq!(tomatoes.unit_price.as_deref(), Some("19.25"));
+        assert!(tomatoes.is_case_priced());
+        assert_eq!(tomatoes.catch_weight_unit(), None);
+
+        let onions = &invoice.lines[1];
+        assert_eq!(onions.quantity, Some(10.5));
+        assert!(!onions.is_case_priced());
+        assert_eq!(onions.catch_weight_unit().as_deref(), Some("lb"));
+
+        let each = &invoices[1].lines[0];
+        assert_eq!(each.line_number, 1);
+        assert!(!each.is_case_priced());
+        assert_eq!(each.catch_weight_unit(), None);
+    }
+
+    #[test]
+    fn rejects_invoices_without_a_number_or_date() {
+        let without_date = format!("{}ST*810*0001~\nBIG**INV100~\nSE*3*0001~\n", ISA);
+        assert!(parse_invoices(&without_date).is_err());
+
+        let without_big = format!("{}ST*810*0001~\nIT1*1*2*CA*19.25**VN*111~\nSE*3*0001~\n", ISA);
+        assert!(parse_invoices(&without_big).is_err());
+
+        let catalog = format!("{}ST*832*0001~\nSE*2*0001~\n", ISA);
+        assert!(parse_invoices(&catalog).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::error::AppError;
+use chrono::NaiveDate;
+
+pub mod catalog;
+pub mod invoice;
+
+// Product ID qualifiers in the order we trust them as the distributor's item code:
+// vendor item number, stock number, vendor part number, buyer part/item number, UPC
+const ITEM_CODE_QUALIFIERS: [&str; 7] = ["VN", "SK", "VP", "BP", "IN", "UP", "UK"];
+
+// One X12 segment, e.g. "PO4*1*25*LB" -> id "PO4", elements ["1", "25", "LB"]
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub id: String,
+    pub elements: Vec<String>,
+    pub position: usize, // 1-based position in the interchange
+}
+
+impl Segment {
+    // Element by its X12 number (LIN03 is element(3)), None when absent or blank
+    pub fn element(&self, number: usize) -> Option<&str> {
+        self.elements
+            .get(number.checked_sub(1)?)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    }
+}
+
+// An X12 interchange split into segments using the separators its ISA header declares
+#[derive(Debug, Clone)]
+pub struct Interchange {
+    pub sender_id: String,
+    pub component_separator: char,
+    pub segments: Vec<Segment>,
+}
+
+impl Interchange {
+    // The first component of a composite element, e.g. "CA" from "CA>1"
+    pub fn first_component<'a>(&self, value: &'a str) -> &'a str {
+        value.split(self.component_separator).next().unwrap_or(value).trim()
+    }
+
+    pub fn segment(&self, id: &str) -> Option<&Segment> {
+        self.segments.iter().find(|segment| segment.id == id)
+    }
+}
+
+// Whether a file looks like an X12 interchange
+pub fn is_interchange(contents: &[u8]) -> bool {
+    let contents = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+    let start = contents.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(contents.len());
+    contents[start..].starts_with(b"ISA")
+}
+
+// The transaction set of the interchange's first ST segment, e.g. "832" or "810"
+pub fn transaction_set(contents: &[u8]) -> Option<String> {
+    let interchange = parse_interchange(&String::from_utf8_lossy(contents)).ok()?;
+    interchange.segment("ST")?.element(1).map(str::to_string)
+}
+
+// Split an interchange into segments. The element separator is the character after "ISA",
+// and the two characters after the 16th element are the component separator and the
+// segment terminator, so both fixed-width and unpadded ISA headers are handled.
+pub fn parse_interchange(text: &str) -> Result<Interchange, AppError> {
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if !text.starts_with("ISA") {
+        return Err(AppError::Validation("Not an EDI X12 file: missing ISA header".to_string()));
+    }
+
+    let invalid = || AppError::Validation("EDI ISA header is incomplete".to_string());
+    let element_separator = text[3..].chars().next().ok_or_else(invalid)?;
+
+    let (isa16_start, _) = text
+        .match_indices(element_separator)
+        .nth(15)
+        .ok_or_else(invalid)?;
+    let mut trailing = text[isa16_start + element_separator.len_utf8()..].chars();
+    let component_separator = trailing.next().ok_or_else(invalid)?;
+    let segment_terminator = trailing.next().ok_or_else(invalid)?;
+
+    let segments: Vec<Segment> = text
+        .split(segment_terminator)
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .enumerate()
+        .map(|(index, segment)| {
+            let mut parts = segment.split(element_separator);
+            Segment {
+                id: parts.next().unwrap_or_default().trim().to_string(),
+                elements: parts.map(str::to_string).collect(),
+                position: index + 1,
+            }
+        })
+        .collect();
+
+    let sender_id = segments
+        .first()
+        .and_then(|isa| isa.element(6))
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(Interchange {
+        sender_id,
+        component_separator,
+        segments,
+    })
+}
+
+// The distributor's item code from qualifier/ID element pairs starting at `first` (LIN02, IT106)
+pub fn item_code(segment: &Segment, first: usize) -> Option<String> {
+    let pairs: Vec<(&str, &str)> = (first..segment.elements.len() + 1)
+        .step_by(2)
+        .filter_map(|n| Some((segment.element(n)?, segment.element(n + 1)?)))
+        .collect();
+
+    ITEM_CODE_QUALIFIERS
+        .iter()
+        .find_map(|wanted| pairs.iter().find(|(qualifier, _)| qualifier == wanted))
+        .or_else(|| pairs.first())
+        .map(|(_, code)| code.to_string())
+}
+
+// X12 dates are CCYYMMDD, or YYMMDD in older versions
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    match value.len() {
+        8 => NaiveDate::parse_from_str(value, "%Y%m%d").ok(),
+        6 => NaiveDate::parse_from_str(value, "%y%m%d").ok(),
+        _ => None,
+    }
+}
+
+// Our unit names for X12 unit-of-measure codes
+pub fn unit_name(code: &str) -> String {
+    let name = match code {
+        "LB" => "LB",
+        "OZ" => "OZ",
+        "FO" | "OF" => "FL OZ",
+        "GA" | "GL" => "GAL",
+        "QT" => "QT",
+        "PT" => "PT",
+        "LT" => "L",
+        "ML" => "ML",
+        "KG" => "KG",
+        "GR" => "G",
+        "EA" => "EA",
+        "CT" => "CT",
+        "DZ" => "DZ",
+        "CA" | "CS" => "CS",
+        other => other,
+    };
+
+    name.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uses_the_separators_the_isa_header_declares() {
+        let text = "ISA|00|          |00|          |ZZ|USF            |ZZ|REST           |260914|1200|U|00401|000000001|0|P|:\nGS|SC|USF|REST|20260914|1200|1|X|004010\nST|832|0001\nLIN||VN|111\n";
+        let interchange = parse_interchange(text).unwrap();
+
+        assert_eq!(interchange.sender_id, "USF");
+        assert_eq!(interchange.component_separator, ':');
+        assert_eq!(interchange.segments.len(), 4);
+        assert_eq!(interchange.segment("ST").and_then(|st| st.element(1)), Some("832"));
+        assert_eq!(interchange.first_component("CA:1"), "CA");
+        assert_eq!(transaction_set(text.as_bytes()).as_deref(), Some("832"));
+    }
+
+    #[test]
+    fn spots_interchanges() {
+        assert!(is_interchange(b"\xEF\xBB\xBF\n ISA*00*"));
+        assert!(!is_interchange(b"Item,Price\n"));
+        assert!(parse_interchange("ISA*00").is_err());
+    }
+
+    #[test]
+    fn prefers_the_vendor_item_number() {
+        let segment = |elements: &[&str]| Segment {
+            id: "LIN".to_string(),
+            elements: elements.iter().map(|e| e.to_string()).collect(),
+            position: 1,
+        };
+        assert_eq!(item_code(&segment(&["", "UP", "0001", "VN", "111"]), 2).as_deref(), Some("111"));
+        assert_eq!(item_code(&segment(&["", "ZZ", "777"]), 2).as_deref(), Some("777"));
+        assert_eq!(item_code(&segment(&[""]), 2), None);
+    }
+
+    #[test]
+    fn parses_dates_and_units() {
+        assert_eq!(parse_date("20260915"), NaiveDate::from_ymd_opt(2026, 9, 15));
+        assert_eq!(parse_date("260915"), NaiveDate::from_ymd_opt(2026, 9, 15));
+        assert_eq!(parse_date("2026091"), None);
+        assert_eq!(unit_name("GA"), "GAL");
+        assert_eq!(unit_name("CA"), "CS");
+        assert_eq!(unit_name("BX"), "BX");
+    }
+}
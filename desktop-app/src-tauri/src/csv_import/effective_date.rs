@@ -0,0 +1,222 @@
+use crate::db::models::{EffectiveDateCandidate, EffectiveDateSource, EffectiveDateSuggestion, ImportOptions};
+use crate::error::AppError;
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use std::path::Path;
+use std::time::SystemTime;
+
+// Work out a file's effective_date. A date the user picked always wins; otherwise a date
+// written in the header row or filename beats file metadata, which changes when files are
+// copied. Per BUSINESS_LOGIC.md metadata uses the creation date, falling back to the
+// modified date when that is earlier (copies get a new creation date but keep mtime).
+// An emailed file's sent date takes the place of metadata, which only says when it was saved.
+pub fn derive_effective_date(
+    file_path: &Path,
+    headers: &[String],
+    options: &ImportOptions,
+) -> Result<EffectiveDateSuggestion, AppError> {
+    let header_date = headers
+        .iter()
+        .find_map(|header| find_date_in_text(header).map(|date| (date, header)))
+        .map(|(date, header)| EffectiveDateCandidate {
+            effective_date: date,
+            source: EffectiveDateSource::HeaderRow,
+            detail: format!("Header \"{}\"", header),
+        });
+
+    choose_effective_date(file_path, header_date, options)
+}
+
+// Same order for EDI documents, whose own dated segment (e.g. DTM*007) stands in for the header row
+pub fn derive_document_effective_date(
+    file_path: &Path,
+    document_date: Option<(NaiveDate, String)>,
+    options: &ImportOptions,
+) -> Result<EffectiveDateSuggestion, AppError> {
+    let document_date = document_date.map(|(date, detail)| EffectiveDateCandidate {
+        effective_date: date,
+        source: EffectiveDateSource::Document,
+        detail,
+    });
+
+    choose_effective_date(file_path, document_date, options)
+}
+
+fn choose_effective_date(
+    file_path: &Path,
+    content_date: Option<EffectiveDateCandidate>,
+    options: &ImportOptions,
+) -> Result<EffectiveDateSuggestion, AppError> {
+    let mut candidates = Vec::new();
+
+    if let Some(date) = options.effective_date {
+        candidates.push(EffectiveDateCandidate {
+            effective_date: date,
+            source: EffectiveDateSource::UserOverride,
+            detail: "Chosen on import".to_string(),
+        });
+    }
+
+    candidates.extend(content_date);
+
+    if let Some(date) = file_path
+        .file_stem()
+        .and_then(|stem| find_date_in_text(&stem.to_string_lossy()))
+    {
+        candidates.push(EffectiveDateCandidate {
+            effective_date: date,
+            source: EffectiveDateSource::FileName,
+            detail: format!("File name \"{}\"", file_path.file_name().unwrap_or_default().to_string_lossy()),
+        });
+    }
+
+    match &options.email {
+        Some(email) => candidates.extend(email.sent_date.map(|date| EffectiveDateCandidate {
+            effective_date: date,
+            source: EffectiveDateSource::EmailSent,
+            detail: match &email.sender {
+                Some(sender) => format!("Email from {}", sender),
+                None => "Email date".to_string(),
+            },
+        })),
+        None => candidates.extend(metadata_candidates(file_path)?),
+    }
+
+    let chosen = candidates.first().cloned().ok_or_else(|| {
+        AppError::Validation("Could not determine the price sheet's effective date, please choose one".to_string())
+    })?;
+
+    Ok(EffectiveDateSuggestion {
+        effective_date: chosen.effective_date,
+        source: chosen.source,
+        candidates,
+    })
+}
+
+// Creation date first, unless the modified date is earlier
+fn metadata_candidates(file_path: &Path) -> Result<Vec<EffectiveDateCandidate>, AppError> {
+    let metadata = std::fs::metadata(file_path)?;
+    let created = metadata.created().ok().map(local_date);
+    let modified = metadata.modified().ok().map(local_date);
+
+    let mut candidates = Vec::new();
+    let created_candidate = created.map(|date| EffectiveDateCandidate {
+        effective_date: date,
+        source: EffectiveDateSource::FileCreated,
+        detail: "File creation date".to_string(),
+    });
+    let modified_candidate = modified.map(|date| EffectiveDateCandidate {
+        effective_date: date,
+        source: EffectiveDateSource::FileModified,
+        detail: "File last modified date".to_string(),
+    });
+
+    match (created_candidate, modified_candidate) {
+        (Some(created), Some(modified)) if modified.effective_date < created.effective_date => {
+            candidates.push(modified);
+            candidates.push(created);
+        }
+        (created, modified) => {
+            candidates.extend(created);
+            candidates.extend(modified);
+        }
+    }
+
+    Ok(candidates)
+}
+
+fn local_date(time: SystemTime) -> NaiveDate {
+    DateTime::<Local>::from(time).date_naive()
+}
+
+// Find the first plausible date in free text such as "Sysco Prices 10-05-2026" or "usf_20261005"
+pub fn find_date_in_text(text: &str) -> Option<NaiveDate> {
+    let is_date_char = |c: char| c.is_ascii_digit() || matches!(c, '-' | '_' | '/' | '.' | ' ');
+
+    // Split the text into runs of digits and separators, then try each run
+    text.split(|c: char| !is_date_char(c))
+        .filter(|run| run.chars().any(|c| c.is_ascii_digit()))
+        .find_map(parse_date_run)
+}
+
+// Try every window of one or three numbers in a run, so "2026-10-05_2026-10-11" yields the first date
+fn parse_date_run(run: &str) -> Option<NaiveDate> {
+    let groups: Vec<&str> = run
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|group| !group.is_empty())
+        .collect();
+
+    for start in 0..groups.len() {
+        if let Some(date) = parse_compact_date(groups[start]) {
+            return Some(date);
+        }
+        if let Some(window) = groups.get(start..start + 3) {
+            if let Some(date) = parse_date_parts(window[0], window[1], window[2]) {
+                return Some(date);
+            }
+        }
+    }
+
+    None
+}
+
+// YYYYMMDD or MMDDYYYY
+fn parse_compact_date(group: &str) -> Option<NaiveDate> {
+    if group.len() != 8 {
+        return None;
+    }
+
+    let number = |range: std::ops::Range<usize>| group[range].parse::<u32>().ok();
+    let year_first = ymd(number(0..4)? as i32, number(4..6)?, number(6..8)?);
+    year_first.or_else(|| ymd(number(4..8)? as i32, number(0..2)?, number(2..4)?))
+}
+
+// YYYY-MM-DD, MM-DD-YYYY or MM-DD-YY (US order, as distributor exports use)
+fn parse_date_parts(a: &str, b: &str, c: &str) -> Option<NaiveDate> {
+    let (x, y, z) = (a.parse::<u32>().ok()?, b.parse::<u32>().ok()?, c.parse::<u32>().ok()?);
+
+    match (a.len(), b.len(), c.len()) {
+        (4, 1..=2, 1..=2) => ymd(x as i32, y, z),
+        (1..=2, 1..=2, 4) => ymd(z as i32, x, y),
+        (1..=2, 1..=2, 2) => ymd(2000 + z as i32, x, y),
+        _ => None,
+    }
+}
+
+// Only accept dates a current price sheet could plausibly carry
+fn ymd(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+    let this_year = Local::now().year();
+    (2000..=this_year + 1).contains(&date.year()).then_some(date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn finds_dates_in_file_names_and_headers() {
+        assert_eq!(find_date_in_text("Sysco Prices 10-05-2026"), date(2026, 10, 5));
+        assert_eq!(find_date_in_text("usf_20261005"), date(2026, 10, 5));
+        assert_eq!(find_date_in_text("pfg 10052026"), date(2026, 10, 5));
+        assert_eq!(find_date_in_text("Prices as of 2026-10-05"), date(2026, 10, 5));
+        assert_eq!(find_date_in_text("Price 10/5/26"), date(2026, 10, 5));
+    }
+
+    #[test]
+    fn takes_the_first_date_of_a_range() {
+        assert_eq!(find_date_in_text("week 2026-10-05_2026-10-11"), date(2026, 10, 5));
+    }
+
+    #[test]
+    fn ignores_numbers_that_are_not_dates() {
+        assert_eq!(find_date_in_text("Case Price"), None);
+        assert_eq!(find_date_in_text("Item 12345678"), None);
+        assert_eq!(find_date_in_text("6/#10"), None);
+        assert_eq!(find_date_in_text("13-45-2026"), None);
+        assert_eq!(find_date_in_text("1999-10-05"), None);
+    }
+}
@@ -0,0 +1,502 @@
+use crate::db::{
+    models::{DistributorSalesContact, EmailImportResult, EmailSource, ImportOptions},
+    DbPool,
+};
+use crate::error::AppError;
+use chrono::NaiveDate;
+use mail_parser::{mailbox::mbox::MessageIterator, Message, MessageParser, MimeHeaders};
+use std::path::Path;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::{history, import_csv_file, watcher};
+
+// Attachments read as price sheets; anything else on the email (logos, PDFs) is ignored
+const SHEET_EXTENSIONS: [&str; 4] = ["csv", "xlsx", "xlsm", "xls"];
+
+// Mail providers anyone can have an address at, so a shared domain says nothing about the distributor
+const FREE_MAIL_DOMAINS: [&str; 10] = [
+    "gmail.com",
+    "googlemail.com",
+    "yahoo.com",
+    "outlook.com",
+    "hotmail.com",
+    "live.com",
+    "msn.com",
+    "aol.com",
+    "icloud.com",
+    "me.com",
+];
+
+// An email read from an .eml or mbox file, with the price sheets attached to it
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub source: EmailSource,
+    pub attachments: Vec<EmailAttachment>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub file_name: String,
+    pub contents: Vec<u8>,
+}
+
+// Whether a file is a saved email or a mailbox export
+pub fn is_email_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| matches!(ext.as_str(), "eml" | "mbox" | "mbx"))
+}
+
+// Read every email in a file: an mbox export starts each message with a "From " line,
+// anything else is taken as a single message
+pub fn read_messages(contents: &[u8]) -> Result<Vec<EmailMessage>, AppError> {
+    let parser = MessageParser::default();
+
+    if contents.starts_with(b"From ") {
+        let mut messages = Vec::new();
+        for entry in MessageIterator::new(contents) {
+            let entry = entry.map_err(|_| AppError::Validation("Could not read the mbox file".to_string()))?;
+            match parser.parse(entry.contents()) {
+                Some(message) => messages.push(read_message(&message, entry.contents())),
+                None => warn!("Skipping an mbox entry that is not an email"),
+            }
+        }
+        return Ok(messages);
+    }
+
+    let message = parser
+        .parse(contents)
+        .ok_or_else(|| AppError::Validation("File is not a readable email".to_string()))?;
+    Ok(vec![read_message(&message, contents)])
+}
+
+fn read_message(message: &Message, raw: &[u8]) -> EmailMessage {
+    // Emails without a Message-ID are recognised by their content instead
+    let message_id = message
+        .message_id()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("sha256:{}", history::content_hash(raw)));
+
+    let mut attachments = Vec::new();
+    collect_attachments(message, &mut attachments);
+
+    EmailMessage {
+        source: EmailSource {
+            message_id,
+            sender: message
+                .from()
+                .and_then(|from| from.first())
+                .and_then(|addr| addr.address())
+                .map(|address| address.trim().to_lowercase()),
+            subject: message.subject().map(str::to_string),
+            sent_date: message
+                .date()
+                .and_then(|date| NaiveDate::from_ymd_opt(date.year.into(), date.month.into(), date.day.into())),
+        },
+        attachments,
+    }
+}
+
+// CSV and Excel attachments, including those of an email forwarded as an attachment
+fn collect_attachments(message: &Message, attachments: &mut Vec<EmailAttachment>) {
+    for part in message.attachments() {
+        if let Some(forwarded) = part.message() {
+            collect_attachments(forwarded, attachments);
+            continue;
+        }
+
+        let is_csv = part.content_type().is_some_and(|content_type| {
+            content_type.ctype().eq_ignore_ascii_case("text")
+                && content_type.subtype().is_some_and(|subtype| subtype.eq_ignore_ascii_case("csv"))
+        });
+        let file_name = match part.attachment_name() {
+            Some(name) => name.to_string(),
+            None if is_csv => format!("attachment-{}.csv", attachments.len() + 1),
+            None => continue,
+        };
+
+        let extension = Path::new(&file_name)
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if SHEET_EXTENSIONS.contains(&extension.as_str()) {
+            attachments.push(EmailAttachment {
+                file_name,
+                contents: part.contents().to_vec(),
+            });
+        }
+    }
+}
+
+// Import the price sheets attached to each email in an .eml or mbox file. The distributor
+// is the one given, or else worked out from the sender; the sent date is an effective-date
+// candidate. An email whose attachments are already imported is skipped unless reimporting.
+pub async fn import_email_file(
+    pool: &DbPool,
+    file_path: &Path,
+    restaurant_id: &str,
+    distributor_id: Option<&str>,
+    options: ImportOptions,
+) -> Result<Vec<EmailImportResult>, AppError> {
+    let contents = std::fs::read(file_path)?;
+    let messages = read_messages(&contents)?;
+    if messages.is_empty() {
+        return Err(AppError::Validation("The file has no emails".to_string()));
+    }
+
+    let mut results = Vec::new();
+    for message in messages {
+        results.push(import_message(pool, message, restaurant_id, distributor_id, &options).await?);
+    }
+
+    Ok(results)
+}
+
+async fn import_message(
+    pool: &DbPool,
+    message: EmailMessage,
+    restaurant_id: &str,
+    distributor_id: Option<&str>,
+    options: &ImportOptions,
+) -> Result<EmailImportResult, AppError> {
+    let mut result = EmailImportResult {
+        email: message.source.clone(),
+        distributor_id: distributor_id.map(str::to_string),
+        already_imported: false,
+        imports: Vec::new(),
+        errors: Vec::new(),
+    };
+
+    if !options.reimport && is_imported(pool, restaurant_id, &message.source.message_id).await? {
+        info!("Email {} was already imported", message.source.message_id);
+        result.already_imported = true;
+        return Ok(result);
+    }
+
+    if message.attachments.is_empty() {
+        result.errors.push("The email has no CSV or Excel attachments".to_string());
+        return Ok(result);
+    }
+
+    let distributor_id = match (distributor_id, &message.source.sender) {
+        (Some(distributor_id), _) => distributor_id.to_string(),
+        (None, Some(sender)) => match distributor_for_sender(pool, restaurant_id, sender).await {
+            Ok(distributor_id) => distributor_id,
+            Err(e) => {
+                result.errors.push(e.to_string());
+                return Ok(result);
+            }
+        },
+        (None, None) => {
+            result.errors.push("The email has no sender; choose the distributor".to_string());
+            return Ok(result);
+        }
+    };
+    result.distributor_id = Some(distributor_id.clone());
+
+    // The pipeline reads files, so each attachment is written out under its own name first.
+    // The folder is removed whether or not that worked.
+    let folder = std::env::temp_dir().join(format!("ymmybttn-email-{}", Uuid::new_v4()));
+    let imported = async {
+        std::fs::create_dir_all(&folder)?;
+
+        for attachment in &message.attachments {
+            let file_name = Path::new(&attachment.file_name)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| attachment.file_name.clone());
+            let path = folder.join(&file_name);
+            std::fs::write(&path, &attachment.contents)?;
+
+            let attachment_options = ImportOptions {
+                email: Some(message.source.clone()),
+                ..options.clone()
+            };
+            match import_csv_file(pool, &path, restaurant_id, &distributor_id, attachment_options).await {
+                Ok(import) => result.imports.push(import),
+                Err(e) => result.errors.push(format!("{}: {}", file_name, e)),
+            }
+        }
+
+        Ok::<(), AppError>(())
+    }
+    .await;
+
+    if folder.exists() {
+        if let Err(e) = std::fs::remove_dir_all(&folder) {
+            warn!("Could not remove {}: {}", folder.display(), e);
+        }
+    }
+    imported?;
+
+    info!(
+        "Email {} from {}: {} attachments imported, {} failed",
+        message.source.message_id,
+        message.source.sender.as_deref().unwrap_or("an unknown sender"),
+        result.imports.len(),
+        result.errors.len()
+    );
+
+    Ok(result)
+}
+
+// Whether an import from this email is still in effect (rolled back or failed ones don't count)
+async fn is_imported(pool: &DbPool, restaurant_id: &str, message_id: &str) -> Result<bool, AppError> {
+    let count = sqlx::query_scalar::<_, i64>(
+        r#"SELECT COUNT(*) FROM csv_imports
+           WHERE restaurant_id = ? AND email_message_id = ? AND status IN ('pending', 'processing', 'completed')"#
+    )
+    .bind(restaurant_id)
+    .bind(message_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count > 0)
+}
+
+// Which distributor a sender works for: a sales contact with this address, then one at the
+// same company domain, then a distributor named in the domain ("orders@usfoods.com")
+pub async fn distributor_for_sender(pool: &DbPool, restaurant_id: &str, sender: &str) -> Result<String, AppError> {
+    let contacts = list_sales_contacts(pool, restaurant_id).await?;
+    let sender = sender.trim().to_lowercase();
+    let domain = sender.rsplit_once('@').map(|(_, domain)| domain.to_string()).unwrap_or_default();
+
+    let same_address = contacts
+        .iter()
+        .filter(|contact| contact.email.as_deref().is_some_and(|email| email.trim().eq_ignore_ascii_case(&sender)));
+    if let Some(distributor_id) = unique_distributor(pool, same_address).await? {
+        return Ok(distributor_id);
+    }
+
+    if !domain.is_empty() && !FREE_MAIL_DOMAINS.contains(&domain.as_str()) {
+        let same_domain = contacts.iter().filter(|contact| {
+            contact
+                .email
+                .as_deref()
+                .and_then(|email| email.trim().rsplit_once('@'))
+                .is_some_and(|(_, contact_domain)| contact_domain.eq_ignore_ascii_case(&domain))
+        });
+        if let Some(distributor_id) = unique_distributor(pool, same_domain).await? {
+            return Ok(distributor_id);
+        }
+
+        let distributors = sqlx::query_as::<_, crate::db::models::Distributor>("SELECT * FROM distributors")
+            .fetch_all(pool)
+            .await?;
+        let named: Vec<_> = distributors
+            .iter()
+            .filter(|distributor| watcher::name_mentions(&domain, distributor))
+            .collect();
+        if let [distributor] = named.as_slice() {
+            return Ok(distributor.distributor_id.clone());
+        }
+    }
+
+    Err(AppError::Validation(format!(
+        "No distributor sales contact matches {}; choose the distributor",
+        sender
+    )))
+}
+
+// The one distributor the matching contacts belong to; contacts of several distributors are an error
+async fn unique_distributor<'a>(
+    pool: &DbPool,
+    contacts: impl Iterator<Item = &'a DistributorSalesContact>,
+) -> Result<Option<String>, AppError> {
+    let mut distributor_ids: Vec<&str> = contacts.map(|contact| contact.distributor_id.as_str()).collect();
+    distributor_ids.sort();
+    distributor_ids.dedup();
+
+    match distributor_ids.as_slice() {
+        [] => Ok(None),
+        [only] => Ok(Some(only.to_string())),
+        [first, second, ..] => {
+            let name = |distributor_id: &str| {
+                sqlx::query_scalar::<_, String>("SELECT distributor_name FROM distributors WHERE distributor_id = ?")
+                    .bind(distributor_id.to_string())
+                    .fetch_optional(pool)
+            };
+            Err(AppError::Validation(format!(
+                "The sender is a contact for both {} and {}; choose the distributor",
+                name(first).await?.unwrap_or_else(|| first.to_string()),
+                name(second).await?.unwrap_or_else(|| second.to_string())
+            )))
+        }
+    }
+}
+
+// Active sales contacts for a restaurant, including those shared by every restaurant
+pub async fn list_sales_contacts(pool: &DbPool, restaurant_id: &str) -> Result<Vec<DistributorSalesContact>, AppError> {
+    let contacts = sqlx::query_as::<_, DistributorSalesContact>(
+        r#"SELECT * FROM distributor_sales_contacts
+           WHERE is_active = 1 AND (restaurant_id = ? OR restaurant_id IS NULL)
+           ORDER BY distributor_id, is_primary DESC, contact_name"#
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(contacts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHEET: &str = "Item #,Description,Case Price\r\n111,Tomatoes,18.50\r\n";
+
+    // A multipart email with the given extra headers and MIME parts
+    fn email(headers: &str, parts: &[String]) -> String {
+        let mut text = format!(
+            "From: Pat Rep <Pat.Rep@Sysco.com>\r\nSubject: Prices this week\r\nDate: Tue, 06 Oct 2026 09:15:00 -0400\r\n{}\
+             MIME-Version: 1.0\r\nContent-Type: multipart/mixed; boundary=\"XX\"\r\n\r\n\
+             --XX\r\nContent-Type: text/plain\r\n\r\nSee attached\r\n",
+            headers
+        );
+        for part in parts {
+            text.push_str("--XX\r\n");
+            text.push_str(part);
+        }
+        text.push_str("--XX--\r\n");
+        text
+    }
+
+    fn attachment(content_type: &str, file_name: Option<&str>, body: &str) -> String {
+        match file_name {
+            Some(name) => format!(
+                "Content-Type: {}; name=\"{}\"\r\nContent-Disposition: attachment; filename=\"{}\"\r\n\r\n{}\r\n",
+                content_type, name, name, body
+            ),
+            None => format!("Content-Type: {}\r\nContent-Disposition: attachment\r\n\r\n{}\r\n", content_type, body),
+        }
+    }
+
+    fn file_names(message: &EmailMessage) -> Vec<&str> {
+        message.attachments.iter().map(|attachment| attachment.file_name.as_str()).collect()
+    }
+
+    async fn seeded_pool() -> DbPool {
+        let pool = crate::db::test_pool().await;
+        for statement in [
+            "INSERT INTO restaurants (restaurant_id, restaurant_name, organization_id) VALUES ('r1', 'Bistro', 'o1')",
+            "INSERT INTO distributors (distributor_id, distributor_name, distributor_code) VALUES ('d1', 'Sysco', 'SYS')",
+            "INSERT INTO distributors (distributor_id, distributor_name, distributor_code) VALUES ('d2', 'US Foods', 'USF')",
+            "INSERT INTO distributor_sales_contacts (contact_id, restaurant_id, distributor_id, contact_name, email)
+             VALUES ('c1', 'r1', 'd1', 'Pat', 'pat.rep@sysco.com')",
+            "INSERT INTO distributor_sales_contacts (contact_id, restaurant_id, distributor_id, contact_name, email)
+             VALUES ('c2', NULL, 'd2', 'Jo', 'jo@usfoods.com')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    async fn add_contact(pool: &DbPool, contact_id: &str, distributor_id: &str, email: &str) {
+        sqlx::query(
+            "INSERT INTO distributor_sales_contacts (contact_id, restaurant_id, distributor_id, contact_name, email)
+             VALUES (?, 'r1', ?, 'Rep', ?)",
+        )
+        .bind(contact_id)
+        .bind(distributor_id)
+        .bind(email)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn reads_one_email_or_every_email_in_an_mbox() {
+        let first = email("Message-ID: <m1@sysco.com>\r\n", &[attachment("text/csv", Some("week1.csv"), SHEET)]);
+        let second = email("Message-ID: <m2@sysco.com>\r\n", &[attachment("text/csv", Some("week2.csv"), SHEET)]);
+
+        let single = read_messages(first.as_bytes()).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].source.message_id, "m1@sysco.com");
+        assert_eq!(single[0].source.sender.as_deref(), Some("pat.rep@sysco.com"));
+        assert_eq!(single[0].source.sent_date, NaiveDate::from_ymd_opt(2026, 10, 6));
+
+        let mbox = format!(
+            "From pat.rep@sysco.com Tue Oct  6 09:15:00 2026\n{}\nFrom pat.rep@sysco.com Tue Oct 13 09:15:00 2026\n{}\n",
+            first.replace("\r\n", "\n"),
+            second.replace("\r\n", "\n")
+        );
+        let messages = read_messages(mbox.as_bytes()).unwrap();
+        let ids: Vec<_> = messages.iter().map(|message| message.source.message_id.as_str()).collect();
+        assert_eq!(ids, ["m1@sysco.com", "m2@sysco.com"]);
+        assert_eq!(file_names(&messages[1]), ["week2.csv"]);
+    }
+
+    #[test]
+    fn collects_sheets_from_forwarded_and_unnamed_parts_only() {
+        let forwarded = email("Message-ID: <inner@sysco.com>\r\n", &[attachment("text/csv", Some("inner.csv"), SHEET)]);
+        let text = email(
+            "Message-ID: <outer@bistro.com>\r\n",
+            &[
+                attachment("text/csv", None, SHEET),
+                attachment(
+                    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                    Some("Order Guide.XLSX"),
+                    "not really a workbook",
+                ),
+                attachment("image/png", Some("logo.png"), "png"),
+                attachment("application/pdf", Some("terms.pdf"), "pdf"),
+                attachment("text/plain", None, "notes"),
+                format!("Content-Type: message/rfc822\r\nContent-Disposition: attachment\r\n\r\n{}\r\n", forwarded),
+            ],
+        );
+
+        let messages = read_messages(text.as_bytes()).unwrap();
+        assert_eq!(file_names(&messages[0]), ["attachment-1.csv", "Order Guide.XLSX", "inner.csv"]);
+        assert_eq!(messages[0].attachments[0].contents, SHEET.as_bytes());
+    }
+
+    #[test]
+    fn recognises_an_email_without_a_message_id_by_its_content() {
+        let text = email("", &[attachment("text/csv", Some("prices.csv"), SHEET)]);
+
+        let message = &read_messages(text.as_bytes()).unwrap()[0];
+        assert_eq!(message.source.message_id, format!("sha256:{}", history::content_hash(text.as_bytes())));
+        assert_eq!(read_messages(text.as_bytes()).unwrap()[0].source.message_id, message.source.message_id);
+    }
+
+    #[tokio::test]
+    async fn finds_the_distributor_by_address_then_domain() {
+        let pool = seeded_pool().await;
+
+        assert_eq!(distributor_for_sender(&pool, "r1", " Pat.Rep@SYSCO.com").await.unwrap(), "d1");
+        assert_eq!(distributor_for_sender(&pool, "r1", "orders@sysco.com").await.unwrap(), "d1");
+        // A contact shared by every restaurant counts too
+        assert_eq!(distributor_for_sender(&pool, "r1", "jo@usfoods.com").await.unwrap(), "d2");
+        // No contact at the domain, but the domain names the distributor
+        sqlx::query("DELETE FROM distributor_sales_contacts WHERE contact_id = 'c2'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(distributor_for_sender(&pool, "r1", "orders@usfoods.com").await.unwrap(), "d2");
+    }
+
+    #[tokio::test]
+    async fn does_not_match_on_a_free_mail_domain() {
+        let pool = seeded_pool().await;
+        add_contact(&pool, "c3", "d1", "pat.sysco@gmail.com").await;
+
+        assert_eq!(distributor_for_sender(&pool, "r1", "pat.sysco@gmail.com").await.unwrap(), "d1");
+        let error = distributor_for_sender(&pool, "r1", "someone@gmail.com").await.unwrap_err();
+        assert!(error.to_string().contains("No distributor sales contact matches someone@gmail.com"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn rejects_a_sender_who_is_a_contact_for_two_distributors() {
+        let pool = seeded_pool().await;
+        add_contact(&pool, "c3", "d2", "pat.rep@sysco.com").await;
+
+        let error = distributor_for_sender(&pool, "r1", "pat.rep@sysco.com").await.unwrap_err();
+        assert!(error.to_string().contains("both Sysco and US Foods"), "{}", error);
+
+        // Contacts of two distributors at one domain leave the domain unusable
+        add_contact(&pool, "c4", "d2", "sam@sysco.com").await;
+        let error = distributor_for_sender(&pool, "r1", "orders@sysco.com").await.unwrap_err();
+        assert!(error.to_string().contains("both Sysco and US Foods"), "{}", error);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::db::models::SourceFormat;
+use crate::error::AppError;
+use calamine::{open_workbook_from_rs, Data, Range, Reader, Xls, Xlsx};
+use std::io::Cursor;
+
+use super::source::SourceRecord;
+
+// Sheet names in workbook order
+pub fn sheet_names(contents: &[u8], format: SourceFormat) -> Result<Vec<String>, AppError> {
+    let names = match format {
+        SourceFormat::Xlsx => open_xlsx(contents)?.sheet_names(),
+        SourceFormat::Xls => open_xls(contents)?.sheet_names(),
+        SourceFormat::Csv | SourceFormat::Edi832 | SourceFormat::Edi810 => Vec::new(),
+    };
+
+    Ok(names)
+}
+
+// Read one sheet as rows of text. Without a sheet name the first sheet `has_header`
+// accepts is used, falling back to the first sheet with any data.
+pub fn read_sheet(
+    contents: &[u8],
+    format: SourceFormat,
+    sheet: Option<&str>,
+    has_header: impl Fn(&[SourceRecord]) -> bool,
+) -> Result<(String, Vec<SourceRecord>), AppError> {
+    let sheets: Vec<(String, Range<Data>)> = match (format, sheet) {
+        (SourceFormat::Xlsx, Some(name)) => {
+            vec![(name.to_string(), sheet_range(open_xlsx(contents)?.worksheet_range(name), name)?)]
+        }
+        (SourceFormat::Xls, Some(name)) => {
+            vec![(name.to_string(), sheet_range(open_xls(contents)?.worksheet_range(name), name)?)]
+        }
+        (SourceFormat::Xlsx, None) => open_xlsx(contents)?.worksheets(),
+        (SourceFormat::Xls, None) => open_xls(contents)?.worksheets(),
+        (SourceFormat::Csv | SourceFormat::Edi832 | SourceFormat::Edi810, _) => {
+            return Err(AppError::Internal("Not a workbook".to_string()))
+        }
+    };
+
+    let sheets: Vec<(String, Vec<SourceRecord>)> = sheets
+        .into_iter()
+        .map(|(name, range)| (name, range_records(&range)))
+        .collect();
+
+    let chosen = sheets
+        .iter()
+        .position(|(_, records)| has_header(records))
+        .or_else(|| sheets.iter().position(|(_, records)| !records.is_empty()))
+        .ok_or_else(|| AppError::Validation("The workbook has no data".to_string()))?;
+
+    Ok(sheets.into_iter().nth(chosen).expect("chosen sheet exists"))
+}
+
+fn open_xlsx(contents: &[u8]) -> Result<Xlsx<Cursor<&[u8]>>, AppError> {
+    open_workbook_from_rs(Cursor::new(contents))
+        .map_err(|e| AppError::Validation(format!("Could not read Excel workbook: {}", e)))
+}
+
+fn open_xls(contents: &[u8]) -> Result<Xls<Cursor<&[u8]>>, AppError> {
+    open_workbook_from_rs(Cursor::new(contents))
+        .map_err(|e| AppError::Validation(format!("Could not read Excel workbook: {}", e)))
+}
+
+fn sheet_range<E: std::fmt::Display>(range: Result<Range<Data>, E>, name: &str) -> Result<Range<Data>, AppError> {
+    range.map_err(|e| AppError::Validation(format!("Could not read sheet \"{}\": {}", name, e)))
+}
+
+// Rows keep their spreadsheet numbering even when the used range starts below row 1
+fn range_records(range: &Range<Data>) -> Vec<SourceRecord> {
+    let first_row = range.start().map(|(row, _)| row).unwrap_or(0);
+
+    range
+        .rows()
+        .enumerate()
+        .map(|(index, cells)| SourceRecord {
+            row_number: (first_row as usize + index + 1) as i32,
+            cells: Ok(cells.iter().map(cell_text).collect()),
+        })
+        .collect()
+}
+
+// Cell values as they would appear in a CSV export: whole numbers without ".0" so item
+// codes match, and dates as YYYY-MM-DD so effective dates can be found in the preamble
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::String(text) => text.trim().to_string(),
+        Data::Float(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{}", *value as i64),
+        Data::DateTime(value) => match value.as_datetime() {
+            Some(at) if at.time() == chrono::NaiveTime::MIN => at.format("%Y-%m-%d").to_string(),
+            Some(at) => at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => value.as_f64().to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{ExcelDateTime, ExcelDateTimeType};
+
+    // Sheets: "Cover" (empty), "Notes" (one line of text) and "Prices", whose used range starts
+    // at row 2 with a title and an effective date above the header on row 5
+    const WORKBOOK: &[u8] = include_bytes!("fixtures/price_list.xlsx");
+
+    fn has_item_header(records: &[SourceRecord]) -> bool {
+        records
+            .iter()
+            .any(|record| record.cells.as_ref().is_ok_and(|cells| cells.iter().any(|cell| cell == "Item #")))
+    }
+
+    fn cells(record: &SourceRecord) -> Vec<&str> {
+        record.cells.as_ref().unwrap().iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn writes_cells_as_a_csv_export_would() {
+        let date = |value, datetime_type| Data::DateTime(ExcelDateTime::new(value, datetime_type, false));
+
+        assert_eq!(cell_text(&Data::Empty), "");
+        assert_eq!(cell_text(&Data::String("  Roma Tomatoes ".to_string())), "Roma Tomatoes");
+        assert_eq!(cell_text(&Data::Float(111.0)), "111");
+        assert_eq!(cell_text(&Data::Float(-4.0)), "-4");
+        assert_eq!(cell_text(&Data::Float(18.5)), "18.5");
+        assert_eq!(cell_text(&Data::Float(1e15)), "1000000000000000");
+        assert_eq!(cell_text(&Data::Int(42)), "42");
+        assert_eq!(cell_text(&date(46300.0, ExcelDateTimeType::DateTime)), "2026-10-05");
+        assert_eq!(cell_text(&date(46300.25, ExcelDateTimeType::DateTime)), "2026-10-05 06:00:00");
+    }
+
+    #[test]
+    fn numbers_rows_as_the_spreadsheet_does() {
+        let (name, records) = read_sheet(WORKBOOK, SourceFormat::Xlsx, Some("Prices"), |_| false).unwrap();
+
+        assert_eq!(name, "Prices");
+        let numbered: Vec<_> = records.iter().map(|record| (record.row_number, cells(record))).collect();
+        assert_eq!(numbered[0], (2, vec!["Sysco Boston price list", "", "", ""]));
+        assert_eq!(numbered[1], (3, vec!["Effective", "2026-10-05", "", ""]));
+        assert_eq!(numbered[3], (5, vec!["Item #", "Description", "Case Price", "Updated"]));
+        assert_eq!(numbered[4], (6, vec!["111", "Roma Tomatoes", "18.5", "2026-10-04 14:30:00"]));
+        assert_eq!(numbered[5], (7, vec!["222", "Yellow Onions", "25", ""]));
+        assert_eq!(numbered.len(), 6);
+    }
+
+    #[test]
+    fn reads_the_named_sheet_or_the_first_usable_one() {
+        assert_eq!(sheet_names(WORKBOOK, SourceFormat::Xlsx).unwrap(), ["Cover", "Notes", "Prices"]);
+
+        // A named sheet is read even when another one has the header
+        let (name, records) = read_sheet(WORKBOOK, SourceFormat::Xlsx, Some("Notes"), has_item_header).unwrap();
+        assert_eq!((name.as_str(), records.len()), ("Notes", 1));
+
+        // Unnamed, the first sheet with the header wins, then the first with any data
+        let (name, _) = read_sheet(WORKBOOK, SourceFormat::Xlsx, None, has_item_header).unwrap();
+        assert_eq!(name, "Prices");
+        let (name, _) = read_sheet(WORKBOOK, SourceFormat::Xlsx, None, |_| false).unwrap();
+        assert_eq!(name, "Notes");
+
+        let error = read_sheet(WORKBOOK, SourceFormat::Xlsx, Some("Specials"), |_| true).unwrap_err();
+        assert!(error.to_string().contains("Could not read sheet \"Specials\""), "{}", error);
+    }
+}
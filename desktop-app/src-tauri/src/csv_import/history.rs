@@ -0,0 +1,164 @@
+use crate::db::{
+    models::{CsvImport, SourceFormat},
+    DbPool,
+};
+use crate::error::AppError;
+use sha2::{Digest, Sha256};
+use sqlx::SqliteConnection;
+
+// Identify a file by content: text files (CSV, EDI) through content_hash, workbooks by their exact bytes
+pub fn file_hash(contents: &[u8], format: SourceFormat) -> String {
+    match format {
+        SourceFormat::Csv | SourceFormat::Edi832 | SourceFormat::Edi810 => content_hash(contents),
+        SourceFormat::Xlsx | SourceFormat::Xls => hex::encode(Sha256::digest(contents)),
+    }
+}
+
+// SHA-256 of a file's content with the BOM and line endings normalized, so the same
+// price sheet is recognised under another name or after a re-save that only changed line endings
+pub fn content_hash(contents: &[u8]) -> String {
+    let contents = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+
+    let mut normalized = Vec::with_capacity(contents.len());
+    let mut bytes = contents.iter().peekable();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'\r' {
+            if bytes.peek() == Some(&&b'\n') {
+                bytes.next();
+            }
+            normalized.push(b'\n');
+        } else {
+            normalized.push(byte);
+        }
+    }
+
+    while normalized.last().is_some_and(|b| b.is_ascii_whitespace()) {
+        normalized.pop();
+    }
+
+    hex::encode(Sha256::digest(&normalized))
+}
+
+// The import that currently owns this content, if any
+pub async fn find_active_import(
+    conn: &mut SqliteConnection,
+    file_hash: &str,
+) -> Result<Option<CsvImport>, AppError> {
+    let import = sqlx::query_as::<_, CsvImport>(
+        r#"SELECT * FROM csv_imports
+           WHERE file_hash = ? AND status IN ('pending', 'processing', 'completed')
+           ORDER BY imported_at DESC LIMIT 1"#
+    )
+    .bind(file_hash)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(import)
+}
+
+// An import for this restaurant and distributor that is still writing its batches
+pub async fn find_running_import(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    distributor_id: &str,
+) -> Result<Option<CsvImport>, AppError> {
+    let import = sqlx::query_as::<_, CsvImport>(
+        r#"SELECT * FROM csv_imports
+           WHERE restaurant_id = ? AND distributor_id = ? AND status = 'processing'
+           ORDER BY imported_at DESC LIMIT 1"#
+    )
+    .bind(restaurant_id)
+    .bind(distributor_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(import)
+}
+
+// "sysco_0105.csv was already imported on 2024-01-05 09:12 by Jane Smith (120 rows: 118 imported, 2 failed)"
+pub fn describe_import(import: &CsvImport) -> String {
+    let imported_at = import
+        .imported_at
+        .map(|at| at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "an unknown date".to_string());
+
+    format!(
+        "{} was already imported on {} by {} ({} rows: {} imported, {} failed)",
+        import.file_name,
+        imported_at,
+        import.imported_by.as_deref().unwrap_or("an unknown user"),
+        import.row_count.unwrap_or(0),
+        import.imported_count.unwrap_or(0),
+        import.failed_count.unwrap_or(0),
+    )
+}
+
+// Mark earlier imports of the same content as replaced, returning the superseded import ids.
+// Their queued uploads are dropped by drop_superseded_uploads once the new import finishes.
+pub async fn supersede_imports(
+    conn: &mut SqliteConnection,
+    file_hash: &str,
+    new_import_id: &str,
+) -> Result<Vec<String>, AppError> {
+    let superseded: Vec<String> = sqlx::query_scalar(
+        "SELECT import_id FROM csv_imports WHERE file_hash = ? AND status != 'superseded'"
+    )
+    .bind(file_hash)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for import_id in &superseded {
+        sqlx::query("UPDATE csv_imports SET status = 'superseded', superseded_by = ? WHERE import_id = ?")
+            .bind(new_import_id)
+            .bind(import_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(superseded)
+}
+
+// Drop the queued uploads of the imports a finished import superseded
+pub async fn drop_superseded_uploads(conn: &mut SqliteConnection, import_id: &str) -> Result<(), AppError> {
+    sqlx::query(
+        r#"DELETE FROM pending_sync_events
+           WHERE event_type = 'csv_import'
+             AND json_extract(payload, '$.import_id') IN (SELECT import_id FROM csv_imports WHERE superseded_by = ?)"#
+    )
+    .bind(import_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Put back the imports an unfinished import superseded, with the status each had before
+pub async fn reinstate_superseded(conn: &mut SqliteConnection, import_id: &str) -> Result<(), AppError> {
+    sqlx::query(
+        r#"UPDATE csv_imports
+           SET superseded_by = NULL,
+               status = CASE
+                   WHEN rolled_back_at IS NOT NULL THEN 'rolled_back'
+                   WHEN error_message IS NOT NULL THEN 'failed'
+                   ELSE 'completed'
+               END
+           WHERE superseded_by = ? AND status = 'superseded'"#
+    )
+    .bind(import_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Recent imports for a restaurant, newest first
+pub async fn list_imports(pool: &DbPool, restaurant_id: &str) -> Result<Vec<CsvImport>, AppError> {
+    let imports = sqlx::query_as::<_, CsvImport>(
+        "SELECT * FROM csv_imports WHERE restaurant_id = ? ORDER BY imported_at DESC"
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(imports)
+}
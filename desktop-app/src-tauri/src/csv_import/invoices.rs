@@ -0,0 +1,345 @@
+use crate::db::{
+    models::{
+        CsvColumnMapping, EdiInvoice, EdiInvoiceLine, ImportErrorType, ImportOptions, ImportSource,
+        InvoiceImportResult, MappingSource, PriceImportResult, SourceFormat,
+    },
+    DbPool,
+};
+use crate::error::AppError;
+use chrono::NaiveDate;
+use sqlx::SqliteConnection;
+use std::path::Path;
+use tracing::info;
+use uuid::Uuid;
+
+use super::edi::invoice::{parse_invoices, Invoice, INVOICE_HEADERS};
+use super::{
+    derive_document_effective_date, detect_format, history, matching, parse_price, profiles, run_import,
+    PreparedImport, PriceRow, RowParseError,
+};
+
+// Import every invoice in an EDI 810 file: each one is recorded with its lines, and its
+// case-priced lines are written as prices effective on the invoice date
+pub async fn import_invoice_file(
+    pool: &DbPool,
+    file_path: &Path,
+    restaurant_id: &str,
+    distributor_id: &str,
+    options: ImportOptions,
+) -> Result<Vec<InvoiceImportResult>, AppError> {
+    let contents = std::fs::read(file_path)?;
+    if detect_format(&contents, file_path) != SourceFormat::Edi810 {
+        return Err(AppError::Validation("Not an EDI 810 invoice file".to_string()));
+    }
+
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.display().to_string());
+    let imported_by = crate::db::get_cached_user(pool).await?.map(|user| user.full_name);
+    let invoices = parse_invoices(&String::from_utf8_lossy(&contents))?;
+
+    // All invoices in the file go in together, so a failure never records half of them
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::new();
+
+    for invoice in &invoices {
+        let prepared = prepare_invoice(file_path, &file_name, imported_by.clone(), invoice, &options)?;
+        let (prices, _) = run_import(&mut tx, &prepared, restaurant_id, distributor_id).await?;
+        let result = record_invoice(&mut tx, invoice, prices, restaurant_id, distributor_id).await?;
+
+        info!(
+            "Invoice {} from {}: {} lines, {} prices written, {} lines unmatched",
+            invoice.invoice_number,
+            file_name,
+            result.lines.len(),
+            result.prices.successful_imports,
+            result.unmatched_lines.len()
+        );
+        results.push(result);
+    }
+
+    tx.commit().await?;
+
+    Ok(results)
+}
+
+// The invoice date as an effective-date candidate
+pub fn invoice_date(invoice: &Invoice) -> Option<(NaiveDate, String)> {
+    Some((invoice.invoice_date, format!("Invoice {} date (BIG01)", invoice.invoice_number)))
+}
+
+// An invoice as a price import of its own, identified by the invoice's segments so a
+// re-sent invoice is caught even when it arrives in a different file
+fn prepare_invoice(
+    file_path: &Path,
+    file_name: &str,
+    imported_by: Option<String>,
+    invoice: &Invoice,
+    options: &ImportOptions,
+) -> Result<PreparedImport, AppError> {
+    let mut rows = Vec::new();
+    let mut parse_errors = Vec::new();
+
+    for line in &invoice.lines {
+        let raw = line.raw();
+        let reject = |error_type, message: String| RowParseError {
+            row_number: line.line_number,
+            error_type,
+            message,
+            raw: raw.clone(),
+        };
+
+        let Some(item_code) = line.item_code.clone() else {
+            parse_errors.push(reject(ImportErrorType::Validation, "IT1 segment has no item number".to_string()));
+            continue;
+        };
+
+        // Lines invoiced per pound are catch-weight prices; per-each lines don't carry a case price
+        let price_unit = line.catch_weight_unit();
+        if !line.is_case_priced() && price_unit.is_none() {
+            parse_errors.push(reject(
+                ImportErrorType::Validation,
+                format!("invoiced per {}, not per case", line.unit_of_measure.as_deref().unwrap_or_default()),
+            ));
+            continue;
+        }
+
+        let case_price = match line.unit_price.as_deref().map(parse_price) {
+            Some(Ok(price)) => price,
+            Some(Err(message)) => {
+                parse_errors.push(reject(ImportErrorType::Parsing, message));
+                continue;
+            }
+            None => {
+                parse_errors.push(reject(ImportErrorType::Validation, "missing unit price (IT104)".to_string()));
+                continue;
+            }
+        };
+
+        rows.push(PriceRow {
+            row_number: line.line_number,
+            item_code: Some(item_code),
+            description: line.description.clone(),
+            case_price,
+            pack_size: line.pack_size.clone(),
+            unit: None,
+            price_unit,
+            raw,
+        });
+    }
+
+    Ok(PreparedImport {
+        file_name: file_name.to_string(),
+        file_hash: history::content_hash(invoice.document.as_bytes()),
+        file_size: invoice.document.len() as i64,
+        headers: INVOICE_HEADERS.iter().map(|h| h.to_string()).collect(),
+        imported_by,
+        reimport: options.reimport,
+        email_message_id: options.email.as_ref().map(|email| email.message_id.clone()),
+        resolved: profiles::ResolvedMapping {
+            mapping: CsvColumnMapping::default(),
+            source: MappingSource::Edi,
+            drift: None,
+            preset: None,
+        },
+        source: ImportSource {
+            format: SourceFormat::Edi810,
+            file_format: None,
+            sheet_name: None,
+            header_row: 0,
+        },
+        effective_date: derive_document_effective_date(file_path, invoice_date(invoice), options)?,
+        rows,
+        parse_errors,
+    })
+}
+
+// Store the invoice header and lines, replacing an earlier copy of the same invoice number
+async fn record_invoice(
+    conn: &mut SqliteConnection,
+    invoice: &Invoice,
+    prices: PriceImportResult,
+    restaurant_id: &str,
+    distributor_id: &str,
+) -> Result<InvoiceImportResult, AppError> {
+    sqlx::query("DELETE FROM edi_invoices WHERE distributor_id = ? AND invoice_number = ?")
+        .bind(distributor_id)
+        .bind(&invoice.invoice_number)
+        .execute(&mut *conn)
+        .await?;
+
+    let invoice_id = Uuid::new_v4().to_string();
+    let mut lines = Vec::new();
+    for line in &invoice.lines {
+        let catalog_product_id = match &line.item_code {
+            Some(item_code) => matching::find_spec_for_row(&mut *conn, distributor_id, Some(item_code), None)
+                .await?
+                .map(|spec| spec.catalog_product_id),
+            None => None,
+        };
+
+        lines.push(EdiInvoiceLine {
+            line_id: 0,
+            invoice_id: invoice_id.clone(),
+            line_number: line.line_number,
+            item_code: line.item_code.clone(),
+            description: line.description.clone(),
+            quantity: line.quantity,
+            unit_of_measure: line.unit_of_measure.clone(),
+            unit_price: line.unit_price.as_deref().and_then(|price| parse_price(price).ok()),
+            pack_size: line.pack_size.clone(),
+            catalog_product_id,
+        });
+    }
+    let unmatched_count = lines.iter().filter(|line| line.catalog_product_id.is_none()).count() as i32;
+
+    sqlx::query(
+        r#"INSERT INTO edi_invoices (invoice_id, import_id, restaurant_id, distributor_id, invoice_number,
+           invoice_date, purchase_order_number, seller_name, ship_to_name, total_amount, line_count, unmatched_count)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+    )
+    .bind(&invoice_id)
+    .bind(&prices.import_id)
+    .bind(restaurant_id)
+    .bind(distributor_id)
+    .bind(&invoice.invoice_number)
+    .bind(invoice.invoice_date)
+    .bind(&invoice.purchase_order_number)
+    .bind(&invoice.seller_name)
+    .bind(&invoice.ship_to_name)
+    .bind(invoice.total_amount)
+    .bind(lines.len() as i32)
+    .bind(unmatched_count)
+    .execute(&mut *conn)
+    .await?;
+
+    for line in lines.iter_mut() {
+        line.line_id = sqlx::query(
+            r#"INSERT INTO edi_invoice_lines (invoice_id, line_number, item_code, description, quantity,
+               unit_of_measure, unit_price, pack_size, catalog_product_id)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+        )
+        .bind(&invoice_id)
+        .bind(line.line_number)
+        .bind(&line.item_code)
+        .bind(&line.description)
+        .bind(line.quantity)
+        .bind(&line.unit_of_measure)
+        .bind(line.unit_price)
+        .bind(&line.pack_size)
+        .bind(&line.catalog_product_id)
+        .execute(&mut *conn)
+        .await?
+        .last_insert_rowid();
+    }
+
+    let invoice = sqlx::query_as::<_, EdiInvoice>("SELECT * FROM edi_invoices WHERE invoice_id = ?")
+        .bind(&invoice_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    let unmatched_lines = lines.iter().filter(|line| line.catalog_product_id.is_none()).cloned().collect();
+
+    Ok(InvoiceImportResult {
+        invoice,
+        lines,
+        unmatched_lines,
+        prices,
+    })
+}
+
+// Invoices received for a restaurant, newest first
+pub async fn list_invoices(pool: &DbPool, restaurant_id: &str) -> Result<Vec<EdiInvoice>, AppError> {
+    let invoices = sqlx::query_as::<_, EdiInvoice>(
+        "SELECT * FROM edi_invoices WHERE restaurant_id = ? ORDER BY invoice_date DESC, invoice_number"
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(invoices)
+}
+
+pub async fn list_invoice_lines(pool: &DbPool, invoice_id: &str) -> Result<Vec<EdiInvoiceLine>, AppError> {
+    let lines = sqlx::query_as::<_, EdiInvoiceLine>(
+        "SELECT * FROM edi_invoice_lines WHERE invoice_id = ? ORDER BY line_number"
+    )
+    .bind(invoice_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csv_import::edi::invoice::tests::ISA;
+    use crate::db::models::EffectiveDateSource;
+
+    // Prepare the one invoice in a file with these lines; the file is there for the date checks
+    fn prepare(lines: &str) -> PreparedImport {
+        let text = format!("{}ST*810*0001~\nBIG*20260918*INV100~\n{}SE*9*0001~\n", ISA, lines);
+        let path = std::env::temp_dir().join(format!("invoice-test-{}.edi", Uuid::new_v4()));
+        std::fs::write(&path, &text).unwrap();
+
+        let invoices = parse_invoices(&text).unwrap();
+        let prepared = prepare_invoice(&path, "invoices.edi", None, &invoices[0], &ImportOptions::default());
+        std::fs::remove_file(&path).unwrap();
+        prepared.unwrap()
+    }
+
+    #[test]
+    fn prices_case_lines_and_catch_weight_lines() {
+        let prepared = prepare("IT1*1*2*CA*19.25**UP*0001*VN*111~\nPID*F****TOMATOES~\nPO4*1*25*LB~\nIT1*2*10.5*LB*0.45**VN*222~\n");
+
+        assert!(prepared.parse_errors.is_empty());
+        let tomatoes = &prepared.rows[0];
+        assert_eq!(tomatoes.item_code.as_deref(), Some("111"));
+        assert_eq!(tomatoes.description.as_deref(), Some("TOMATOES"));
+        assert_eq!(tomatoes.case_price, 19.25);
+        assert_eq!(tomatoes.pack_size.as_deref(), Some("1/25 LB"));
+        assert_eq!(tomatoes.price_unit, None);
+
+        let onions = &prepared.rows[1];
+        assert_eq!(onions.row_number, 2);
+        assert_eq!(onions.case_price, 0.45);
+        assert_eq!(onions.price_unit.as_deref(), Some("lb"));
+
+        assert_eq!(prepared.effective_date.effective_date, NaiveDate::from_ymd_opt(2026, 9, 18).unwrap());
+        assert_eq!(prepared.effective_date.source, EffectiveDateSource::Document);
+        assert_eq!(prepared.source.format, SourceFormat::Edi810);
+    }
+
+    #[test]
+    fn rejects_lines_without_a_case_price_or_item_number() {
+        let prepared = prepare(
+            "IT1*1*4*EA*3.00**VN*333~\nIT1*2*1*CA***VN*444~\nIT1*3*1*CA*9.50~\nIT1*4*1*CA*abc**VN*555~\nIT1*5*1*CA*12.00**VN*666~\n",
+        );
+
+        assert_eq!(prepared.rows.len(), 1);
+        assert_eq!(prepared.rows[0].item_code.as_deref(), Some("666"));
+
+        let errors: Vec<_> = prepared
+            .parse_errors
+            .iter()
+            .map(|error| (error.row_number, error.error_type, error.message.as_str()))
+            .collect();
+        assert_eq!(errors[0], (1, ImportErrorType::Validation, "invoiced per EA, not per case"));
+        assert_eq!(errors[1], (2, ImportErrorType::Validation, "missing unit price (IT104)"));
+        assert_eq!(errors[2], (3, ImportErrorType::Validation, "IT1 segment has no item number"));
+        assert_eq!((errors[3].0, errors[3].1), (4, ImportErrorType::Parsing));
+        assert_eq!(errors.len(), 4);
+        assert_eq!(prepared.parse_errors[1].raw, ["2", "444", "", "1", "CS", ""]);
+    }
+
+    #[test]
+    fn identifies_an_invoice_by_its_own_segments() {
+        let first = prepare("IT1*1*2*CA*19.25**VN*111~\n");
+        let again = prepare("IT1*1*2*CA*19.25**VN*111~\n");
+        let changed = prepare("IT1*1*2*CA*19.50**VN*111~\n");
+
+        assert_eq!(first.file_hash, again.file_hash);
+        assert_ne!(first.file_hash, changed.file_hash);
+    }
+}
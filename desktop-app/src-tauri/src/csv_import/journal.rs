@@ -0,0 +1,93 @@
+use crate::db::{
+    models::{CsvImportError, ImportErrorType},
+    DbPool,
+};
+use crate::error::AppError;
+use sqlx::SqliteConnection;
+use std::path::Path;
+
+// Record a rejected row with its original values so it can be exported and fixed
+pub async fn record_row_error(
+    conn: &mut SqliteConnection,
+    import_id: &str,
+    row_number: i32,
+    error_type: ImportErrorType,
+    message: &str,
+    raw: &[String],
+) -> Result<(), AppError> {
+    let row_data = if raw.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(raw)?)
+    };
+
+    sqlx::query(
+        r#"INSERT INTO csv_import_errors (import_id, row_number, error_type, error_message, row_data)
+           VALUES (?, ?, ?, ?, ?)"#
+    )
+    .bind(import_id)
+    .bind(row_number)
+    .bind(error_type.as_str())
+    .bind(message)
+    .bind(row_data)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_errors(pool: &DbPool, import_id: &str) -> Result<Vec<CsvImportError>, AppError> {
+    let errors = sqlx::query_as::<_, CsvImportError>(
+        "SELECT * FROM csv_import_errors WHERE import_id = ? ORDER BY row_number"
+    )
+    .bind(import_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(errors)
+}
+
+// Write an import's rejected rows to a CSV in the original column layout plus a Reason column,
+// returning how many rows were written
+pub async fn export_rejected_rows(pool: &DbPool, import_id: &str, output_path: &Path) -> Result<usize, AppError> {
+    let headers_json = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT headers FROM csv_imports WHERE import_id = ?"
+    )
+    .bind(import_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Import {} not found", import_id)))?;
+
+    let errors = list_errors(pool, import_id).await?;
+    let rows: Vec<(Vec<String>, String)> = errors
+        .iter()
+        .map(|error| {
+            let values = error
+                .row_data
+                .as_deref()
+                .and_then(|data| serde_json::from_str::<Vec<String>>(data).ok())
+                .unwrap_or_default();
+            (values, format!("Row {}: {}", error.row_number, error.error_message))
+        })
+        .collect();
+
+    let mut headers: Vec<String> = match headers_json {
+        Some(json) => serde_json::from_str(&json)?,
+        None => Vec::new(),
+    };
+    let width = rows.iter().map(|(values, _)| values.len()).max().unwrap_or(0);
+    while headers.len() < width {
+        headers.push(format!("Column {}", headers.len() + 1));
+    }
+
+    let mut writer = csv::Writer::from_path(output_path)?;
+    writer.write_record(headers.iter().map(String::as_str).chain(["Reason"]))?;
+    for (mut values, reason) in rows {
+        values.resize(headers.len(), String::new());
+        values.push(reason);
+        writer.write_record(&values)?;
+    }
+    writer.flush()?;
+
+    Ok(errors.len())
+}
@@ -0,0 +1,217 @@
+use crate::db::models::{ColumnMatch, CsvColumnMapping, CsvField};
+use crate::error::AppError;
+
+// Matches scoring below this are shown to the user for confirmation
+const CONFIDENT_SCORE: f64 = 0.7;
+
+// A runner-up column scoring within this margin makes the match ambiguous
+const AMBIGUITY_MARGIN: f64 = 0.15;
+
+// Header names seen in distributor exports, strongest first. Headers are normalized
+// before comparison, so "Item #" becomes "item no" and "Pack/Size" becomes "pack size".
+fn synonyms(field: CsvField) -> &'static [(&'static str, f64)] {
+    match field {
+        CsvField::ItemCode => &[
+            ("item no", 1.0),
+            ("item number", 1.0),
+            ("item code", 1.0),
+            ("supc", 1.0),
+            ("product no", 0.95),
+            ("product number", 0.95),
+            ("product code", 0.95),
+            ("prod no", 0.9),
+            ("sku", 0.9),
+            ("item", 0.7),
+            ("code", 0.6),
+            ("mfr no", 0.4),
+            ("upc", 0.3),
+            ("gtin", 0.3),
+        ],
+        CsvField::Description => &[
+            ("description", 1.0),
+            ("item description", 1.0),
+            ("product description", 1.0),
+            ("desc", 0.95),
+            ("product name", 0.9),
+            ("item name", 0.9),
+            ("name", 0.7),
+            ("product", 0.5),
+        ],
+        CsvField::CasePrice => &[
+            ("case price", 1.0),
+            ("cs price", 1.0),
+            ("price case", 1.0),
+            ("case cost", 0.95),
+            ("price", 0.85),
+            ("net price", 0.85),
+            ("your price", 0.85),
+            ("sell price", 0.8),
+            ("cost", 0.7),
+            ("unit price", 0.5),
+            ("amount", 0.4),
+        ],
+        CsvField::PackSize => &[
+            ("pack size", 1.0),
+            ("pk sz", 1.0),
+            ("pack", 0.75),
+            ("size", 0.7),
+            ("case pack", 0.7),
+        ],
+        CsvField::Unit => &[
+            ("uom", 1.0),
+            ("unit of measure", 1.0),
+            ("u m", 1.0),
+            ("unit", 0.8),
+            ("measure", 0.7),
+            ("price uom", 0.6),
+        ],
+    }
+}
+
+// Words that rule a header out for a field, so "Unit Price" is never taken as the unit column.
+// A header that is exactly one of the field's synonyms ("Price UOM") is never ruled out.
+fn excluded_words(field: CsvField) -> &'static [&'static str] {
+    match field {
+        CsvField::CasePrice => &["uom"],
+        _ => &["price", "cost"],
+    }
+}
+
+// Lowercase a header and reduce punctuation to single spaces ("Item #" -> "item no")
+pub fn normalize_header(header: &str) -> String {
+    let spaced: String = header
+        .to_lowercase()
+        .replace('#', " no ")
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+
+    spaced.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Score how well a header names a field, from 0.0 (no match) to 1.0 (exact synonym)
+pub fn score_header(field: CsvField, header: &str) -> f64 {
+    let normalized = normalize_header(header);
+    if normalized.is_empty() {
+        return 0.0;
+    }
+
+    let exact = synonyms(field).iter().find(|(synonym, _)| normalized == *synonym);
+    if let Some((_, weight)) = exact {
+        return *weight;
+    }
+
+    let padded = format!(" {} ", normalized);
+    if excluded_words(field).iter().any(|word| padded.contains(&format!(" {} ", word))) {
+        return 0.0;
+    }
+
+    synonyms(field)
+        .iter()
+        .map(|(synonym, weight)| {
+            if padded.contains(&format!(" {} ", synonym)) {
+                // The synonym appears as whole words inside a longer header ("Sysco Case Price")
+                weight * 0.8
+            } else {
+                0.0
+            }
+        })
+        .fold(0.0, f64::max)
+}
+
+// Score every header against every field and assign each column to at most one field
+pub fn detect_column_mapping(headers: &[String]) -> Result<CsvColumnMapping, AppError> {
+    let mut candidates: Vec<(CsvField, usize, f64)> = Vec::new();
+    for field in CsvField::ALL {
+        for (index, header) in headers.iter().enumerate() {
+            let score = score_header(field, header);
+            if score > 0.0 {
+                candidates.push((field, index, score));
+            }
+        }
+    }
+
+    // Greedily take the strongest pairs first so "Case Price" is claimed before "Price"
+    let mut ordered = candidates.clone();
+    ordered.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut mapping = CsvColumnMapping::default();
+    let mut used_columns = Vec::new();
+
+    for (field, index, score) in ordered {
+        if mapping.get(field).is_some() || used_columns.contains(&index) {
+            continue;
+        }
+
+        let runner_up = candidates
+            .iter()
+            .filter(|(f, i, _)| *f == field && *i != index && !used_columns.contains(i))
+            .map(|(_, _, s)| *s)
+            .fold(0.0, f64::max);
+
+        mapping.set(
+            field,
+            Some(ColumnMatch {
+                column_index: index,
+                header: headers[index].clone(),
+                confidence: score,
+                ambiguous: score < CONFIDENT_SCORE || score - runner_up < AMBIGUITY_MARGIN,
+            }),
+        );
+        used_columns.push(index);
+    }
+
+    Ok(mapping)
+}
+
+// Make sure a mapping has the columns an import cannot do without
+pub fn require_columns(mapping: &CsvColumnMapping, headers: &[String]) -> Result<(), AppError> {
+    // Files without item codes are matched on their descriptions instead
+    let identified = mapping.get(CsvField::ItemCode).is_some() || mapping.get(CsvField::Description).is_some();
+    let missing: Vec<&str> = [CsvField::ItemCode, CsvField::CasePrice]
+        .into_iter()
+        .filter(|field| match field {
+            CsvField::ItemCode => !identified,
+            _ => mapping.get(*field).is_none(),
+        })
+        .map(CsvField::label)
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!(
+            "Could not find a {} column in headers: {}",
+            missing.join(" or "),
+            headers.join(", ")
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_price_is_not_the_unit_column() {
+        assert_eq!(score_header(CsvField::Unit, "Unit Price"), 0.0);
+        assert!(score_header(CsvField::CasePrice, "Unit Price") > 0.0);
+    }
+
+    #[test]
+    fn price_uom_is_the_unit_column() {
+        assert_eq!(score_header(CsvField::Unit, "Price UOM"), 0.6);
+        assert_eq!(score_header(CsvField::CasePrice, "Price UOM"), 0.0);
+
+        let headers: Vec<String> = ["Item #", "Description", "Price", "Price UOM"].iter().map(|h| h.to_string()).collect();
+        let mapping = detect_column_mapping(&headers).unwrap();
+        assert_eq!(mapping.case_price.unwrap().column_index, 2);
+        assert_eq!(mapping.unit.unwrap().column_index, 3);
+    }
+
+    #[test]
+    fn headers_are_normalized() {
+        assert_eq!(normalize_header("Item #"), "item no");
+        assert_eq!(normalize_header(" Pack/Size "), "pack size");
+    }
+}
@@ -0,0 +1,368 @@
+use crate::db::{
+    models::{DistributorSpec, ItemCodeAlias, PackSize, ProductMatchCandidate},
+    DbPool,
+};
+use crate::error::AppError;
+use sqlx::SqliteConnection;
+use std::collections::HashSet;
+
+use super::pack_size::{disagrees_with_spec, parse_pack_size, with_preferred_units};
+use super::units::measurement_type_of;
+use super::PriceRow;
+
+// Candidates proposed per row, and the description similarity below which a product isn't proposed
+const MAX_CANDIDATES: usize = 5;
+const MIN_DESCRIPTION_SCORE: f64 = 0.3;
+
+// Share of the score from the description; the rest comes from pack-size compatibility
+const DESCRIPTION_WEIGHT: f64 = 0.8;
+
+// Words that say nothing about which product a line is
+const STOP_WORDS: [&str; 24] = [
+    "a", "an", "and", "the", "of", "with", "w", "in", "for", "per", "case", "cs", "pack", "pk", "bag", "box",
+    "lb", "lbs", "oz", "ct", "ea", "each", "gal", "can",
+];
+
+// Abbreviations common in distributor descriptions
+const ABBREVIATIONS: [(&str, &str); 22] = [
+    ("yel", "yellow"),
+    ("ylw", "yellow"),
+    ("grn", "green"),
+    ("wht", "white"),
+    ("blk", "black"),
+    ("chkn", "chicken"),
+    ("chix", "chicken"),
+    ("brst", "breast"),
+    ("bnls", "boneless"),
+    ("sknls", "skinless"),
+    ("grd", "ground"),
+    ("bf", "beef"),
+    ("frz", "frozen"),
+    ("frzn", "frozen"),
+    ("frsh", "fresh"),
+    ("whl", "whole"),
+    ("shrd", "shredded"),
+    ("chz", "cheese"),
+    ("tom", "tomato"),
+    ("pot", "potato"),
+    ("veg", "vegetable"),
+    ("org", "organic"),
+];
+
+// Reduce a description to its product words, sorted, so "ONION, YELLOW JUMBO 50#" and
+// "Yellow Jumbo Onions" normalize the same. Sizes, counts and units are dropped.
+pub fn normalize_description(text: &str) -> String {
+    let mut words: Vec<String> = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !word.chars().any(|c| c.is_ascii_digit()))
+        .filter(|word| !STOP_WORDS.contains(word))
+        .map(|word| {
+            let word = ABBREVIATIONS
+                .iter()
+                .find(|(short, _)| *short == word)
+                .map(|(_, long)| long.to_string())
+                .unwrap_or_else(|| word.to_string());
+            singular(&word)
+        })
+        .collect();
+
+    words.sort();
+    words.dedup();
+    words.join(" ")
+}
+
+fn singular(word: &str) -> String {
+    if word.len() <= 3 {
+        return word.to_string();
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        return format!("{}y", stem);
+    }
+    if ["oes", "ches", "shes", "xes"].iter().any(|suffix| word.ends_with(suffix)) {
+        return word[..word.len() - 2].to_string();
+    }
+    match word.strip_suffix('s') {
+        Some(stem) if !stem.ends_with('s') && !stem.ends_with('u') => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+// Similarity of two normalized descriptions, 0.0 - 1.0. Words are matched one to one,
+// with a truncated word ("tomat", "jumb") counting as most of a match; letter trigrams
+// of the whole text catch what word matching misses.
+pub fn description_similarity(a: &str, b: &str) -> f64 {
+    let a_words: Vec<&str> = a.split_whitespace().collect();
+    let b_words: Vec<&str> = b.split_whitespace().collect();
+    if a_words.is_empty() || b_words.is_empty() {
+        return 0.0;
+    }
+
+    let mut used = vec![false; b_words.len()];
+    let mut matched = 0.0;
+    for word in &a_words {
+        let best = b_words
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !used[*index])
+            .map(|(index, other)| (index, word_similarity(word, other)))
+            .max_by(|x, y| x.1.total_cmp(&y.1));
+
+        if let Some((index, score)) = best.filter(|(_, score)| *score > 0.0) {
+            used[index] = true;
+            matched += score;
+        }
+    }
+    let word_score = 2.0 * matched / (a_words.len() + b_words.len()) as f64;
+
+    0.7 * word_score + 0.3 * trigram_similarity(a, b)
+}
+
+fn word_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if short.len() >= 3 && long.starts_with(short) {
+        return 0.8;
+    }
+    0.0
+}
+
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let a = trigrams(a);
+    let b = trigrams(b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+fn trigrams(text: &str) -> HashSet<String> {
+    let chars: Vec<char> = format!("  {} ", text).chars().collect();
+    chars.windows(3).map(|window| window.iter().collect()).collect()
+}
+
+// The spec a price line belongs to: the distributor's own item code, then a match the user
+// confirmed earlier for that item code or, for lines without one, that description
+pub async fn find_spec_for_row(
+    conn: &mut SqliteConnection,
+    distributor_id: &str,
+    item_code: Option<&str>,
+    description: Option<&str>,
+) -> Result<Option<DistributorSpec>, AppError> {
+    if let Some(item_code) = item_code {
+        if let Some(spec) = super::find_spec_by_item_code(&mut *conn, distributor_id, item_code).await? {
+            return Ok(Some(spec));
+        }
+        return find_spec_by_alias(&mut *conn, distributor_id, "item_code", item_code).await;
+    }
+
+    match description.map(normalize_description).filter(|alias| !alias.is_empty()) {
+        Some(alias) => find_spec_by_alias(&mut *conn, distributor_id, "description", &alias).await,
+        None => Ok(None),
+    }
+}
+
+async fn find_spec_by_alias(
+    conn: &mut SqliteConnection,
+    distributor_id: &str,
+    alias_type: &str,
+    alias: &str,
+) -> Result<Option<DistributorSpec>, AppError> {
+    let spec = sqlx::query_as::<_, DistributorSpec>(
+        r#"SELECT s.* FROM item_code_aliases a
+           JOIN distributor_specs s ON s.spec_id = a.spec_id
+           WHERE a.distributor_id = ? AND a.alias_type = ? AND a.alias = ?"#
+    )
+    .bind(distributor_id)
+    .bind(alias_type)
+    .bind(alias)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(spec)
+}
+
+// The pack size of a row, for ranking candidates
+pub fn row_pack_size(row: &PriceRow) -> Option<PackSize> {
+    parse_pack_size(row.pack_size.as_deref()?, row.unit.as_deref())
+}
+
+// Rank this distributor's products against a line's description, best first. A pack size
+// that fits the product's spec lifts a candidate; one that can't be the same case sinks it.
+pub async fn suggest_matches(
+    conn: &mut SqliteConnection,
+    distributor_id: &str,
+    description: &str,
+    pack: Option<&PackSize>,
+) -> Result<Vec<ProductMatchCandidate>, AppError> {
+    let wanted = normalize_description(description);
+    if wanted.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let specs = sqlx::query_as::<_, DistributorSpec>("SELECT * FROM distributor_specs WHERE distributor_id = ?")
+        .bind(distributor_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut candidates = Vec::new();
+    for spec in specs {
+        let product = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT product_name, preferred_measurement, measurement_type FROM products WHERE catalog_product_id = ?"
+        )
+        .bind(&spec.catalog_product_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some((product_name, preferred_measurement, measurement_type)) = product else {
+            continue;
+        };
+
+        let description_score = description_similarity(&wanted, &normalize_description(&product_name));
+        if description_score < MIN_DESCRIPTION_SCORE {
+            continue;
+        }
+
+        let pack_size_compatible = pack.map(|pack| {
+            let same_measure = measurement_type_of(&pack.pack_unit_of_measure)
+                .is_none_or(|pack_type| pack_type == measurement_type);
+            same_measure && !disagrees_with_spec(&with_preferred_units(pack.clone(), &preferred_measurement), &spec)
+        });
+        let pack_score = match pack_size_compatible {
+            Some(true) => 1.0,
+            None => 0.5,
+            Some(false) => 0.0,
+        };
+
+        candidates.push(ProductMatchCandidate {
+            spec_id: spec.spec_id,
+            catalog_product_id: spec.catalog_product_id,
+            product_name,
+            distributor_item_code: spec.distributor_item_code,
+            score: DESCRIPTION_WEIGHT * description_score + (1.0 - DESCRIPTION_WEIGHT) * pack_score,
+            description_score,
+            pack_size_compatible,
+        });
+    }
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.product_name.cmp(&b.product_name)));
+    candidates.truncate(MAX_CANDIDATES);
+
+    Ok(candidates)
+}
+
+// Remember a confirmed match: by item code when the line has one, otherwise by its
+// normalized description. Confirming again for the same line replaces the earlier match.
+pub async fn confirm_match(
+    pool: &DbPool,
+    distributor_id: &str,
+    item_code: Option<&str>,
+    description: Option<&str>,
+    spec_id: &str,
+) -> Result<ItemCodeAlias, AppError> {
+    let spec = sqlx::query_as::<_, DistributorSpec>("SELECT * FROM distributor_specs WHERE spec_id = ?")
+        .bind(spec_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Distributor spec {}", spec_id)))?;
+    if spec.distributor_id != distributor_id {
+        return Err(AppError::Validation(format!("Spec {} belongs to another distributor", spec_id)));
+    }
+
+    let item_code = item_code.map(str::trim).filter(|code| !code.is_empty());
+    let (alias_type, alias) = match (item_code, description.map(normalize_description)) {
+        (Some(item_code), _) => ("item_code", item_code.to_string()),
+        (None, Some(description)) if !description.is_empty() => ("description", description),
+        _ => return Err(AppError::Validation("An item code or description is required".to_string())),
+    };
+    let confirmed_by = crate::db::get_cached_user(pool).await?.map(|user| user.full_name);
+
+    sqlx::query(
+        r#"INSERT INTO item_code_aliases (distributor_id, alias_type, alias, spec_id, catalog_product_id, confirmed_by)
+           VALUES (?, ?, ?, ?, ?, ?)
+           ON CONFLICT(distributor_id, alias_type, alias) DO UPDATE SET
+               spec_id = excluded.spec_id,
+               catalog_product_id = excluded.catalog_product_id,
+               confirmed_by = excluded.confirmed_by,
+               created_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(distributor_id)
+    .bind(alias_type)
+    .bind(&alias)
+    .bind(&spec.spec_id)
+    .bind(&spec.catalog_product_id)
+    .bind(&confirmed_by)
+    .execute(pool)
+    .await?;
+
+    let alias = sqlx::query_as::<_, ItemCodeAlias>(
+        "SELECT * FROM item_code_aliases WHERE distributor_id = ? AND alias_type = ? AND alias = ?"
+    )
+    .bind(distributor_id)
+    .bind(alias_type)
+    .bind(&alias)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(alias)
+}
+
+pub async fn list_aliases(pool: &DbPool, distributor_id: &str) -> Result<Vec<ItemCodeAlias>, AppError> {
+    let aliases = sqlx::query_as::<_, ItemCodeAlias>(
+        "SELECT * FROM item_code_aliases WHERE distributor_id = ? ORDER BY alias_type, alias"
+    )
+    .bind(distributor_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(aliases)
+}
+
+pub async fn delete_alias(pool: &DbPool, alias_id: i64) -> Result<(), AppError> {
+    let deleted = sqlx::query("DELETE FROM item_code_aliases WHERE alias_id = ?")
+        .bind(alias_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Item code alias {}", alias_id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_word_order_abbreviations_and_sizes() {
+        assert_eq!(normalize_description("ONION, YELLOW JUMBO 50#"), "jumbo onion yellow");
+        assert_eq!(normalize_description("Yellow Jumbo Onions"), "jumbo onion yellow");
+        assert_eq!(normalize_description("CHKN BRST BNLS SKNLS 4/10 LB"), "boneless breast chicken skinless");
+        assert_eq!(normalize_description("Tomatoes 6x6 case"), "tomato");
+        assert_eq!(normalize_description("Cherries"), "cherry");
+        assert_eq!(normalize_description("Asparagus"), "asparagus");
+    }
+
+    #[test]
+    fn scores_the_same_product_highest() {
+        let row = normalize_description("ONION YEL JUMBO 50 LB");
+        let same = description_similarity(&row, &normalize_description("Yellow Jumbo Onions"));
+        let related = description_similarity(&row, &normalize_description("Red Onions"));
+        let unrelated = description_similarity(&row, &normalize_description("Chicken Breast"));
+
+        assert!((same - 1.0).abs() < 1e-9);
+        assert!(same > related && related > unrelated);
+        assert!(unrelated < MIN_DESCRIPTION_SCORE);
+    }
+
+    #[test]
+    fn counts_a_truncated_word_as_most_of_a_match() {
+        let truncated = description_similarity("tomat", "tomato");
+        assert!(truncated > MIN_DESCRIPTION_SCORE && truncated < 1.0);
+        assert_eq!(description_similarity("", "tomato"), 0.0);
+    }
+}
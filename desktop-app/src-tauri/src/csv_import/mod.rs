@@ -1,52 +1,1195 @@
-use crate::db::{models::PriceImportResult, DbPool};
+use crate::db::{
+    models::{
+        CsvColumnMapping, CsvField, CsvImport, CsvMappingPreview, DistributorSpec, EffectiveDateSuggestion,
+        ImportErrorType, ImportOptions, ImportPreset, ImportPreview, ImportProgress, ImportRowOutcome,
+        ImportRowPreview, ImportSource, LocalCurrentPrice, MappingSource, PackSize, PresetDateLocation,
+        PriceImportResult, PricingMode, SourceFormat,
+    },
+    DbPool,
+};
 use crate::error::AppError;
-use chrono::NaiveDate;
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone, Utc};
+use serde_json::json;
+use sqlx::SqliteConnection;
 use std::path::Path;
+use tracing::{error, info};
+use uuid::Uuid;
 
-// CSV import functionality - stub for now
+pub mod dialect;
+pub mod edi;
+mod effective_date;
+pub mod email;
+mod excel;
+pub mod history;
+pub mod invoices;
+pub mod journal;
+mod mapping;
+pub mod matching;
+pub mod pack_size;
+pub mod presets;
+pub mod profiles;
+pub mod progress;
+pub mod quarantine;
+pub mod rollback;
+pub mod source;
+pub mod spec_changes;
+mod units;
+pub mod watcher;
+
+pub use effective_date::{derive_document_effective_date, derive_effective_date};
+pub use progress::ImportControl;
+pub use source::{detect_format, SourceTable};
+pub use mapping::{detect_column_mapping, require_columns};
+
+// Rows written per transaction, so a large file commits in steps and can report progress
+const IMPORT_BATCH_SIZE: usize = 500;
+
+// A single price line read from a distributor file, before it is matched to a product
+#[derive(Debug, Clone)]
+pub struct PriceRow {
+    pub row_number: i32,
+    pub item_code: Option<String>, // Files without item codes are matched by description
+    pub description: Option<String>,
+    pub case_price: f64,
+    pub pack_size: Option<String>,
+    pub unit: Option<String>,
+    pub price_unit: Option<String>, // Set for catch-weight rows, whose case_price is per this unit (lb)
+    pub raw: Vec<String>, // Original cell values, kept for the error journal
+}
+
+impl PriceRow {
+    // How the row is named in messages: its item code, or its description
+    pub fn label(&self) -> String {
+        match (&self.item_code, &self.description) {
+            (Some(item_code), _) => format!("item code '{}'", item_code),
+            (None, Some(description)) => format!("'{}'", description),
+            (None, None) => format!("row {}", self.row_number),
+        }
+    }
+}
+
+// A row that could not be read into a PriceRow
+#[derive(Debug, Clone)]
+pub struct RowParseError {
+    pub row_number: i32,
+    pub error_type: ImportErrorType,
+    pub message: String,
+    pub raw: Vec<String>,
+}
+
+// What happened to a price row when it was written
+#[derive(Debug, Clone)]
+pub enum PriceWriteOutcome {
+    Inserted { price_id: String },
+    Updated { previous: Box<LocalCurrentPrice> },
+    SkippedOlder { existing_date: NaiveDate },
+}
+
+// The file a written price came from, kept on local_current_prices
+#[derive(Debug, Clone, Copy)]
+pub struct PriceOrigin<'a> {
+    pub source_type: &'a str,
+    pub file_name: &'a str,
+    pub file_hash: &'a str,
+}
+
+// What a written price buys. A case price is for the spec's case; a catch-weight price per
+// pound is carried on a nominal case weight, so unit_price comes out as the quoted per-pound price.
+#[derive(Debug, Clone, Copy)]
+pub struct CasePricing {
+    pub case_price: f64,
+    pub total_preferred_units: f64,
+    pub mode: PricingMode,
+}
+
+impl CasePricing {
+    pub fn by_case(case_price: f64, spec: &DistributorSpec) -> Self {
+        CasePricing {
+            case_price,
+            total_preferred_units: spec.total_preferred_units,
+            mode: PricingMode::Case,
+        }
+    }
+
+    pub fn unit_price(&self) -> f64 {
+        self.case_price / self.total_preferred_units
+    }
+}
+
+// A file read and mapped, ready to run against the database
+#[derive(Debug, Clone)]
+pub struct PreparedImport {
+    pub file_name: String,
+    pub file_hash: String,
+    pub file_size: i64,
+    pub headers: Vec<String>,
+    pub imported_by: Option<String>,
+    pub reimport: bool,
+    pub email_message_id: Option<String>, // Message-ID of the email the file was attached to
+    pub resolved: profiles::ResolvedMapping,
+    pub source: ImportSource,
+    pub effective_date: EffectiveDateSuggestion,
+    pub rows: Vec<PriceRow>,
+    pub parse_errors: Vec<RowParseError>,
+}
+
+// Import a distributor CSV price sheet into local_current_prices
 pub async fn import_csv_file(
-    _pool: &DbPool,
-    _file_path: &Path,
-    _restaurant_id: &str,
+    pool: &DbPool,
+    file_path: &Path,
+    restaurant_id: &str,
     distributor_id: &str,
-    effective_date: NaiveDate,
+    options: ImportOptions,
 ) -> Result<PriceImportResult, AppError> {
-    // TODO: Implement CSV parsing and import logic
-    // For now, return a mock result
-    Ok(PriceImportResult {
+    import_csv_file_with_control(pool, file_path, restaurant_id, distributor_id, options, &ImportControl::default()).await
+}
+
+// Import in batches of IMPORT_BATCH_SIZE rows, reporting progress after each. A cancel or
+// failure part-way undoes the batches already committed, so a price sheet is never left half in.
+// The file itself is read and parsed whole before the first batch: workbooks and EDI are
+// read whole anyway, and the mapping and effective date need the header and preamble.
+// Only a "reading" event (total_rows 0) is sent for that step, and a cancel then stops the
+// import before anything is written.
+// Batches commit as they go, so until the import finishes the winner calculation already
+// sees the prices written so far.
+pub async fn import_csv_file_with_control(
+    pool: &DbPool,
+    file_path: &Path,
+    restaurant_id: &str,
+    distributor_id: &str,
+    options: ImportOptions,
+    control: &ImportControl,
+) -> Result<PriceImportResult, AppError> {
+    let import_id = Uuid::new_v4().to_string();
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.display().to_string());
+    control.report(&ImportProgress {
+        import_id: import_id.clone(),
+        file_name: file_name.clone(),
         total_rows: 0,
-        successful_imports: 0,
-        failed_imports: 0,
-        errors: vec![],
-        distributor_id: distributor_id.to_string(),
+        processed_rows: 0,
+        matched_rows: 0,
+        failed_rows: 0,
+        quarantined_rows: 0,
+    });
+
+    let prepared = prepare_import(pool, file_path, distributor_id, options).await?;
+    if control.is_cancelled() {
+        return Err(AppError::Cancelled(format!("Import of {} was cancelled", file_name)));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut run = begin_import(&mut tx, &prepared, &import_id, restaurant_id, distributor_id, false).await?;
+    tx.commit().await?;
+
+    control.report(&run.progress(&prepared.file_name));
+
+    let written = async {
+        for batch in prepared.rows.chunks(IMPORT_BATCH_SIZE) {
+            if control.is_cancelled() {
+                return Err(AppError::Cancelled(format!("Import of {} was cancelled", prepared.file_name)));
+            }
+
+            let mut tx = pool.begin().await?;
+            import_rows(&mut tx, &prepared, &mut run, batch, restaurant_id).await?;
+            tx.commit().await?;
+            control.report(&run.progress(&prepared.file_name));
+        }
+
+        let mut tx = pool.begin().await?;
+        let (result, _) = finish_import(&mut tx, &prepared, run, restaurant_id, distributor_id).await?;
+        tx.commit().await?;
+        Ok(result)
+    }
+    .await;
+
+    let result = match written {
+        Ok(result) => result,
+        Err(e) => {
+            if let Err(undo_error) = rollback::abandon_import(pool, &import_id, &e.to_string()).await {
+                error!("Could not undo import {}: {}", import_id, undo_error);
+            }
+            return Err(e);
+        }
+    };
+
+    info!(
+        "Import {} finished: {} imported, {} skipped, {} failed, {} held for review",
+        result.import_id, result.successful_imports, result.skipped_imports, result.failed_imports, result.quarantined_imports
+    );
+
+    Ok(result)
+}
+
+// Run the full import inside a transaction that is always rolled back, reporting what each row would do
+pub async fn preview_csv_import(
+    pool: &DbPool,
+    file_path: &Path,
+    restaurant_id: &str,
+    distributor_id: &str,
+    options: ImportOptions,
+) -> Result<ImportPreview, AppError> {
+    let prepared = prepare_import(pool, file_path, distributor_id, options).await?;
+
+    let mut tx = pool.begin().await?;
+    let (result, mut rows) = run_import(&mut tx, &prepared, restaurant_id, distributor_id).await?;
+    tx.rollback().await?;
+
+    let mut conn = pool.acquire().await?;
+    for row in rows.iter_mut() {
+        if let Some(product_id) = &row.catalog_product_id {
+            row.product_name = sqlx::query_scalar::<_, String>(
+                "SELECT product_name FROM products WHERE catalog_product_id = ?"
+            )
+            .bind(product_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        }
+
+        // Propose products for rows nothing matched, for the user to confirm
+        if let (ImportRowOutcome::UnmatchedItemCode, Some(description)) = (&row.outcome, &row.description) {
+            row.match_candidates =
+                matching::suggest_matches(&mut conn, distributor_id, description, row.pack_size.as_ref()).await?;
+        }
+    }
+
+    let distributor_name = sqlx::query_scalar::<_, String>(
+        "SELECT distributor_name FROM distributors WHERE distributor_id = ?"
+    )
+    .bind(distributor_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .unwrap_or_else(|| distributor_id.to_string());
+
+    Ok(ImportPreview {
+        result,
+        distributor_name,
+        rows,
+    })
+}
+
+// Read a file, find its price table, resolve its column mapping and parse its rows.
+// EDI 832 catalogs have fixed segments instead, so they skip mapping entirely.
+// EDI 810 invoices are recorded as invoices and go through invoices::import_invoice_file.
+pub async fn prepare_import(
+    pool: &DbPool,
+    file_path: &Path,
+    distributor_id: &str,
+    options: ImportOptions,
+) -> Result<PreparedImport, AppError> {
+    let contents = std::fs::read(file_path)?;
+    let file_name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.display().to_string());
+    let format = detect_format(&contents, file_path);
+    let file_hash = history::file_hash(&contents, format);
+
+    if format == SourceFormat::Edi810 {
+        return Err(AppError::Validation(format!(
+            "{} is an EDI 810 invoice; import it as an invoice",
+            file_name
+        )));
+    }
+
+    if format == SourceFormat::Edi832 {
+        let catalog = edi::catalog::parse_price_catalog(&String::from_utf8_lossy(&contents))?;
+        let effective_date = derive_document_effective_date(file_path, catalog.document_date, &options)?;
+
+        return Ok(PreparedImport {
+            file_name,
+            file_hash,
+            file_size: contents.len() as i64,
+            headers: edi::catalog::CATALOG_HEADERS.iter().map(|h| h.to_string()).collect(),
+            imported_by: crate::db::get_cached_user(pool).await?.map(|user| user.full_name),
+            reimport: options.reimport,
+            email_message_id: options.email.map(|email| email.message_id),
+            resolved: profiles::ResolvedMapping {
+                mapping: CsvColumnMapping::default(),
+                source: MappingSource::Edi,
+                drift: None,
+                preset: None,
+            },
+            source: ImportSource {
+                format,
+                file_format: None,
+                sheet_name: None,
+                header_row: 0,
+            },
+            effective_date,
+            rows: catalog.rows,
+            parse_errors: catalog.errors,
+        });
+    }
+
+    let profile = profiles::get_profile(pool, distributor_id).await?;
+    let presets = presets::presets_for_distributor(pool, distributor_id).await?;
+    let table = source::load_table(
+        pool,
+        &contents,
+        file_path,
+        profile.as_ref(),
+        &presets,
+        options.sheet.as_deref(),
+        options.header_row,
+    )
+    .await?;
+
+    let headers = table.headers.clone();
+    let resolved = profiles::resolve_mapping(profile.as_ref(), &presets, &headers, options.mapping.clone())?;
+
+    // Never guess silently when a saved profile's columns have gone missing
+    if let Some(drift) = resolved.drift.as_ref().filter(|d| !d.missing_headers.is_empty()) {
+        return Err(AppError::Validation(format!(
+            "Column layout for this distributor has changed (missing: {}). Confirm the column mapping before importing.",
+            drift.missing_headers.join(", ")
+        )));
+    }
+    require_columns(&resolved.mapping, &headers)?;
+
+    let (mut rows, parse_errors) = read_price_rows(&table, &resolved.mapping);
+    let effective_date = match &resolved.preset {
+        Some(preset) => {
+            presets::apply_pack_count(preset, &headers, &mut rows);
+            preset_effective_date(preset, &table, file_path, &options)?
+        }
+        None => derive_effective_date(file_path, &header_text(&table), &options)?,
+    };
+    let imported_by = crate::db::get_cached_user(pool).await?.map(|user| user.full_name);
+
+    Ok(PreparedImport {
+        file_name,
+        file_hash,
+        file_size: contents.len() as i64,
+        headers,
+        imported_by,
+        reimport: options.reimport,
+        email_message_id: options.email.map(|email| email.message_id),
+        resolved,
+        source: table.source,
         effective_date,
+        rows,
+        parse_errors,
     })
 }
 
-// Validate CSV format
-pub fn validate_csv_format(_file_path: &Path) -> Result<bool, AppError> {
-    // TODO: Implement CSV validation
-    // Check headers, data types, etc.
-    Ok(true)
+// Write a prepared import inside the caller's transaction, returning the totals and each row's outcome
+pub async fn run_import(
+    conn: &mut SqliteConnection,
+    prepared: &PreparedImport,
+    restaurant_id: &str,
+    distributor_id: &str,
+) -> Result<(PriceImportResult, Vec<ImportRowPreview>), AppError> {
+    let import_id = Uuid::new_v4().to_string();
+    let mut run = begin_import(&mut *conn, prepared, &import_id, restaurant_id, distributor_id, true).await?;
+    import_rows(&mut *conn, prepared, &mut run, &prepared.rows, restaurant_id).await?;
+    finish_import(&mut *conn, prepared, run, restaurant_id, distributor_id).await
 }
 
-// Parse CSV headers to determine column mapping
-pub fn detect_column_mapping(_headers: &[String]) -> Result<CsvColumnMapping, AppError> {
-    // TODO: Implement intelligent column detection
-    Ok(CsvColumnMapping {
-        item_code_column: 0,
-        description_column: 1,
-        case_price_column: 2,
-        pack_size_column: 3,
-        unit_column: 4,
+// An import being written: running totals, the prices to queue for upload and, for
+// previews, every row's outcome
+#[derive(Debug)]
+pub struct ImportRun {
+    pub result: PriceImportResult,
+    pub matched_rows: i32,
+    row_outcomes: Option<Vec<ImportRowPreview>>,
+    written_prices: Vec<serde_json::Value>,
+}
+
+impl ImportRun {
+    pub fn progress(&self, file_name: &str) -> ImportProgress {
+        ImportProgress {
+            import_id: self.result.import_id.clone(),
+            file_name: file_name.to_string(),
+            total_rows: self.result.total_rows,
+            processed_rows: self.result.successful_imports
+                + self.result.skipped_imports
+                + self.result.failed_imports
+                + self.result.quarantined_imports,
+            matched_rows: self.matched_rows,
+            failed_rows: self.result.failed_imports,
+            quarantined_rows: self.result.quarantined_imports,
+        }
+    }
+}
+
+// Create the csv_imports row and journal the rows that couldn't be parsed
+pub async fn begin_import(
+    conn: &mut SqliteConnection,
+    prepared: &PreparedImport,
+    import_id: &str,
+    restaurant_id: &str,
+    distributor_id: &str,
+    keep_row_outcomes: bool,
+) -> Result<ImportRun, AppError> {
+    let effective_date = prepared.effective_date.effective_date;
+
+    info!(
+        "Importing {} ({} rows) for distributor {} effective {}",
+        prepared.file_name,
+        prepared.rows.len() + prepared.parse_errors.len(),
+        distributor_id,
+        effective_date
+    );
+
+    // Two imports writing the same distributor's prices at once would undo each other's
+    // batches if either stopped part-way
+    if let Some(running) = history::find_running_import(&mut *conn, restaurant_id, distributor_id).await? {
+        return Err(AppError::Validation(format!(
+            "{} is still being imported for this distributor; wait for it to finish",
+            running.file_name
+        )));
+    }
+
+    // Catch a repeated file before any price is touched
+    if let Some(previous) = history::find_active_import(&mut *conn, &prepared.file_hash).await? {
+        if !prepared.reimport {
+            return Err(AppError::AlreadyExists(history::describe_import(&previous)));
+        }
+    }
+
+    let superseded = history::supersede_imports(&mut *conn, &prepared.file_hash, import_id).await?;
+    if !superseded.is_empty() {
+        info!("Import {} supersedes {}", import_id, superseded.join(", "));
+    }
+
+    sqlx::query(
+        r#"INSERT INTO csv_imports (import_id, restaurant_id, distributor_id, file_name, file_hash, file_size,
+           status, imported_by, headers, source_format, sheet_name, email_message_id)
+           VALUES (?, ?, ?, ?, ?, ?, 'processing', ?, ?, ?, ?, ?)"#
+    )
+    .bind(import_id)
+    .bind(restaurant_id)
+    .bind(distributor_id)
+    .bind(&prepared.file_name)
+    .bind(&prepared.file_hash)
+    .bind(prepared.file_size)
+    .bind(&prepared.imported_by)
+    .bind(serde_json::to_string(&prepared.headers)?)
+    .bind(prepared.source.format.as_str())
+    .bind(&prepared.source.sheet_name)
+    .bind(&prepared.email_message_id)
+    .execute(&mut *conn)
+    .await?;
+
+    let mut run = ImportRun {
+        result: PriceImportResult {
+            import_id: import_id.to_string(),
+            total_rows: (prepared.rows.len() + prepared.parse_errors.len()) as i32,
+            successful_imports: 0,
+            skipped_imports: 0,
+            failed_imports: 0,
+            quarantined_imports: 0,
+            errors: Vec::new(),
+            distributor_id: distributor_id.to_string(),
+            effective_date,
+            effective_date_source: prepared.effective_date.source,
+            effective_date_candidates: prepared.effective_date.candidates.clone(),
+            mapping: prepared.resolved.mapping.clone(),
+            mapping_source: prepared.resolved.source,
+            preset_id: prepared.resolved.preset.as_ref().map(|preset| preset.preset_id.clone()),
+            profile_drift: prepared.resolved.drift.clone(),
+            source: prepared.source.clone(),
+            spec_change_candidates: Vec::new(),
+        },
+        matched_rows: 0,
+        row_outcomes: keep_row_outcomes.then(Vec::new),
+        written_prices: Vec::new(),
+    };
+
+    for error in &prepared.parse_errors {
+        reject_row(&mut *conn, &mut run.result, error.row_number, error.error_type, &error.message, &error.raw).await?;
+        if let Some(outcomes) = run.row_outcomes.as_mut() {
+            outcomes.push(ImportRowPreview {
+                row_number: error.row_number,
+                item_code: None,
+                description: None,
+                case_price: None,
+                catalog_product_id: None,
+                product_name: None,
+                pack_size: None,
+                spec_change_candidate: false,
+                pricing_mode: PricingMode::Case,
+                match_candidates: Vec::new(),
+                outcome: match error.error_type {
+                    ImportErrorType::Parsing => ImportRowOutcome::ParseError { message: error.message.clone() },
+                    _ => ImportRowOutcome::ValidationError { message: error.message.clone() },
+                },
+            });
+        }
+    }
+
+    Ok(run)
+}
+
+// Match and write a batch of price rows
+pub async fn import_rows(
+    conn: &mut SqliteConnection,
+    prepared: &PreparedImport,
+    run: &mut ImportRun,
+    rows: &[PriceRow],
+    restaurant_id: &str,
+) -> Result<(), AppError> {
+    let import_id = run.result.import_id.clone();
+    let effective_date = prepared.effective_date.effective_date;
+    let result = &mut run.result;
+
+    for row in rows {
+        let mut preview = ImportRowPreview {
+            row_number: row.row_number,
+            item_code: row.item_code.clone(),
+            description: row.description.clone(),
+            case_price: Some(row.case_price),
+            catalog_product_id: None,
+            product_name: None,
+            pack_size: None,
+            spec_change_candidate: false,
+            pricing_mode: PricingMode::Case,
+            match_candidates: Vec::new(),
+            outcome: ImportRowOutcome::UnmatchedItemCode,
+        };
+
+        let spec = matching::find_spec_for_row(
+            &mut *conn,
+            &result.distributor_id,
+            row.item_code.as_deref(),
+            row.description.as_deref(),
+        )
+        .await?;
+        let spec = match spec {
+            Some(spec) => spec,
+            None => {
+                let message = format!("no product spec found for {}", row.label());
+                reject_row(&mut *conn, result, row.row_number, ImportErrorType::ProductNotFound, &message, &row.raw).await?;
+                // Kept so the preview can rank candidate products against it
+                preview.pack_size = matching::row_pack_size(row);
+                run.row_outcomes.iter_mut().for_each(|outcomes| outcomes.push(preview.clone()));
+                continue;
+            }
+        };
+        run.matched_rows += 1;
+        preview.catalog_product_id = Some(spec.catalog_product_id.clone());
+
+        if spec.total_preferred_units <= 0.0 {
+            let message = format!("product spec for {} has no preferred units", row.label());
+            reject_row(&mut *conn, result, row.row_number, ImportErrorType::Validation, &message, &row.raw).await?;
+            preview.outcome = ImportRowOutcome::ValidationError { message };
+            run.row_outcomes.iter_mut().for_each(|outcomes| outcomes.push(preview.clone()));
+            continue;
+        }
+
+        if let Some(message) = check_row_measurement(&mut *conn, row, &spec).await? {
+            reject_row(&mut *conn, result, row.row_number, ImportErrorType::MeasurementMismatch, &message, &row.raw).await?;
+            preview.outcome = ImportRowOutcome::MeasurementMismatch { message };
+            run.row_outcomes.iter_mut().for_each(|outcomes| outcomes.push(preview.clone()));
+            continue;
+        }
+
+        // Prices are still written against the synced spec; a disagreeing pack size is only flagged
+        let (pack, candidate) = spec_changes::check_pack_size(&mut *conn, &import_id, row, &spec).await?;
+        preview.pack_size = pack;
+        if let Some(candidate) = candidate {
+            preview.spec_change_candidate = true;
+            result.spec_change_candidates.push(candidate);
+        }
+
+        let pricing = match row_pricing(&mut *conn, row, &spec, preview.pack_size.as_ref()).await? {
+            Some(pricing) => pricing,
+            None => {
+                let message = format!(
+                    "priced per {} but the product's measurement can't be converted from it",
+                    row.price_unit.as_deref().unwrap_or_default()
+                );
+                reject_row(&mut *conn, result, row.row_number, ImportErrorType::MeasurementMismatch, &message, &row.raw)
+                    .await?;
+                preview.outcome = ImportRowOutcome::MeasurementMismatch { message };
+                run.row_outcomes.iter_mut().for_each(|outcomes| outcomes.push(preview.clone()));
+                continue;
+            }
+        };
+        preview.pricing_mode = pricing.mode;
+
+        let origin = PriceOrigin {
+            source_type: prepared.source.format.price_source_type(),
+            file_name: &prepared.file_name,
+            file_hash: &prepared.file_hash,
+        };
+
+        // Likely mistakes ($185.00 for $18.50) wait for approval instead of becoming the current price
+        if let Some(anomaly) = quarantine::check_price(&mut *conn, restaurant_id, &spec, pricing, effective_date).await? {
+            let quarantine_id = quarantine::hold_price(
+                &mut *conn,
+                &import_id,
+                restaurant_id,
+                &spec,
+                row.row_number,
+                pricing,
+                effective_date,
+                origin,
+                &anomaly,
+            )
+            .await?;
+            result.quarantined_imports += 1;
+            result.errors.push(format!("Row {}: held for review, {}", row.row_number, anomaly.reason));
+            preview.outcome = ImportRowOutcome::Quarantined {
+                quarantine_id,
+                anomaly_type: anomaly.anomaly_type,
+                reason: anomaly.reason,
+                new_unit_price: anomaly.unit_price,
+                reference_unit_price: anomaly.reference_unit_price,
+            };
+            run.row_outcomes.iter_mut().for_each(|outcomes| outcomes.push(preview.clone()));
+            continue;
+        }
+
+        let new_unit_price = pricing.unit_price();
+        let outcome = write_price(&mut *conn, restaurant_id, &spec, pricing, effective_date, origin).await?;
+        rollback::record_price_change(&mut *conn, &import_id, &outcome).await?;
+
+        preview.outcome = match outcome {
+            PriceWriteOutcome::Inserted { .. } => ImportRowOutcome::NewPrice { unit_price: new_unit_price },
+            PriceWriteOutcome::Updated { previous } => ImportRowOutcome::PriceChange {
+                old_case_price: previous.case_price,
+                new_case_price: pricing.case_price,
+                old_unit_price: previous.unit_price,
+                new_unit_price,
+                change_percent: (new_unit_price - previous.unit_price) / previous.unit_price * 100.0,
+                previous_effective_date: previous.effective_date,
+            },
+            PriceWriteOutcome::SkippedOlder { existing_date } => ImportRowOutcome::SkippedOlder {
+                existing_effective_date: existing_date,
+            },
+        };
+
+        match &preview.outcome {
+            ImportRowOutcome::SkippedOlder { existing_effective_date } => {
+                result.skipped_imports += 1;
+                result.errors.push(format!(
+                    "Row {}: skipped, existing price for {} is dated {} which is not older than {}",
+                    row.row_number, row.label(), existing_effective_date, effective_date
+                ));
+            }
+            _ => {
+                result.successful_imports += 1;
+                run.written_prices.push(json!({
+                    "catalog_product_id": spec.catalog_product_id,
+                    "distributor_id": spec.distributor_id,
+                    "case_price": pricing.case_price,
+                    "total_preferred_units": pricing.total_preferred_units,
+                    "pricing_mode": pricing.mode.as_str(),
+                }));
+            }
+        }
+        run.row_outcomes.iter_mut().for_each(|outcomes| outcomes.push(preview.clone()));
+    }
+
+    Ok(())
+}
+
+// Queue the written prices for upload and record the import's final status
+pub async fn finish_import(
+    conn: &mut SqliteConnection,
+    prepared: &PreparedImport,
+    run: ImportRun,
+    restaurant_id: &str,
+    distributor_id: &str,
+) -> Result<(PriceImportResult, Vec<ImportRowPreview>), AppError> {
+    let ImportRun { result, row_outcomes, written_prices, .. } = run;
+
+    history::drop_superseded_uploads(&mut *conn, &result.import_id).await?;
+    quarantine::discard_superseded(&mut *conn, &result.import_id).await?;
+    if !written_prices.is_empty() {
+        queue_price_upload(
+            &mut *conn,
+            restaurant_id,
+            json!({
+                "import_id": result.import_id,
+                "distributor_id": distributor_id,
+                "effective_date": result.effective_date,
+                "file_name": prepared.file_name,
+                "source_type": prepared.source.format.price_source_type(),
+                "prices": written_prices,
+            }),
+        )
+        .await?;
+    }
+
+    let nothing_imported = result.successful_imports == 0 && result.quarantined_imports == 0;
+    let (status, error_message) = if nothing_imported && result.failed_imports > 0 {
+        ("failed", Some("No rows could be imported".to_string()))
+    } else {
+        ("completed", None)
+    };
+
+    sqlx::query(
+        r#"UPDATE csv_imports
+           SET row_count = ?, imported_count = ?, failed_count = ?, quarantined_count = ?, status = ?, error_message = ?
+           WHERE import_id = ?"#
+    )
+    .bind(result.total_rows)
+    .bind(result.successful_imports)
+    .bind(result.failed_imports)
+    .bind(result.quarantined_imports)
+    .bind(status)
+    .bind(&error_message)
+    .bind(&result.import_id)
+    .execute(&mut *conn)
+    .await?;
+
+    let mut row_outcomes = row_outcomes.unwrap_or_default();
+    row_outcomes.sort_by_key(|row| row.row_number);
+
+    Ok((result, row_outcomes))
+}
+
+// Look for the effective date where the distributor's export keeps it
+fn preset_effective_date(
+    preset: &ImportPreset,
+    table: &SourceTable,
+    file_path: &Path,
+    options: &ImportOptions,
+) -> Result<EffectiveDateSuggestion, AppError> {
+    match &preset.effective_date {
+        PresetDateLocation::Preamble => derive_effective_date(file_path, &header_text(table), options),
+        PresetDateLocation::FileName => derive_effective_date(file_path, &[], options),
+        PresetDateLocation::Column { .. } => {
+            derive_document_effective_date(file_path, presets::column_date(preset, table), options)
+        }
+    }
+}
+
+// Text in and above the header row, searched for an effective date
+fn header_text(table: &SourceTable) -> Vec<String> {
+    table.preamble.iter().chain(&table.headers).cloned().collect()
+}
+
+// Read price rows from a file's price table, returning the parsed rows and per-row parse errors
+pub fn read_price_rows(table: &SourceTable, mapping: &CsvColumnMapping) -> (Vec<PriceRow>, Vec<RowParseError>) {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for record in &table.records {
+        let row_number = record.row_number;
+        let record = match &record.cells {
+            Ok(cells) => cells,
+            Err(message) => {
+                errors.push(RowParseError {
+                    row_number,
+                    error_type: ImportErrorType::Parsing,
+                    message: message.clone(),
+                    raw: Vec::new(),
+                });
+                continue;
+            }
+        };
+
+        if record.iter().all(|field| field.is_empty()) {
+            continue;
+        }
+
+        let raw = record.clone();
+
+        let field = |field: CsvField| {
+            mapping
+                .column(field)
+                .and_then(|column| record.get(column))
+                .map(|value| value.trim())
+                .filter(|v| !v.is_empty())
+        };
+
+        // A row without an item code can still be matched by its description
+        let item_code = field(CsvField::ItemCode).map(str::to_string);
+        let description = field(CsvField::Description).map(str::to_string);
+        if item_code.is_none() && description.is_none() {
+            errors.push(RowParseError {
+                row_number,
+                error_type: ImportErrorType::Validation,
+                message: "missing item code".to_string(),
+                raw,
+            });
+            continue;
+        }
+
+        let case_price = match field(CsvField::CasePrice).map(parse_price) {
+            Some(Ok(price)) => price,
+            Some(Err(message)) => {
+                errors.push(RowParseError { row_number, error_type: ImportErrorType::Parsing, message, raw });
+                continue;
+            }
+            None => {
+                errors.push(RowParseError {
+                    row_number,
+                    error_type: ImportErrorType::Validation,
+                    message: "missing case price".to_string(),
+                    raw,
+                });
+                continue;
+            }
+        };
+
+        let pack_size = field(CsvField::PackSize).map(str::to_string);
+        let unit = field(CsvField::Unit).map(str::to_string);
+        rows.push(PriceRow {
+            row_number,
+            item_code,
+            description,
+            case_price,
+            price_unit: pack_size::catch_weight_price_unit(pack_size.as_deref(), unit.as_deref()),
+            pack_size,
+            unit,
+            raw,
+        });
+    }
+
+    (rows, errors)
+}
+
+// Parse a price cell such as "$1,234.50" into a number
+pub fn parse_price(value: &str) -> Result<f64, String> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !matches!(c, '$' | ',' | ' '))
+        .collect();
+
+    let price: f64 = cleaned
+        .parse()
+        .map_err(|_| format!("invalid case price '{}'", value))?;
+
+    // Zero prices are read so the import can hold them for review
+    if !price.is_finite() || price < 0.0 {
+        return Err(format!("case price can't be negative, got '{}'", value));
+    }
+
+    Ok(price)
+}
+
+// Look up the synced distributor spec for a distributor's item code
+pub async fn find_spec_by_item_code(
+    conn: &mut SqliteConnection,
+    distributor_id: &str,
+    item_code: &str,
+) -> Result<Option<DistributorSpec>, AppError> {
+    let spec = sqlx::query_as::<_, DistributorSpec>(
+        "SELECT * FROM distributor_specs WHERE distributor_id = ? AND distributor_item_code = ? LIMIT 1"
+    )
+    .bind(distributor_id)
+    .bind(item_code)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(spec)
+}
+
+// Write a price following the CSV import rule: only overwrite when the new effective_date is newer.
+// A same-day price from this same file is overwritten too, which is what a deliberate re-import means.
+// local_current_prices holds only the current price per product and distributor; the price it
+// replaced survives only as the before-image in csv_import_price_changes. created_at stays when
+// the first price for the product was written.
+pub async fn write_price(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    spec: &DistributorSpec,
+    pricing: CasePricing,
+    effective_date: NaiveDate,
+    origin: PriceOrigin<'_>,
+) -> Result<PriceWriteOutcome, AppError> {
+    let existing = sqlx::query_as::<_, LocalCurrentPrice>(
+        r#"SELECT * FROM local_current_prices
+           WHERE restaurant_id = ? AND catalog_product_id = ? AND distributor_id = ?
+           ORDER BY effective_date DESC LIMIT 1"#
+    )
+    .bind(restaurant_id)
+    .bind(&spec.catalog_product_id)
+    .bind(&spec.distributor_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match existing {
+        Some(previous)
+            if previous.effective_date > effective_date
+                || (previous.effective_date == effective_date
+                    && previous.source_file_hash.as_deref() != Some(origin.file_hash)) =>
+        {
+            Ok(PriceWriteOutcome::SkippedOlder { existing_date: previous.effective_date })
+        }
+        Some(previous) => {
+            sqlx::query(
+                r#"UPDATE local_current_prices
+                   SET case_price = ?, total_preferred_units = ?, pricing_mode = ?, effective_date = ?,
+                       source_type = ?, source_file_name = ?, source_file_hash = ?
+                   WHERE price_id = ?"#
+            )
+            .bind(pricing.case_price)
+            .bind(pricing.total_preferred_units)
+            .bind(pricing.mode.as_str())
+            .bind(effective_date)
+            .bind(origin.source_type)
+            .bind(origin.file_name)
+            .bind(origin.file_hash)
+            .bind(&previous.price_id)
+            .execute(&mut *conn)
+            .await?;
+
+            Ok(PriceWriteOutcome::Updated { previous: Box::new(previous) })
+        }
+        None => {
+            let price_id = Uuid::new_v4().to_string();
+            sqlx::query(
+                r#"INSERT INTO local_current_prices (price_id, restaurant_id, catalog_product_id, distributor_id,
+                   case_price, total_preferred_units, pricing_mode, effective_date, source_type, source_file_name,
+                   source_file_hash)
+                   VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+            )
+            .bind(&price_id)
+            .bind(restaurant_id)
+            .bind(&spec.catalog_product_id)
+            .bind(&spec.distributor_id)
+            .bind(pricing.case_price)
+            .bind(pricing.total_preferred_units)
+            .bind(pricing.mode.as_str())
+            .bind(effective_date)
+            .bind(origin.source_type)
+            .bind(origin.file_name)
+            .bind(origin.file_hash)
+            .execute(&mut *conn)
+            .await?;
+
+            Ok(PriceWriteOutcome::Inserted { price_id })
+        }
+    }
+}
+
+// The pricing for a matched row. Catch-weight rows are priced per unit, so their case is the
+// nominal weight from the row's pack size ("40 LB AVG"), or the spec's when the file has none.
+// None when the per-unit price can't be converted to the product's preferred measurement.
+async fn row_pricing(
+    conn: &mut SqliteConnection,
+    row: &PriceRow,
+    spec: &DistributorSpec,
+    pack: Option<&PackSize>,
+) -> Result<Option<CasePricing>, AppError> {
+    let Some(price_unit) = row.price_unit.as_deref() else {
+        return Ok(Some(CasePricing::by_case(row.case_price, spec)));
+    };
+
+    let preferred_measurement = sqlx::query_scalar::<_, String>(
+        "SELECT preferred_measurement FROM products WHERE catalog_product_id = ?"
+    )
+    .bind(&spec.catalog_product_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(unit_price) = preferred_measurement
+        .and_then(|preferred| pack_size::price_per_preferred_unit(row.case_price, price_unit, &preferred))
+    else {
+        return Ok(None);
+    };
+
+    let nominal_weight = pack
+        .and_then(|pack| pack.total_preferred_units)
+        .filter(|total| *total > 0.0)
+        .unwrap_or(spec.total_preferred_units);
+
+    Ok(Some(CasePricing {
+        case_price: unit_price * nominal_weight,
+        total_preferred_units: nominal_weight,
+        mode: PricingMode::CatchWeight,
+    }))
+}
+
+// Count a rejected row and record it in the import's error journal
+async fn reject_row(
+    conn: &mut SqliteConnection,
+    result: &mut PriceImportResult,
+    row_number: i32,
+    error_type: ImportErrorType,
+    message: &str,
+    raw: &[String],
+) -> Result<(), AppError> {
+    result.failed_imports += 1;
+    result.errors.push(format!("Row {}: {}", row_number, message));
+    journal::record_row_error(conn, &result.import_id, row_number, error_type, message, raw).await
+}
+
+// A row whose unit of measure can't describe the product, e.g. a "12 CT" pack for a product bought by weight
+async fn check_row_measurement(
+    conn: &mut SqliteConnection,
+    row: &PriceRow,
+    spec: &DistributorSpec,
+) -> Result<Option<String>, AppError> {
+    let Some(unit) = pack_size::measure_unit(row.pack_size.as_deref(), row.unit.as_deref()) else {
+        return Ok(None);
+    };
+    let Some(row_type) = units::measurement_type_of(&unit) else {
+        return Ok(None);
+    };
+
+    let product_type = sqlx::query_scalar::<_, String>(
+        "SELECT measurement_type FROM products WHERE catalog_product_id = ?"
+    )
+    .bind(&spec.catalog_product_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(product_type.filter(|product_type| product_type != row_type).map(|product_type| {
+        format!(
+            "unit '{}' is a {} measure but the product is measured by {}",
+            unit,
+            row_type,
+            product_type
+        )
+    }))
+}
+
+// Queue imported prices for the Monday upload
+async fn queue_price_upload(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    payload: serde_json::Value,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"INSERT INTO pending_sync_events (event_id, event_type, restaurant_id, payload, scheduled_for)
+           VALUES (?, 'csv_import', ?, ?, ?)"#
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(restaurant_id)
+    .bind(payload.to_string())
+    .bind(next_monday_sync_time())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Next Monday at 12:01 AM local time, when pending events are uploaded
+fn next_monday_sync_time() -> chrono::DateTime<Utc> {
+    let today = Local::now().date_naive();
+    let days_until_monday = 7 - today.weekday().num_days_from_monday() as i64;
+    let monday = today + Duration::days(days_until_monday);
+    let sync_time = monday.and_hms_opt(0, 1, 0).unwrap_or_default();
+
+    Local
+        .from_local_datetime(&sync_time)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| sync_time.and_utc())
+}
+
+// Resolve the column mapping for a file so the Import page can confirm ambiguous columns
+pub async fn preview_column_mapping(
+    pool: &DbPool,
+    file_path: &Path,
+    distributor_id: Option<&str>,
+    sheet: Option<&str>,
+    header_row: Option<i32>,
+) -> Result<CsvMappingPreview, AppError> {
+    let contents = std::fs::read(file_path)?;
+    let format = detect_format(&contents, file_path);
+    let edi_headers = match format {
+        SourceFormat::Edi832 => Some(edi::catalog::CATALOG_HEADERS.as_slice()),
+        SourceFormat::Edi810 => Some(edi::invoice::INVOICE_HEADERS.as_slice()),
+        _ => None,
+    };
+    if let Some(headers) = edi_headers {
+        return Ok(CsvMappingPreview {
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            mapping: CsvColumnMapping::default(),
+            mapping_source: MappingSource::Edi,
+            preset_id: None,
+            profile_drift: None,
+            needs_review: false,
+            source: ImportSource {
+                format,
+                file_format: None,
+                sheet_name: None,
+                header_row: 0,
+            },
+        });
+    }
+
+    let (profile, presets) = match distributor_id {
+        Some(distributor_id) => (
+            profiles::get_profile(pool, distributor_id).await?,
+            presets::presets_for_distributor(pool, distributor_id).await?,
+        ),
+        None => (None, Vec::new()),
+    };
+    let table = source::load_table(pool, &contents, file_path, profile.as_ref(), &presets, sheet, header_row).await?;
+    let resolved = profiles::resolve_mapping(profile.as_ref(), &presets, &table.headers, None)?;
+
+    Ok(CsvMappingPreview {
+        needs_review: resolved.mapping.needs_review() || resolved.drift.is_some(),
+        headers: table.headers,
+        mapping: resolved.mapping,
+        mapping_source: resolved.source,
+        preset_id: resolved.preset.map(|preset| preset.preset_id),
+        profile_drift: resolved.drift,
+        source: table.source,
     })
 }
 
-#[derive(Debug, Clone)]
-pub struct CsvColumnMapping {
-    pub item_code_column: usize,
-    pub description_column: usize,
-    pub case_price_column: usize,
-    pub pack_size_column: usize,
-    pub unit_column: usize,
-}
\ No newline at end of file
+// Sheet names of an Excel workbook, empty for CSV files
+pub fn list_sheets(file_path: &Path) -> Result<Vec<String>, AppError> {
+    let contents = std::fs::read(file_path)?;
+    excel::sheet_names(&contents, detect_format(&contents, file_path))
+}
+
+// The earlier import of this file's content, if it has been imported before
+pub async fn find_duplicate_import(pool: &DbPool, file_path: &Path) -> Result<Option<CsvImport>, AppError> {
+    let contents = std::fs::read(file_path)?;
+    let file_hash = history::file_hash(&contents, detect_format(&contents, file_path));
+    let mut conn = pool.acquire().await?;
+    history::find_active_import(&mut conn, &file_hash).await
+}
+
+// Suggest an effective_date for a file before importing it
+pub fn suggest_effective_date(file_path: &Path) -> Result<EffectiveDateSuggestion, AppError> {
+    let contents = std::fs::read(file_path)?;
+    match detect_format(&contents, file_path) {
+        SourceFormat::Edi832 => {
+            let catalog = edi::catalog::parse_price_catalog(&String::from_utf8_lossy(&contents))?;
+            return derive_document_effective_date(file_path, catalog.document_date, &ImportOptions::default());
+        }
+        SourceFormat::Edi810 => {
+            let invoices = edi::invoice::parse_invoices(&String::from_utf8_lossy(&contents))?;
+            return derive_document_effective_date(
+                file_path,
+                invoices::invoice_date(&invoices[0]),
+                &ImportOptions::default(),
+            );
+        }
+        _ => {}
+    }
+
+    let table = read_file_table(file_path)?;
+    derive_effective_date(file_path, &header_text(&table), &ImportOptions::default())
+}
+
+// Check that a file is a readable price sheet with item code and case price columns,
+// an EDI 832 catalog with at least one priced item or an EDI 810 invoice with lines
+pub fn validate_csv_format(file_path: &Path) -> Result<bool, AppError> {
+    let contents = std::fs::read(file_path)?;
+    match detect_format(&contents, file_path) {
+        SourceFormat::Edi832 => {
+            let catalog = edi::catalog::parse_price_catalog(&String::from_utf8_lossy(&contents))?;
+            return Ok(!catalog.rows.is_empty());
+        }
+        SourceFormat::Edi810 => {
+            let invoices = edi::invoice::parse_invoices(&String::from_utf8_lossy(&contents))?;
+            return Ok(invoices.iter().any(|invoice| !invoice.lines.is_empty()));
+        }
+        _ => {}
+    }
+
+    let table = read_file_table(file_path)?;
+    let mapping = detect_column_mapping(&table.headers)?;
+
+    Ok(require_columns(&mapping, &table.headers).is_ok())
+}
+
+// Read a file's price table without settings or profiles, sniffing the CSV dialect
+fn read_file_table(file_path: &Path) -> Result<SourceTable, AppError> {
+    let contents = std::fs::read(file_path)?;
+    source::read_table(&contents, detect_format(&contents, file_path), None, None, &[], None, None)
+}
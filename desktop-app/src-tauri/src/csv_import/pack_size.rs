@@ -0,0 +1,232 @@
+use crate::db::models::{DistributorSpec, PackSize};
+
+use super::units::{canonical_unit, convert, measurement_type_of};
+
+// Totals within this fraction of the spec's are treated as the same case
+const TOTAL_TOLERANCE: f64 = 0.01;
+
+// Parse a distributor pack/size string into spec fields:
+//   "2/5 LB" -> 2 packs of 5 lb, "12/32 OZ" -> 12 x 32 oz, "1/25#" -> 1 x 25 lb,
+//   "6/#10" -> 6 #10 cans, "40 CT" -> 1 x 40 ct
+// `unit_hint` (the file's unit column) is used when the string has no unit of its own.
+pub fn parse_pack_size(text: &str, unit_hint: Option<&str>) -> Option<PackSize> {
+    let text = text.trim().to_uppercase();
+    let (case_packs, size) = split_case_packs(&text);
+
+    // "#10" at the start of the size is a can size, not a pound mark
+    if let Some(can) = size.strip_prefix('#') {
+        let can_size: String = can.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if !can_size.is_empty() {
+            return Some(PackSize {
+                case_packs,
+                pack_size: 1.0,
+                pack_unit_of_measure: format!("#{} can", can_size),
+                total_preferred_units: None,
+            });
+        }
+    }
+
+    let number: String = size
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let pack_size: f64 = number.parse().ok()?;
+    if !pack_size.is_finite() || pack_size <= 0.0 {
+        return None;
+    }
+
+    let unit = match parse_unit(&size[number.len()..]) {
+        Some(unit) => unit,
+        None => canonical_unit(unit_hint.filter(|u| !u.trim().is_empty())?),
+    };
+
+    Some(PackSize {
+        case_packs,
+        pack_size,
+        pack_unit_of_measure: unit,
+        total_preferred_units: None,
+    })
+}
+
+// "6/..." or "6X..." -> (6, "..."); anything else is a single pack
+fn split_case_packs(text: &str) -> (i32, &str) {
+    let digits = text.chars().take_while(|c| c.is_ascii_digit()).count();
+    let rest = text[digits..].trim_start();
+
+    if digits > 0 {
+        if let Some(size) = rest.strip_prefix('/').or_else(|| rest.strip_prefix('X')) {
+            let size = size.trim_start();
+            let starts_size = size.starts_with(|c: char| c.is_ascii_digit() || c == '#' || c == '.');
+            if let (true, Ok(case_packs)) = (starts_size, text[..digits].parse::<i32>()) {
+                if case_packs > 0 {
+                    return (case_packs, size);
+                }
+            }
+        }
+    }
+
+    (1, text)
+}
+
+// The unit after the size number: "LB", " GAL", "#", "FL OZ", "LBA" (average weight)
+fn parse_unit(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.starts_with('#') {
+        return Some("lb".to_string());
+    }
+
+    let mut words = text.split_whitespace();
+    let first: String = words
+        .next()?
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+
+    let unit = match first.as_str() {
+        "" => return None,
+        "FL" if words.next().is_some_and(|w| w.starts_with("OZ")) => "floz".to_string(),
+        "LBA" | "LBAVG" => "lb".to_string(),
+        other => canonical_unit(other),
+    };
+
+    Some(unit)
+}
+
+// Fill in total_preferred_units for the product's preferred measurement ("lb", "gal", "ea", ...)
+pub fn with_preferred_units(mut pack: PackSize, preferred_measurement: &str) -> PackSize {
+    let quantity = pack.case_packs as f64 * pack.pack_size;
+
+    pack.total_preferred_units = if canonical_unit(&pack.pack_unit_of_measure) == canonical_unit(preferred_measurement) {
+        Some(quantity)
+    } else if pack.pack_unit_of_measure.ends_with(" can") {
+        convert(quantity, "ea", preferred_measurement)
+    } else {
+        convert(quantity, &pack.pack_unit_of_measure, preferred_measurement)
+    };
+
+    pack
+}
+
+// Whether a parsed pack size describes a different case than the synced spec. Cases that
+// hold the same amount ("2/5 LB" against 1 x 10 lb) agree; when the amount can't be
+// worked out the pack fields themselves must match.
+pub fn disagrees_with_spec(pack: &PackSize, spec: &DistributorSpec) -> bool {
+    match pack.total_preferred_units {
+        Some(total) => (total - spec.total_preferred_units).abs() > spec.total_preferred_units.abs() * TOTAL_TOLERANCE,
+        None => {
+            pack.case_packs != spec.case_packs
+                || (pack.pack_size - spec.pack_size).abs() > f64::EPSILON
+                || canonical_unit(&pack.pack_unit_of_measure) != canonical_unit(&spec.pack_unit_of_measure)
+        }
+    }
+}
+
+// Whether a pack size marks a catch-weight (random weight) item: "40 LB AVG", "4/10# CW",
+// "CATCH WT", "40 LBA"
+pub fn is_catch_weight(text: &str) -> bool {
+    text.to_uppercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| {
+            let word = word.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+            matches!(word, "AVG" | "CW" | "CATCH" | "RANDOM" | "RW" | "LBA" | "LBAVG")
+        })
+}
+
+// The weight unit a catch-weight row is priced per, from its unit column or else its pack
+// size. None when the row isn't catch weight or its price is for the whole case ("CS").
+pub fn catch_weight_price_unit(pack_size: Option<&str>, unit: Option<&str>) -> Option<String> {
+    let pack_size = pack_size.filter(|text| is_catch_weight(text))?;
+
+    let unit = match unit.map(str::trim).filter(|unit| !unit.is_empty()) {
+        Some(unit) => canonical_unit(unit),
+        None => parse_pack_size(pack_size, None)?.pack_unit_of_measure,
+    };
+    (measurement_type_of(&unit) == Some("weight")).then_some(unit)
+}
+
+// The unit a row is measured in: the pack size's own unit ("4/1 GAL"), otherwise the unit
+// column when it names a weight or volume. A count there ("EA", "CS") is usually the unit
+// the item is ordered in, which says nothing about how the product is measured.
+pub fn measure_unit(pack_size: Option<&str>, unit: Option<&str>) -> Option<String> {
+    if let Some(pack) = pack_size.and_then(|text| parse_pack_size(text, None)) {
+        return Some(pack.pack_unit_of_measure);
+    }
+
+    let unit = unit.map(str::trim).filter(|unit| !unit.is_empty())?;
+    matches!(measurement_type_of(unit), Some("weight" | "volume")).then(|| canonical_unit(unit))
+}
+
+// Price per preferred unit for a price quoted per `price_unit` ($/lb for a product counted in oz)
+pub fn price_per_preferred_unit(price: f64, price_unit: &str, preferred_measurement: &str) -> Option<f64> {
+    let preferred_per_unit = convert(1.0, price_unit, preferred_measurement)?;
+    (preferred_per_unit > 0.0).then(|| price / preferred_per_unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> (i32, f64, String) {
+        let pack = parse_pack_size(text, None).unwrap();
+        (pack.case_packs, pack.pack_size, pack.pack_unit_of_measure)
+    }
+
+    #[test]
+    fn parses_pack_sizes() {
+        assert_eq!(parse("2/5 LB"), (2, 5.0, "lb".to_string()));
+        assert_eq!(parse("12/32 OZ"), (12, 32.0, "oz".to_string()));
+        assert_eq!(parse("1/25#"), (1, 25.0, "lb".to_string()));
+        assert_eq!(parse("4/1 GAL"), (4, 1.0, "gal".to_string()));
+        assert_eq!(parse("6X5LB"), (6, 5.0, "lb".to_string()));
+        assert_eq!(parse("40 CT"), (1, 40.0, "ct".to_string()));
+        assert_eq!(parse("12/16 FL OZ"), (12, 16.0, "floz".to_string()));
+        assert_eq!(parse("40 LBA"), (1, 40.0, "lb".to_string()));
+    }
+
+    #[test]
+    fn reads_a_leading_pound_mark_as_a_can_size() {
+        assert_eq!(parse("6/#10"), (6, 1.0, "#10 can".to_string()));
+        assert_eq!(parse("#10"), (1, 1.0, "#10 can".to_string()));
+    }
+
+    #[test]
+    fn falls_back_to_the_unit_column() {
+        let pack = parse_pack_size("25", Some("LBS")).unwrap();
+        assert_eq!((pack.case_packs, pack.pack_size, pack.pack_unit_of_measure.as_str()), (1, 25.0, "lb"));
+        assert!(parse_pack_size("25", None).is_none());
+        assert!(parse_pack_size("LB", Some("LB")).is_none());
+        assert!(parse_pack_size("0/5 LB", None).is_none());
+    }
+
+    #[test]
+    fn converts_to_preferred_units() {
+        let total = |text: &str, preferred: &str| {
+            with_preferred_units(parse_pack_size(text, None).unwrap(), preferred).total_preferred_units
+        };
+        assert_eq!(total("2/5 LB", "lb"), Some(10.0));
+        assert_eq!(total("12/32 OZ", "lb"), Some(24.0));
+        assert_eq!(total("4/1 GAL", "gal"), Some(4.0));
+        assert_eq!(total("4/1 GAL", "qt"), Some(16.0));
+        assert_eq!(total("6/#10", "ea"), Some(6.0));
+        assert_eq!(total("4/1 GAL", "lb"), None);
+    }
+
+    #[test]
+    fn measures_rows_by_the_pack_size_before_the_order_unit() {
+        assert_eq!(measure_unit(Some("1/25 LB"), Some("CS")), Some("lb".to_string()));
+        assert_eq!(measure_unit(Some("12 CT"), Some("LB")), Some("ct".to_string()));
+        assert_eq!(measure_unit(None, Some("LBS")), Some("lb".to_string()));
+        assert_eq!(measure_unit(None, Some("EA")), None);
+        assert_eq!(measure_unit(Some("25"), Some("CS")), None);
+    }
+
+    #[test]
+    fn spots_catch_weight() {
+        assert!(is_catch_weight("40 LB AVG"));
+        assert!(is_catch_weight("4/10# CW"));
+        assert!(is_catch_weight("40 LBA"));
+        assert!(!is_catch_weight("4/1 GAL"));
+        assert_eq!(catch_weight_price_unit(Some("40 LB AVG"), None), Some("lb".to_string()));
+        assert_eq!(catch_weight_price_unit(Some("40 LB AVG"), Some("CS")), None);
+    }
+}
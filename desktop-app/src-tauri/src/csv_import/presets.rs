@@ -0,0 +1,310 @@
+use crate::db::{
+    models::{ColumnMatch, CsvColumnMapping, CsvField, ImportPreset, PresetDateLocation},
+    DbPool,
+};
+use crate::error::AppError;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use super::effective_date::find_date_in_text;
+use super::mapping::{detect_column_mapping, normalize_header};
+use super::source::SourceTable;
+use super::PriceRow;
+
+// Preset folders: the ones shipped with the app, then <app data>/import_presets for
+// presets added later. A preset in the second folder replaces a shipped one with the same id.
+pub fn preset_dirs(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(resources) = app.path().resource_dir() {
+        dirs.push(resources.join("presets"));
+    }
+    if let Ok(app_data) = app.path().app_data_dir() {
+        dirs.push(app_data.join("import_presets"));
+    }
+    dirs
+}
+
+// Replace the stored presets with the *.json files in `dirs`. Files that can't be read
+// are logged and skipped so one bad preset doesn't take the others down.
+pub async fn load_presets(pool: &DbPool, dirs: &[PathBuf]) -> Result<usize, AppError> {
+    let mut presets: BTreeMap<String, (ImportPreset, PathBuf)> = BTreeMap::new();
+
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")))
+            .collect();
+        files.sort();
+
+        for file in files {
+            match read_preset(&file) {
+                Ok(preset) => {
+                    presets.insert(preset.preset_id.clone(), (preset, file));
+                }
+                Err(e) => warn!("Skipping import preset {}: {}", file.display(), e),
+            }
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM import_presets").execute(&mut *tx).await?;
+
+    for (preset, file) in presets.values() {
+        sqlx::query(
+            "INSERT INTO import_presets (preset_id, distributor_code, definition, source_file) VALUES (?, ?, ?, ?)"
+        )
+        .bind(&preset.preset_id)
+        .bind(preset.distributor_code.to_uppercase())
+        .bind(serde_json::to_string(preset)?)
+        .bind(file.display().to_string())
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    info!("Loaded {} import presets", presets.len());
+
+    Ok(presets.len())
+}
+
+fn read_preset(file: &Path) -> Result<ImportPreset, AppError> {
+    let preset: ImportPreset = serde_json::from_str(&std::fs::read_to_string(file)?)?;
+
+    if preset.preset_id.trim().is_empty() || preset.distributor_code.trim().is_empty() {
+        return Err(AppError::Validation("preset_id and distributor_code are required".to_string()));
+    }
+    for field in [CsvField::ItemCode, CsvField::CasePrice] {
+        if preset.columns.get(field).is_empty() {
+            return Err(AppError::Validation(format!("no {} column names", field.label())));
+        }
+    }
+
+    Ok(preset)
+}
+
+pub async fn list_presets(pool: &DbPool) -> Result<Vec<ImportPreset>, AppError> {
+    let definitions: Vec<String> = sqlx::query_scalar(
+        "SELECT definition FROM import_presets ORDER BY distributor_code, preset_id"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    definitions
+        .iter()
+        .map(|definition| Ok(serde_json::from_str(definition)?))
+        .collect()
+}
+
+// Presets for a distributor, found through its distributor_code
+pub async fn presets_for_distributor(pool: &DbPool, distributor_id: &str) -> Result<Vec<ImportPreset>, AppError> {
+    let definitions: Vec<String> = sqlx::query_scalar(
+        r#"SELECT p.definition FROM import_presets p
+           JOIN distributors d ON UPPER(d.distributor_code) = p.distributor_code
+           WHERE d.distributor_id = ?
+           ORDER BY p.preset_id"#
+    )
+    .bind(distributor_id)
+    .fetch_all(pool)
+    .await?;
+
+    definitions
+        .iter()
+        .map(|definition| Ok(serde_json::from_str(definition)?))
+        .collect()
+}
+
+// Index of the first header matching one of `names`, compared the way profiles compare headers
+pub fn find_column(headers: &[String], names: &[String]) -> Option<usize> {
+    names.iter().find_map(|name| {
+        let wanted = normalize_header(name);
+        headers.iter().position(|header| normalize_header(header) == wanted)
+    })
+}
+
+// Whether a row has the preset's item code and case price columns
+pub fn matches_header(preset: &ImportPreset, headers: &[String]) -> bool {
+    [CsvField::ItemCode, CsvField::CasePrice]
+        .into_iter()
+        .all(|field| find_column(headers, preset.columns.get(field)).is_some())
+}
+
+// The preset that fits these headers best, with its mapping. Columns the preset names but
+// the file lacks fall back to header detection, marked for review.
+pub fn match_preset<'a>(
+    presets: &'a [ImportPreset],
+    headers: &[String],
+) -> Result<Option<(&'a ImportPreset, CsvColumnMapping)>, AppError> {
+    let Some(preset) = presets
+        .iter()
+        .filter(|preset| matches_header(preset, headers))
+        .max_by_key(|preset| {
+            CsvField::ALL
+                .into_iter()
+                .filter(|field| find_column(headers, preset.columns.get(*field)).is_some())
+                .count()
+        })
+    else {
+        return Ok(None);
+    };
+
+    let detected = detect_column_mapping(headers)?;
+    let pack_count = find_column(headers, &preset.pack_count_columns);
+    let mut mapping = CsvColumnMapping::default();
+
+    for field in CsvField::ALL {
+        let column = match find_column(headers, preset.columns.get(field)) {
+            Some(index) => Some(ColumnMatch {
+                column_index: index,
+                header: headers[index].clone(),
+                confidence: 1.0,
+                ambiguous: false,
+            }),
+            None => detected
+                .get(field)
+                .filter(|m| Some(m.column_index) != pack_count)
+                .cloned()
+                .map(|m| ColumnMatch {
+                    ambiguous: m.ambiguous || !preset.columns.get(field).is_empty(),
+                    ..m
+                }),
+        };
+        mapping.set(field, column);
+    }
+
+    Ok(Some((preset, mapping)))
+}
+
+// Join a separate pack count column onto the size, so "6" and "#10" read as "6/#10"
+pub fn apply_pack_count(preset: &ImportPreset, headers: &[String], rows: &mut [PriceRow]) {
+    let Some(column) = find_column(headers, &preset.pack_count_columns) else {
+        return;
+    };
+
+    for row in rows.iter_mut() {
+        let count = row.raw.get(column).map(|value| value.trim()).filter(|value| !value.is_empty());
+        if let (Some(count), Some(size)) = (count, row.pack_size.as_deref()) {
+            if !size.contains('/') && count.chars().all(|c| c.is_ascii_digit()) {
+                row.pack_size = Some(format!("{}/{}", count, size));
+            }
+        }
+    }
+}
+
+// The first date in the preset's date column, for presets whose exports carry one
+pub fn column_date(preset: &ImportPreset, table: &SourceTable) -> Option<(NaiveDate, String)> {
+    let PresetDateLocation::Column { headers } = &preset.effective_date else {
+        return None;
+    };
+    let column = find_column(&table.headers, headers)?;
+
+    table.records.iter().find_map(|record| {
+        let value = record.cells.as_ref().ok()?.get(column)?;
+        find_date_in_text(value).map(|date| (date, format!("Column \"{}\" value \"{}\"", table.headers[column], value)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shipped_presets() -> Vec<ImportPreset> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("presets");
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        files
+            .iter()
+            .map(|file| read_preset(file).unwrap_or_else(|e| panic!("{}: {}", file.display(), e)))
+            .collect()
+    }
+
+    fn headers(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn mapped(mapping: &CsvColumnMapping) -> Vec<Option<(usize, bool)>> {
+        CsvField::ALL
+            .into_iter()
+            .map(|field| mapping.get(field).map(|m| (m.column_index, m.ambiguous)))
+            .collect()
+    }
+
+    fn row(raw: &[&str], pack_size: &str) -> PriceRow {
+        PriceRow {
+            row_number: 2,
+            item_code: Some(raw[0].to_string()),
+            description: None,
+            case_price: 40.0,
+            pack_size: Some(pack_size.to_string()),
+            unit: None,
+            price_unit: None,
+            raw: raw.iter().map(|value| value.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn reads_every_shipped_preset() {
+        let presets = shipped_presets();
+
+        let ids: Vec<&str> = presets.iter().map(|preset| preset.preset_id.as_str()).collect();
+        assert_eq!(ids, ["pfg-price-list", "rd-price-list", "sysco-order-guide", "usf-order-guide"]);
+    }
+
+    #[test]
+    fn maps_a_sysco_order_guide_header() {
+        let presets = shipped_presets();
+        let headers = headers(&["SUPC", "Pack", "Size", "Unit", "Brand", "Desc", "Case $"]);
+
+        let (preset, mapping) = match_preset(&presets, &headers).unwrap().unwrap();
+        assert_eq!(preset.preset_id, "sysco-order-guide");
+        // Item code, description, case price, pack size, unit
+        assert_eq!(
+            mapped(&mapping),
+            [Some((0, false)), Some((5, false)), Some((6, false)), Some((2, false)), Some((3, false))]
+        );
+        assert_eq!(find_column(&headers, &preset.pack_count_columns), Some(1));
+    }
+
+    #[test]
+    fn picks_the_preset_naming_the_most_columns() {
+        let presets = shipped_presets();
+
+        // Sysco, PFG and Restaurant Depot all know these; only Restaurant Depot names "Pack Size" too
+        let headers = headers(&["Item Number", "Description", "Pack Size", "Case Price"]);
+        let (preset, mapping) = match_preset(&presets, &headers).unwrap().unwrap();
+        assert_eq!(preset.preset_id, "rd-price-list");
+        assert_eq!(mapping.get(CsvField::PackSize).map(|m| m.column_index), Some(2));
+
+        // Without an item code and a case price column no preset applies
+        assert!(match_preset(&presets, &headers[1..]).unwrap().is_none());
+    }
+
+    #[test]
+    fn joins_a_pack_count_onto_sizes_without_one() {
+        let presets = shipped_presets();
+        let sysco = presets.iter().find(|preset| preset.preset_id == "sysco-order-guide").unwrap();
+        let headers = headers(&["SUPC", "Pack", "Size", "Desc", "Case $"]);
+
+        let mut rows = vec![
+            row(&["111", "6", "#10", "Beans", "40.00"], "#10"),
+            row(&["222", "2", "2/5 LB", "Cheese", "40.00"], "2/5 LB"),
+            row(&["333", "", "25 LB", "Onions", "40.00"], "25 LB"),
+            row(&["444", "CS", "12 CT", "Limes", "40.00"], "12 CT"),
+        ];
+        apply_pack_count(sysco, &headers, &mut rows);
+
+        let sizes: Vec<_> = rows.iter().map(|row| row.pack_size.as_deref().unwrap()).collect();
+        assert_eq!(sizes, ["6/#10", "2/5 LB", "25 LB", "12 CT"]);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::db::{
+    models::{
+        ColumnMatch, CsvColumnMapping, CsvField, CsvMappingProfile, ImportPreset, MappingProfileDrift, MappingSource,
+    },
+    DbPool,
+};
+use crate::error::AppError;
+
+use super::dialect::{parse_delimiter, parse_encoding};
+use super::mapping::{detect_column_mapping, normalize_header};
+use super::presets::match_preset;
+
+// The mapping an import will use and where it came from
+#[derive(Debug, Clone)]
+pub struct ResolvedMapping {
+    pub mapping: CsvColumnMapping,
+    pub source: MappingSource,
+    pub drift: Option<MappingProfileDrift>,
+    pub preset: Option<ImportPreset>, // The distributor preset the mapping came from
+}
+
+pub async fn get_profile(pool: &DbPool, distributor_id: &str) -> Result<Option<CsvMappingProfile>, AppError> {
+    let profile = sqlx::query_as::<_, CsvMappingProfile>(
+        "SELECT * FROM csv_mapping_profiles WHERE distributor_id = ?"
+    )
+    .bind(distributor_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(profile)
+}
+
+pub async fn list_profiles(pool: &DbPool) -> Result<Vec<CsvMappingProfile>, AppError> {
+    let profiles = sqlx::query_as::<_, CsvMappingProfile>(
+        r#"SELECT p.* FROM csv_mapping_profiles p
+           LEFT JOIN distributors d ON d.distributor_id = p.distributor_id
+           ORDER BY d.distributor_name"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(profiles)
+}
+
+// Create or replace the saved mapping for a distributor. A delimiter or encoding
+// here overrides the global csv_delimiter / csv_encoding settings for its files.
+pub async fn save_profile(
+    pool: &DbPool,
+    distributor_id: &str,
+    mapping: &CsvColumnMapping,
+    headers: &[String],
+    delimiter: Option<&str>,
+    encoding: Option<&str>,
+) -> Result<CsvMappingProfile, AppError> {
+    if let Some(delimiter) = delimiter.filter(|d| *d != "auto" && parse_delimiter(d).is_none()) {
+        return Err(AppError::Validation(format!("Unsupported CSV delimiter '{}'", delimiter)));
+    }
+    if let Some(encoding) = encoding.filter(|e| *e != "auto" && parse_encoding(e).is_none()) {
+        return Err(AppError::Validation(format!("Unknown text encoding '{}'", encoding)));
+    }
+
+    sqlx::query(
+        r#"INSERT INTO csv_mapping_profiles (distributor_id, column_mapping, headers, delimiter, encoding)
+           VALUES (?, ?, ?, ?, ?)
+           ON CONFLICT(distributor_id) DO UPDATE SET
+               column_mapping = excluded.column_mapping,
+               headers = excluded.headers,
+               delimiter = excluded.delimiter,
+               encoding = excluded.encoding,
+               updated_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(distributor_id)
+    .bind(serde_json::to_string(mapping)?)
+    .bind(serde_json::to_string(headers)?)
+    .bind(delimiter)
+    .bind(encoding)
+    .execute(pool)
+    .await?;
+
+    get_profile(pool, distributor_id)
+        .await?
+        .ok_or_else(|| AppError::Internal("Mapping profile was not saved".to_string()))
+}
+
+pub async fn delete_profile(pool: &DbPool, distributor_id: &str) -> Result<(), AppError> {
+    let deleted = sqlx::query("DELETE FROM csv_mapping_profiles WHERE distributor_id = ?")
+        .bind(distributor_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("No mapping profile for distributor {}", distributor_id)));
+    }
+
+    Ok(())
+}
+
+// Pick the mapping for a file: the user's confirmed mapping, then the distributor's profile,
+// then a built-in preset for the distributor's export, then detection
+pub fn resolve_mapping(
+    profile: Option<&CsvMappingProfile>,
+    presets: &[ImportPreset],
+    headers: &[String],
+    provided: Option<CsvColumnMapping>,
+) -> Result<ResolvedMapping, AppError> {
+    if let Some(mapping) = provided {
+        return Ok(ResolvedMapping {
+            mapping,
+            source: MappingSource::Provided,
+            drift: None,
+            preset: None,
+        });
+    }
+
+    if let Some(profile) = profile {
+        let (mapping, drift) = apply_profile(profile, headers)?;
+        return Ok(ResolvedMapping {
+            mapping,
+            source: MappingSource::Profile,
+            drift,
+            preset: None,
+        });
+    }
+
+    if let Some((preset, mapping)) = match_preset(presets, headers)? {
+        return Ok(ResolvedMapping {
+            mapping,
+            source: MappingSource::Preset,
+            drift: None,
+            preset: Some(preset.clone()),
+        });
+    }
+
+    Ok(ResolvedMapping {
+        mapping: detect_column_mapping(headers)?,
+        source: MappingSource::Detected,
+        drift: None,
+        preset: None,
+    })
+}
+
+// Apply a saved profile by header name, so reordered columns still map correctly.
+// Fields whose saved header has disappeared fall back to detection and are marked ambiguous.
+pub fn apply_profile(
+    profile: &CsvMappingProfile,
+    headers: &[String],
+) -> Result<(CsvColumnMapping, Option<MappingProfileDrift>), AppError> {
+    let saved: CsvColumnMapping = serde_json::from_str(&profile.column_mapping)?;
+    let saved_headers: Vec<String> = serde_json::from_str(&profile.headers)?;
+    let detected = detect_column_mapping(headers)?;
+
+    let mut mapping = CsvColumnMapping::default();
+    let mut missing_headers = Vec::new();
+
+    for field in CsvField::ALL {
+        let Some(saved_match) = saved.get(field) else {
+            continue;
+        };
+
+        let wanted = normalize_header(&saved_match.header);
+        let found = headers
+            .iter()
+            .position(|header| normalize_header(header) == wanted);
+
+        let column = match found {
+            Some(index) => Some(ColumnMatch {
+                column_index: index,
+                header: headers[index].clone(),
+                confidence: 1.0,
+                ambiguous: false,
+            }),
+            None => {
+                missing_headers.push(saved_match.header.clone());
+                detected.get(field).cloned().map(|m| ColumnMatch { ambiguous: true, ..m })
+            }
+        };
+        mapping.set(field, column);
+    }
+
+    let normalized = |list: &[String]| list.iter().map(|h| normalize_header(h)).collect::<Vec<_>>();
+    let drifted = !missing_headers.is_empty() || normalized(&saved_headers) != normalized(headers);
+
+    let drift = drifted.then(|| MappingProfileDrift {
+        saved_headers,
+        current_headers: headers.to_vec(),
+        missing_headers,
+        suggested_mapping: mapping.clone(),
+    });
+
+    Ok((mapping, drift))
+}
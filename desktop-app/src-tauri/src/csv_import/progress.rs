@@ -0,0 +1,55 @@
+use crate::db::models::ImportProgress;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+
+type ProgressCallback = Box<dyn Fn(&ImportProgress) + Send + Sync>;
+
+// Progress reporting and cancellation for one running import. Clones share the same state,
+// so the import and the cancel command can each hold one.
+#[derive(Clone, Default)]
+pub struct ImportControl {
+    inner: Arc<ControlState>,
+}
+
+#[derive(Default)]
+struct ControlState {
+    import_id: OnceLock<String>, // Known once the csv_imports row exists
+    cancelled: AtomicBool,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl ImportControl {
+    pub fn new(on_progress: impl Fn(&ImportProgress) + Send + Sync + 'static) -> Self {
+        ImportControl {
+            inner: Arc::new(ControlState {
+                on_progress: Some(Box::new(on_progress)),
+                ..Default::default()
+            }),
+        }
+    }
+
+    pub fn import_id(&self) -> Option<&str> {
+        self.inner.import_id.get().map(String::as_str)
+    }
+
+    // Stop the import before its next batch
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    // Whether two handles control the same import
+    pub fn same_as(&self, other: &ImportControl) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn report(&self, progress: &ImportProgress) {
+        self.inner.import_id.get_or_init(|| progress.import_id.clone());
+        if let Some(on_progress) = &self.inner.on_progress {
+            on_progress(progress);
+        }
+    }
+}
@@ -0,0 +1,568 @@
+use crate::db::{
+    models::{
+        DistributorSpec, LocalCurrentPrice, PriceAnomalyThresholds, PriceAnomalyType, PricingMode,
+        QuarantineReviewResult, QuarantinedPrice,
+    },
+    DbPool,
+};
+use crate::error::AppError;
+use chrono::{Duration, NaiveDate};
+use serde_json::json;
+use sqlx::SqliteConnection;
+use tracing::info;
+use uuid::Uuid;
+
+use super::{queue_price_upload, rollback, write_price, CasePricing, PriceOrigin, PriceWriteOutcome};
+
+// Ratios between a new and a recent unit price that look like a case priced as a single
+// pack, or the other way round ($24 a case of 12 read as $24 each)
+const PACK_MULTIPLES: [f64; 9] = [2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 24.0, 48.0];
+const PACK_MULTIPLE_TOLERANCE: f64 = 0.03;
+
+// Why a price should be held, as found by check_price
+#[derive(Debug, Clone)]
+pub struct PriceAnomaly {
+    pub anomaly_type: PriceAnomalyType,
+    pub reason: String,
+    pub unit_price: f64,
+    pub reference_unit_price: Option<f64>,
+    pub change_percent: Option<f64>,
+}
+
+// Compare a new price with the product's recent prices. Returns the anomaly when it should
+// be held for approval; prices with nothing recent to compare with pass unless they're zero.
+pub async fn check_price(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    spec: &DistributorSpec,
+    pricing: CasePricing,
+    effective_date: NaiveDate,
+) -> Result<Option<PriceAnomaly>, AppError> {
+    let unit_price = pricing.unit_price();
+    if pricing.case_price <= 0.0 {
+        return Ok(Some(PriceAnomaly {
+            anomaly_type: PriceAnomalyType::ZeroPrice,
+            reason: "case price is zero".to_string(),
+            unit_price,
+            reference_unit_price: None,
+            change_percent: None,
+        }));
+    }
+
+    // A price older than the current one is skipped by write_price anyway
+    let latest = sqlx::query_as::<_, LocalCurrentPrice>(
+        r#"SELECT * FROM local_current_prices
+           WHERE restaurant_id = ? AND catalog_product_id = ? AND distributor_id = ?
+           ORDER BY effective_date DESC LIMIT 1"#
+    )
+    .bind(restaurant_id)
+    .bind(&spec.catalog_product_id)
+    .bind(&spec.distributor_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if latest.is_some_and(|latest| latest.effective_date > effective_date) {
+        return Ok(None);
+    }
+
+    let thresholds = thresholds_for_product(&mut *conn, &spec.catalog_product_id).await?;
+    let since = effective_date - Duration::days(thresholds.history_days as i64);
+    let Some(reference) = recent_unit_price(&mut *conn, restaurant_id, spec, since).await? else {
+        return Ok(None);
+    };
+
+    Ok(compare_unit_prices(unit_price, reference, &thresholds))
+}
+
+// Judge a unit price against the recent one: about a whole pack multiple away, or further
+// up or down than the thresholds allow
+fn compare_unit_prices(
+    unit_price: f64,
+    reference: f64,
+    thresholds: &PriceAnomalyThresholds,
+) -> Option<PriceAnomaly> {
+    let ratio = unit_price / reference;
+    let change_percent = (ratio - 1.0) * 100.0;
+    let anomaly = |anomaly_type, reason| {
+        Some(PriceAnomaly {
+            anomaly_type,
+            reason,
+            unit_price,
+            reference_unit_price: Some(reference),
+            change_percent: Some(change_percent),
+        })
+    };
+
+    let near = |multiple: f64, value: f64| (value / multiple - 1.0).abs() <= PACK_MULTIPLE_TOLERANCE;
+    if let Some(multiple) = PACK_MULTIPLES.iter().find(|m| near(**m, ratio) || near(**m, 1.0 / ratio)) {
+        let direction = if ratio > 1.0 { "times" } else { "divided by" };
+        return anomaly(
+            PriceAnomalyType::PackSizeSwing,
+            format!(
+                "unit price {:.4} is the recent {:.4} {} {}, which looks like a pack size change",
+                unit_price, reference, direction, multiple
+            ),
+        );
+    }
+
+    if change_percent > thresholds.max_increase_percent {
+        return anomaly(
+            PriceAnomalyType::PriceJump,
+            format!(
+                "unit price {:.4} is {:.0}% above the recent {:.4} (limit {:.0}%)",
+                unit_price, change_percent, reference, thresholds.max_increase_percent
+            ),
+        );
+    }
+    if -change_percent > thresholds.max_decrease_percent {
+        return anomaly(
+            PriceAnomalyType::PriceDrop,
+            format!(
+                "unit price {:.4} is {:.0}% below the recent {:.4} (limit {:.0}%)",
+                unit_price, -change_percent, reference, thresholds.max_decrease_percent
+            ),
+        );
+    }
+
+    None
+}
+
+// Median unit price over the window: this distributor's current price and the prices imports
+// have overwritten since, or when it has none, what other distributors charge
+async fn recent_unit_price(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    spec: &DistributorSpec,
+    since: NaiveDate,
+) -> Result<Option<f64>, AppError> {
+    let own: Vec<f64> = sqlx::query_scalar(
+        r#"SELECT unit_price FROM local_current_prices
+           WHERE restaurant_id = ? AND catalog_product_id = ? AND distributor_id = ? AND effective_date >= ?
+           UNION ALL
+           SELECT c.previous_case_price / c.previous_total_preferred_units
+           FROM csv_import_price_changes c
+           JOIN local_current_prices p ON p.price_id = c.price_id
+           WHERE p.restaurant_id = ? AND p.catalog_product_id = ? AND p.distributor_id = ?
+             AND c.change_type = 'updated' AND c.previous_effective_date >= ?
+             AND c.previous_total_preferred_units > 0"#
+    )
+    .bind(restaurant_id)
+    .bind(&spec.catalog_product_id)
+    .bind(&spec.distributor_id)
+    .bind(since)
+    .bind(restaurant_id)
+    .bind(&spec.catalog_product_id)
+    .bind(&spec.distributor_id)
+    .bind(since)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let prices = if own.is_empty() {
+        sqlx::query_scalar(
+            r#"SELECT unit_price FROM local_current_prices
+               WHERE restaurant_id = ? AND catalog_product_id = ? AND distributor_id != ? AND effective_date >= ?"#
+        )
+        .bind(restaurant_id)
+        .bind(&spec.catalog_product_id)
+        .bind(&spec.distributor_id)
+        .bind(since)
+        .fetch_all(&mut *conn)
+        .await?
+    } else {
+        own
+    };
+
+    Ok(median(prices))
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    values.retain(|value| value.is_finite() && *value > 0.0);
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    })
+}
+
+// Hold a row's price for approval instead of writing it. Returns the quarantine id.
+#[allow(clippy::too_many_arguments)]
+pub async fn hold_price(
+    conn: &mut SqliteConnection,
+    import_id: &str,
+    restaurant_id: &str,
+    spec: &DistributorSpec,
+    row_number: i32,
+    pricing: CasePricing,
+    effective_date: NaiveDate,
+    origin: PriceOrigin<'_>,
+    anomaly: &PriceAnomaly,
+) -> Result<String, AppError> {
+    let quarantine_id = Uuid::new_v4().to_string();
+
+    sqlx::query(
+        r#"INSERT INTO quarantined_prices (quarantine_id, import_id, restaurant_id, catalog_product_id, distributor_id,
+           spec_id, row_number, case_price, total_preferred_units, pricing_mode, unit_price, reference_unit_price,
+           change_percent, anomaly_type, reason, effective_date, source_type, source_file_name, source_file_hash)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+    )
+    .bind(&quarantine_id)
+    .bind(import_id)
+    .bind(restaurant_id)
+    .bind(&spec.catalog_product_id)
+    .bind(&spec.distributor_id)
+    .bind(&spec.spec_id)
+    .bind(row_number)
+    .bind(pricing.case_price)
+    .bind(pricing.total_preferred_units)
+    .bind(pricing.mode.as_str())
+    .bind(anomaly.unit_price)
+    .bind(anomaly.reference_unit_price)
+    .bind(anomaly.change_percent)
+    .bind(anomaly.anomaly_type.as_str())
+    .bind(&anomaly.reason)
+    .bind(effective_date)
+    .bind(origin.source_type)
+    .bind(origin.file_name)
+    .bind(origin.file_hash)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(quarantine_id)
+}
+
+// Held prices for a restaurant, newest first; all statuses when `status` is None
+pub async fn list_quarantined(
+    pool: &DbPool,
+    restaurant_id: &str,
+    status: Option<&str>,
+) -> Result<Vec<QuarantinedPrice>, AppError> {
+    let prices = sqlx::query_as::<_, QuarantinedPrice>(
+        r#"SELECT * FROM quarantined_prices
+           WHERE restaurant_id = ? AND (? IS NULL OR status = ?)
+           ORDER BY created_at DESC, row_number"#
+    )
+    .bind(restaurant_id)
+    .bind(status)
+    .bind(status)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(prices)
+}
+
+// Write a held price as its import would have, and queue it for upload. The write follows
+// the usual date rule, so a price that has since been replaced by a newer one isn't written.
+pub async fn approve_price(pool: &DbPool, quarantine_id: &str) -> Result<QuarantineReviewResult, AppError> {
+    let reviewed_by = crate::db::get_cached_user(pool).await?.map(|user| user.full_name);
+    let mut tx = pool.begin().await?;
+    let held = pending_price(&mut tx, quarantine_id).await?;
+
+    let import_status = sqlx::query_scalar::<_, String>("SELECT status FROM csv_imports WHERE import_id = ?")
+        .bind(&held.import_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if import_status.as_deref() != Some("completed") {
+        return Err(AppError::Validation(format!(
+            "The import this price came from is {}, so it can no longer be approved",
+            import_status.as_deref().unwrap_or("gone")
+        )));
+    }
+    if held.case_price <= 0.0 {
+        return Err(AppError::Validation("A zero price can't be approved".to_string()));
+    }
+
+    let spec = sqlx::query_as::<_, DistributorSpec>("SELECT * FROM distributor_specs WHERE spec_id = ?")
+        .bind(&held.spec_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Distributor spec {}", held.spec_id)))?;
+    // Priced against the case the import checked, even if the spec has been re-synced since
+    let pricing = CasePricing {
+        case_price: held.case_price,
+        total_preferred_units: held.total_preferred_units,
+        mode: PricingMode::parse(&held.pricing_mode),
+    };
+
+    let outcome = write_price(
+        &mut tx,
+        &held.restaurant_id,
+        &spec,
+        pricing,
+        held.effective_date,
+        PriceOrigin {
+            source_type: &held.source_type,
+            file_name: held.source_file_name.as_deref().unwrap_or_default(),
+            file_hash: held.source_file_hash.as_deref().unwrap_or_default(),
+        },
+    )
+    .await?;
+    rollback::record_price_change(&mut tx, &held.import_id, &outcome).await?;
+
+    let price_written = !matches!(outcome, PriceWriteOutcome::SkippedOlder { .. });
+    if price_written {
+        queue_price_upload(
+            &mut tx,
+            &held.restaurant_id,
+            json!({
+                "import_id": held.import_id,
+                "distributor_id": held.distributor_id,
+                "effective_date": held.effective_date,
+                "file_name": held.source_file_name,
+                "source_type": held.source_type,
+                "prices": [{
+                    "catalog_product_id": held.catalog_product_id,
+                    "distributor_id": held.distributor_id,
+                    "case_price": held.case_price,
+                    "total_preferred_units": held.total_preferred_units,
+                    "pricing_mode": held.pricing_mode,
+                }],
+            }),
+        )
+        .await?;
+
+        sqlx::query("UPDATE csv_imports SET imported_count = COALESCE(imported_count, 0) + 1 WHERE import_id = ?")
+            .bind(&held.import_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let quarantined = review(&mut tx, quarantine_id, "approved", reviewed_by).await?;
+    tx.commit().await?;
+    info!("Approved quarantined price {} (written: {})", quarantine_id, price_written);
+
+    Ok(QuarantineReviewResult { quarantined, price_written })
+}
+
+// Drop a held price; the current price stays as it was
+pub async fn reject_price(pool: &DbPool, quarantine_id: &str) -> Result<QuarantineReviewResult, AppError> {
+    let reviewed_by = crate::db::get_cached_user(pool).await?.map(|user| user.full_name);
+    let mut tx = pool.begin().await?;
+    pending_price(&mut tx, quarantine_id).await?;
+
+    let quarantined = review(&mut tx, quarantine_id, "rejected", reviewed_by).await?;
+    tx.commit().await?;
+
+    Ok(QuarantineReviewResult {
+        quarantined,
+        price_written: false,
+    })
+}
+
+async fn pending_price(conn: &mut SqliteConnection, quarantine_id: &str) -> Result<QuarantinedPrice, AppError> {
+    let held = sqlx::query_as::<_, QuarantinedPrice>("SELECT * FROM quarantined_prices WHERE quarantine_id = ?")
+        .bind(quarantine_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Quarantined price {}", quarantine_id)))?;
+
+    if held.status != "pending" {
+        return Err(AppError::Validation(format!("This price has already been {}", held.status)));
+    }
+    Ok(held)
+}
+
+async fn review(
+    conn: &mut SqliteConnection,
+    quarantine_id: &str,
+    status: &str,
+    reviewed_by: Option<String>,
+) -> Result<QuarantinedPrice, AppError> {
+    sqlx::query(
+        "UPDATE quarantined_prices SET status = ?, reviewed_by = ?, reviewed_at = CURRENT_TIMESTAMP WHERE quarantine_id = ?"
+    )
+    .bind(status)
+    .bind(reviewed_by)
+    .bind(quarantine_id)
+    .execute(&mut *conn)
+    .await?;
+
+    let held = sqlx::query_as::<_, QuarantinedPrice>("SELECT * FROM quarantined_prices WHERE quarantine_id = ?")
+        .bind(quarantine_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    Ok(held)
+}
+
+// Forget the held prices of an import that was undone or never finished
+pub async fn discard_for_import(conn: &mut SqliteConnection, import_id: &str) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM quarantined_prices WHERE import_id = ?")
+        .bind(import_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+// Forget prices still pending from the imports a finished import superseded; the new
+// import has checked the same file again
+pub async fn discard_superseded(conn: &mut SqliteConnection, import_id: &str) -> Result<(), AppError> {
+    sqlx::query(
+        r#"DELETE FROM quarantined_prices
+           WHERE status = 'pending'
+             AND import_id IN (SELECT import_id FROM csv_imports WHERE superseded_by = ?)"#
+    )
+    .bind(import_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// The thresholds for a product's category, or the default ones
+async fn thresholds_for_product(
+    conn: &mut SqliteConnection,
+    catalog_product_id: &str,
+) -> Result<PriceAnomalyThresholds, AppError> {
+    let thresholds = sqlx::query_as::<_, PriceAnomalyThresholds>(
+        r#"SELECT t.* FROM price_anomaly_thresholds t
+           LEFT JOIN products p ON p.category_id = t.category_id AND p.catalog_product_id = ?
+           WHERE p.catalog_product_id IS NOT NULL OR t.category_id = 'default'
+           ORDER BY t.category_id = 'default'
+           LIMIT 1"#
+    )
+    .bind(catalog_product_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    thresholds.ok_or_else(|| AppError::Config("Default price anomaly thresholds are missing".to_string()))
+}
+
+pub async fn list_thresholds(pool: &DbPool) -> Result<Vec<PriceAnomalyThresholds>, AppError> {
+    let thresholds = sqlx::query_as::<_, PriceAnomalyThresholds>(
+        "SELECT * FROM price_anomaly_thresholds ORDER BY category_id = 'default' DESC, category_id"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(thresholds)
+}
+
+// Set the thresholds for a category ('default' for the fallback)
+pub async fn save_thresholds(
+    pool: &DbPool,
+    category_id: &str,
+    max_increase_percent: f64,
+    max_decrease_percent: f64,
+    history_days: i32,
+) -> Result<PriceAnomalyThresholds, AppError> {
+    if category_id.trim().is_empty() {
+        return Err(AppError::Validation("A category is required".to_string()));
+    }
+    if max_increase_percent.is_nan() || max_increase_percent <= 0.0 {
+        return Err(AppError::Validation("The increase limit must be above 0%".to_string()));
+    }
+    if max_decrease_percent.is_nan() || max_decrease_percent <= 0.0 || max_decrease_percent > 100.0 {
+        return Err(AppError::Validation("The decrease limit must be between 0% and 100%".to_string()));
+    }
+    if history_days <= 0 {
+        return Err(AppError::Validation("The history window must be at least one day".to_string()));
+    }
+
+    sqlx::query(
+        r#"INSERT INTO price_anomaly_thresholds (category_id, max_increase_percent, max_decrease_percent, history_days)
+           VALUES (?, ?, ?, ?)
+           ON CONFLICT(category_id) DO UPDATE SET
+               max_increase_percent = excluded.max_increase_percent,
+               max_decrease_percent = excluded.max_decrease_percent,
+               history_days = excluded.history_days,
+               updated_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(category_id)
+    .bind(max_increase_percent)
+    .bind(max_decrease_percent)
+    .bind(history_days)
+    .execute(pool)
+    .await?;
+
+    let thresholds = sqlx::query_as::<_, PriceAnomalyThresholds>(
+        "SELECT * FROM price_anomaly_thresholds WHERE category_id = ?"
+    )
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(thresholds)
+}
+
+// Drop a category's own thresholds so it uses the default ones again
+pub async fn delete_thresholds(pool: &DbPool, category_id: &str) -> Result<(), AppError> {
+    if category_id == "default" {
+        return Err(AppError::Validation("The default thresholds can be changed but not removed".to_string()));
+    }
+
+    let deleted = sqlx::query("DELETE FROM price_anomaly_thresholds WHERE category_id = ?")
+        .bind(category_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Price anomaly thresholds for category {}", category_id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thresholds() -> PriceAnomalyThresholds {
+        PriceAnomalyThresholds {
+            category_id: "default".to_string(),
+            max_increase_percent: 50.0,
+            max_decrease_percent: 40.0,
+            history_days: 90,
+            updated_at: None,
+        }
+    }
+
+    // Unit price of a 25 lb case
+    fn per_lb(case_price: f64) -> f64 {
+        case_price / 25.0
+    }
+
+    fn anomaly_type(case_price: f64, reference_case_price: f64) -> Option<PriceAnomalyType> {
+        compare_unit_prices(per_lb(case_price), per_lb(reference_case_price), &thresholds())
+            .map(|anomaly| anomaly.anomaly_type)
+    }
+
+    #[test]
+    fn holds_a_misplaced_decimal_point() {
+        let anomaly = compare_unit_prices(per_lb(185.00), per_lb(18.50), &thresholds()).unwrap();
+        assert_eq!(anomaly.anomaly_type, PriceAnomalyType::PriceJump);
+        assert!((anomaly.change_percent.unwrap() - 900.0).abs() < 1e-9);
+        assert_eq!(anomaly.reason, "unit price 7.4000 is 900% above the recent 0.7400 (limit 50%)");
+
+        assert_eq!(anomaly_type(1.85, 18.50), Some(PriceAnomalyType::PriceDrop));
+    }
+
+    #[test]
+    fn holds_a_case_priced_as_one_pack() {
+        assert_eq!(anomaly_type(24.00 * 12.0, 24.00), Some(PriceAnomalyType::PackSizeSwing));
+        assert_eq!(anomaly_type(24.00, 24.00 * 6.0), Some(PriceAnomalyType::PackSizeSwing));
+        assert_eq!(anomaly_type(24.00 * 6.1, 24.00), Some(PriceAnomalyType::PackSizeSwing));
+        assert_eq!(anomaly_type(24.00 * 6.5, 24.00), Some(PriceAnomalyType::PriceJump));
+    }
+
+    #[test]
+    fn passes_changes_within_the_thresholds() {
+        assert_eq!(anomaly_type(18.50, 18.50), None);
+        assert_eq!(anomaly_type(27.00, 18.50), None);
+        assert_eq!(anomaly_type(28.00, 18.50), Some(PriceAnomalyType::PriceJump));
+        assert_eq!(anomaly_type(11.50, 18.50), None);
+        assert_eq!(anomaly_type(11.00, 18.50), Some(PriceAnomalyType::PriceDrop));
+    }
+
+    #[test]
+    fn takes_the_median_of_recent_prices() {
+        assert_eq!(median(vec![0.9, 0.7, 0.8]), Some(0.8));
+        assert_eq!(median(vec![0.7, 0.8, 0.0, f64::NAN]), Some(0.75));
+        assert_eq!(median(Vec::new()), None);
+    }
+}
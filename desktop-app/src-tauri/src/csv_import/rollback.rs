@@ -0,0 +1,284 @@
+use crate::db::{
+    models::{CsvImport, CsvImportPriceChange, LocalCurrentPrice, RollbackResult},
+    DbPool,
+};
+use crate::error::AppError;
+use sqlx::SqliteConnection;
+use tracing::{info, warn};
+
+use super::{history, quarantine, PriceWriteOutcome};
+
+// Remember what an import did to a price so rollback_import can undo it
+pub async fn record_price_change(
+    conn: &mut SqliteConnection,
+    import_id: &str,
+    outcome: &PriceWriteOutcome,
+) -> Result<(), AppError> {
+    match outcome {
+        PriceWriteOutcome::Inserted { price_id } => {
+            sqlx::query(
+                "INSERT INTO csv_import_price_changes (import_id, price_id, change_type) VALUES (?, ?, 'inserted')"
+            )
+            .bind(import_id)
+            .bind(price_id)
+            .execute(&mut *conn)
+            .await?;
+        }
+        PriceWriteOutcome::Updated { previous } => {
+            sqlx::query(
+                r#"INSERT INTO csv_import_price_changes (import_id, price_id, change_type,
+                   previous_case_price, previous_total_preferred_units, previous_effective_date,
+                   previous_source_type, previous_source_file_name, previous_source_file_hash, previous_created_at,
+                   previous_pricing_mode)
+                   VALUES (?, ?, 'updated', ?, ?, ?, ?, ?, ?, ?, ?)"#
+            )
+            .bind(import_id)
+            .bind(&previous.price_id)
+            .bind(previous.case_price)
+            .bind(previous.total_preferred_units)
+            .bind(previous.effective_date)
+            .bind(&previous.source_type)
+            .bind(&previous.source_file_name)
+            .bind(&previous.source_file_hash)
+            .bind(previous.created_at)
+            .bind(&previous.pricing_mode)
+            .execute(&mut *conn)
+            .await?;
+        }
+        PriceWriteOutcome::SkippedOlder { .. } => {}
+    }
+
+    Ok(())
+}
+
+// Undo a completed import: delete the prices it created, restore the ones it overwrote,
+// drop its queued upload and mark it rolled back. Refuses if a later import has since
+// replaced any of its prices, since undoing it would then clobber newer data.
+pub async fn rollback_import(pool: &DbPool, import_id: &str) -> Result<RollbackResult, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let import = sqlx::query_as::<_, CsvImport>("SELECT * FROM csv_imports WHERE import_id = ?")
+        .bind(import_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Import {} not found", import_id)))?;
+
+    if import.status != "completed" {
+        return Err(AppError::Validation(format!(
+            "Only completed imports can be rolled back, {} is {}",
+            import.file_name, import.status
+        )));
+    }
+
+    let changes = sqlx::query_as::<_, CsvImportPriceChange>(
+        "SELECT * FROM csv_import_price_changes WHERE import_id = ? ORDER BY change_id DESC"
+    )
+    .bind(import_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut result = RollbackResult {
+        import_id: import_id.to_string(),
+        prices_removed: 0,
+        prices_restored: 0,
+        sync_events_removed: 0,
+    };
+    let mut conflicts = Vec::new();
+
+    for change in &changes {
+        let current = sqlx::query_as::<_, LocalCurrentPrice>("SELECT * FROM local_current_prices WHERE price_id = ?")
+            .bind(&change.price_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        match current {
+            Some(price) if price.source_file_hash.as_deref() == Some(import.file_hash.as_str()) => {}
+            Some(price) => conflicts.push(format!(
+                "{} price dated {} now comes from {}",
+                price.catalog_product_id,
+                price.effective_date,
+                price.source_file_name.as_deref().unwrap_or(&price.source_type)
+            )),
+            None => {}
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Prices from {} have been replaced since it was imported; roll back the newer import first ({})",
+            import.file_name,
+            conflicts.join("; ")
+        )));
+    }
+
+    (result.prices_removed, result.prices_restored) = undo_price_changes(&mut tx, &changes).await?;
+
+    result.sync_events_removed = sqlx::query(
+        "DELETE FROM pending_sync_events WHERE event_type = 'csv_import' AND json_extract(payload, '$.import_id') = ?"
+    )
+    .bind(import_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected() as i32;
+
+    // An invoice's record goes with its prices, so it can be imported again
+    sqlx::query("DELETE FROM edi_invoices WHERE import_id = ?")
+        .bind(import_id)
+        .execute(&mut *tx)
+        .await?;
+    quarantine::discard_for_import(&mut tx, import_id).await?;
+
+    sqlx::query("UPDATE csv_imports SET status = 'rolled_back', rolled_back_at = CURRENT_TIMESTAMP WHERE import_id = ?")
+        .bind(import_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    info!(
+        "Rolled back import {}: {} prices removed, {} restored, {} sync events removed",
+        import_id, result.prices_removed, result.prices_restored, result.sync_events_removed
+    );
+
+    Ok(result)
+}
+
+// Delete the prices the changes inserted and restore the before-images of the ones they
+// overwrote, newest change first. Returns (removed, restored).
+async fn undo_price_changes(
+    conn: &mut SqliteConnection,
+    changes: &[CsvImportPriceChange],
+) -> Result<(i32, i32), AppError> {
+    let mut removed = 0;
+    let mut restored = 0;
+
+    for change in changes {
+        if change.change_type == "inserted" {
+            removed += sqlx::query("DELETE FROM local_current_prices WHERE price_id = ?")
+                .bind(&change.price_id)
+                .execute(&mut *conn)
+                .await?
+                .rows_affected() as i32;
+        } else {
+            restored += sqlx::query(
+                r#"UPDATE local_current_prices
+                   SET case_price = ?, total_preferred_units = ?, effective_date = ?, source_type = ?,
+                       source_file_name = ?, source_file_hash = ?, created_at = ?,
+                       pricing_mode = COALESCE(?, 'case')
+                   WHERE price_id = ?"#
+            )
+            .bind(change.previous_case_price)
+            .bind(change.previous_total_preferred_units)
+            .bind(change.previous_effective_date)
+            .bind(&change.previous_source_type)
+            .bind(&change.previous_source_file_name)
+            .bind(&change.previous_source_file_hash)
+            .bind(change.previous_created_at)
+            .bind(&change.previous_pricing_mode)
+            .bind(&change.price_id)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected() as i32;
+        }
+    }
+
+    Ok((removed, restored))
+}
+
+// Undo an import that stopped part-way, after some of its batches were committed: its
+// prices go back to how they were, the imports it superseded are reinstated and it is
+// marked failed with the reason. Prices another import has written since are left alone
+// and noted in the reason.
+pub async fn abandon_import(pool: &DbPool, import_id: &str, reason: &str) -> Result<RollbackResult, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let import = sqlx::query_as::<_, CsvImport>("SELECT * FROM csv_imports WHERE import_id = ?")
+        .bind(import_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Import {} not found", import_id)))?;
+
+    let changes = sqlx::query_as::<_, CsvImportPriceChange>(
+        "SELECT * FROM csv_import_price_changes WHERE import_id = ? ORDER BY change_id DESC"
+    )
+    .bind(import_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut ours = Vec::new();
+    let mut replaced = Vec::new();
+    for change in changes {
+        let current_hash = sqlx::query_scalar::<_, Option<String>>(
+            "SELECT source_file_hash FROM local_current_prices WHERE price_id = ?"
+        )
+        .bind(&change.price_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        match current_hash {
+            Some(hash) if hash.as_deref() != Some(import.file_hash.as_str()) => replaced.push(change.price_id),
+            _ => ours.push(change),
+        }
+    }
+    replaced.sort();
+    replaced.dedup();
+
+    let (prices_removed, prices_restored) = undo_price_changes(&mut tx, &ours).await?;
+
+    let reason = match replaced.len() {
+        0 => reason.to_string(),
+        kept => {
+            warn!(
+                "Import {} left {} prices alone that another import has replaced: {}",
+                import_id,
+                kept,
+                replaced.join(", ")
+            );
+            let prices = if kept == 1 { "1 price".to_string() } else { format!("{} prices", kept) };
+            format!("{} (kept {} another import has replaced since)", reason, prices)
+        }
+    };
+
+    sqlx::query("DELETE FROM csv_import_price_changes WHERE import_id = ?")
+        .bind(import_id)
+        .execute(&mut *tx)
+        .await?;
+
+    history::reinstate_superseded(&mut tx, import_id).await?;
+    quarantine::discard_for_import(&mut tx, import_id).await?;
+
+    sqlx::query("UPDATE csv_imports SET status = 'failed', error_message = ? WHERE import_id = ?")
+        .bind(&reason)
+        .bind(import_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    info!(
+        "Abandoned import {} ({}): {} prices removed, {} restored",
+        import_id, reason, prices_removed, prices_restored
+    );
+
+    Ok(RollbackResult {
+        import_id: import_id.to_string(),
+        prices_removed,
+        prices_restored,
+        sync_events_removed: 0,
+    })
+}
+
+// Undo imports left in 'processing' by a crash or a closed app, so their half-written
+// batches don't linger
+pub async fn recover_interrupted_imports(pool: &DbPool) -> Result<usize, AppError> {
+    let interrupted: Vec<String> = sqlx::query_scalar("SELECT import_id FROM csv_imports WHERE status = 'processing'")
+        .fetch_all(pool)
+        .await?;
+
+    for import_id in &interrupted {
+        warn!("Import {} was interrupted, undoing its partial writes", import_id);
+        abandon_import(pool, import_id, "Interrupted before it finished").await?;
+    }
+
+    Ok(interrupted.len())
+}
@@ -0,0 +1,242 @@
+use crate::db::{
+    models::{CsvColumnMapping, CsvField, CsvMappingProfile, ImportPreset, ImportSource, SourceFormat},
+    DbPool,
+};
+use crate::error::AppError;
+use std::path::Path;
+
+use super::dialect::{self, CsvDialect};
+use super::edi;
+use super::excel;
+use super::mapping::{detect_column_mapping, normalize_header, require_columns};
+use super::presets;
+
+// Rows searched for the header when a file has preamble rows above it
+const HEADER_SEARCH_ROWS: usize = 30;
+
+// One row of a file, or why it couldn't be read
+#[derive(Debug, Clone)]
+pub struct SourceRecord {
+    pub row_number: i32, // 1-based, as a spreadsheet shows it
+    pub cells: Result<Vec<String>, String>,
+}
+
+// A file's price table: its header, the rows below it and any preamble above it
+#[derive(Debug, Clone)]
+pub struct SourceTable {
+    pub source: ImportSource,
+    pub preamble: Vec<String>, // Non-empty cells above the header, e.g. "Prices effective 10/05/2026"
+    pub headers: Vec<String>,
+    pub records: Vec<SourceRecord>,
+}
+
+// EDI and workbooks by their signature (ISA header, ZIP for xlsx, OLE for xls),
+// falling back to the extension
+pub fn detect_format(contents: &[u8], file_path: &Path) -> SourceFormat {
+    if edi::is_interchange(contents) {
+        return match edi::transaction_set(contents).as_deref() {
+            Some("810") => SourceFormat::Edi810,
+            _ => SourceFormat::Edi832,
+        };
+    }
+    if contents.starts_with(b"PK\x03\x04") {
+        return SourceFormat::Xlsx;
+    }
+    if contents.starts_with(&[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1]) {
+        return SourceFormat::Xls;
+    }
+
+    let extension = file_path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "xlsx" | "xlsm" => SourceFormat::Xlsx,
+        "xls" => SourceFormat::Xls,
+        "edi" | "x12" | "832" => SourceFormat::Edi832,
+        "810" => SourceFormat::Edi810,
+        _ => SourceFormat::Csv,
+    }
+}
+
+// Read a file's price table, resolving the CSV dialect from the profile and app settings
+pub async fn load_table(
+    pool: &DbPool,
+    contents: &[u8],
+    file_path: &Path,
+    profile: Option<&CsvMappingProfile>,
+    presets: &[ImportPreset],
+    sheet: Option<&str>,
+    header_row: Option<i32>,
+) -> Result<SourceTable, AppError> {
+    let format = detect_format(contents, file_path);
+    let dialect = match format {
+        SourceFormat::Csv => Some(dialect::resolve_dialect(pool, contents, profile).await?),
+        _ => None,
+    };
+
+    read_table(contents, format, dialect, profile, presets, sheet, header_row)
+}
+
+// Read a file's price table. CSV files without a dialect have theirs sniffed.
+pub fn read_table(
+    contents: &[u8],
+    format: SourceFormat,
+    dialect: Option<CsvDialect>,
+    profile: Option<&CsvMappingProfile>,
+    presets: &[ImportPreset],
+    sheet: Option<&str>,
+    header_row: Option<i32>,
+) -> Result<SourceTable, AppError> {
+    let (records, file_format, sheet_name) = match format {
+        SourceFormat::Csv => {
+            let dialect = dialect.unwrap_or_else(|| CsvDialect::sniff(contents));
+            let records = read_csv_records(&dialect.decode(contents), &dialect);
+            (records, Some(dialect.file_format()), None)
+        }
+        SourceFormat::Xlsx | SourceFormat::Xls => {
+            let (sheet_name, records) = excel::read_sheet(contents, format, sheet, |records| {
+                find_header(records, profile, presets).is_some()
+            })?;
+            (records, None, Some(sheet_name))
+        }
+        SourceFormat::Edi832 | SourceFormat::Edi810 => {
+            return Err(AppError::Validation("EDI documents are read by segment, not as a table".to_string()))
+        }
+    };
+
+    let header_index = match header_row {
+        Some(row) => records
+            .iter()
+            .position(|record| record.row_number == row)
+            .ok_or_else(|| AppError::Validation(format!("The file has no row {}", row)))?,
+        None => find_header(&records, profile, presets)
+            .or_else(|| records.iter().position(|record| !is_blank(record)))
+            .ok_or_else(|| AppError::Validation("The file has no header row".to_string()))?,
+    };
+
+    let mut records = records;
+    let body = records.split_off(header_index + 1);
+    let header = records.pop().expect("header index is within the records");
+    let headers = header
+        .cells
+        .map_err(|e| AppError::Validation(format!("Could not read the header row: {}", e)))?;
+
+    let preamble = records
+        .into_iter()
+        .filter_map(|record| record.cells.ok())
+        .flatten()
+        .filter(|cell| !cell.is_empty())
+        .collect();
+
+    Ok(SourceTable {
+        source: ImportSource {
+            format,
+            file_format,
+            sheet_name,
+            header_row: header.row_number,
+        },
+        preamble,
+        headers,
+        records: body,
+    })
+}
+
+fn read_csv_records(text: &str, dialect: &CsvDialect) -> Vec<SourceRecord> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(dialect.delimiter)
+        .quote(dialect.quote)
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+
+    // Row numbers are worked out from each record's byte offset, as the reader's own line
+    // count points at the blank lines skipped before a record rather than the record itself
+    let bytes = text.as_bytes();
+    let mut line = 1;
+    let mut counted_to = 0;
+    let mut row_number = |byte: Option<u64>| {
+        let byte = byte.map(|b| b as usize).unwrap_or(counted_to);
+        let start = byte + bytes[byte.min(bytes.len())..]
+            .iter()
+            .take_while(|b| matches!(b, b'\r' | b'\n'))
+            .count();
+        line += bytes[counted_to..start].iter().filter(|b| **b == b'\n').count() as i32;
+        counted_to = start;
+        line
+    };
+
+    let mut records = Vec::new();
+    for record in reader.records() {
+        records.push(match record {
+            Ok(record) => SourceRecord {
+                row_number: row_number(record.position().map(|pos| pos.byte())),
+                cells: Ok(record.iter().map(str::to_string).collect()),
+            },
+            Err(e) => SourceRecord {
+                row_number: row_number(e.position().map(|pos| pos.byte())),
+                cells: Err(e.to_string()),
+            },
+        });
+    }
+
+    records
+}
+
+// The first row near the top that names the item code and case price columns, either
+// by the distributor profile's saved headers, a preset's column names or header detection.
+// A preset's usual header row is tried before anything else.
+fn find_header(
+    records: &[SourceRecord],
+    profile: Option<&CsvMappingProfile>,
+    presets: &[ImportPreset],
+) -> Option<usize> {
+    let preset_row = presets.iter().find_map(|preset| {
+        let row = preset.header_row?;
+        records.iter().position(|record| {
+            record.row_number == row
+                && matches!(&record.cells, Ok(cells) if presets::matches_header(preset, cells))
+        })
+    });
+    if preset_row.is_some() {
+        return preset_row;
+    }
+
+    let profile_headers: Option<Vec<String>> = profile
+        .and_then(|profile| serde_json::from_str::<CsvColumnMapping>(&profile.column_mapping).ok())
+        .and_then(|mapping| {
+            [CsvField::ItemCode, CsvField::CasePrice]
+                .into_iter()
+                .map(|field| mapping.get(field).map(|m| normalize_header(&m.header)))
+                .collect()
+        });
+
+    records.iter().take(HEADER_SEARCH_ROWS).position(|record| {
+        let Ok(cells) = &record.cells else {
+            return false;
+        };
+        if cells.iter().all(|cell| cell.is_empty()) {
+            return false;
+        }
+
+        if let Some(wanted) = &profile_headers {
+            let normalized: Vec<String> = cells.iter().map(|cell| normalize_header(cell)).collect();
+            if wanted.iter().all(|header| normalized.contains(header)) {
+                return true;
+            }
+        }
+        if presets.iter().any(|preset| presets::matches_header(preset, cells)) {
+            return true;
+        }
+
+        detect_column_mapping(cells)
+            .map(|mapping| require_columns(&mapping, cells).is_ok())
+            .unwrap_or(false)
+    })
+}
+
+fn is_blank(record: &SourceRecord) -> bool {
+    matches!(&record.cells, Ok(cells) if cells.iter().all(|cell| cell.is_empty()))
+}
@@ -0,0 +1,127 @@
+use crate::db::{
+    models::{DistributorSpec, PackSize, SpecChangeCandidate},
+    DbPool,
+};
+use crate::error::AppError;
+use sqlx::SqliteConnection;
+
+use super::pack_size::{disagrees_with_spec, parse_pack_size, with_preferred_units};
+use super::PriceRow;
+
+// Parse a row's pack size against its product, recording a spec-change candidate when it
+// disagrees with the synced spec. Returns the parsed pack size and any pending candidate.
+pub async fn check_pack_size(
+    conn: &mut SqliteConnection,
+    import_id: &str,
+    row: &PriceRow,
+    spec: &DistributorSpec,
+) -> Result<(Option<PackSize>, Option<SpecChangeCandidate>), AppError> {
+    let Some(text) = row.pack_size.as_deref() else {
+        return Ok((None, None));
+    };
+    let Some(pack) = parse_pack_size(text, row.unit.as_deref()) else {
+        return Ok((None, None));
+    };
+
+    let preferred_measurement = sqlx::query_scalar::<_, String>(
+        "SELECT preferred_measurement FROM products WHERE catalog_product_id = ?"
+    )
+    .bind(&spec.catalog_product_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let pack = match preferred_measurement {
+        Some(preferred_measurement) => with_preferred_units(pack, &preferred_measurement),
+        None => pack,
+    };
+
+    if !disagrees_with_spec(&pack, spec) {
+        return Ok((Some(pack), None));
+    }
+
+    let candidate = record_candidate(&mut *conn, import_id, text, &pack, spec).await?;
+    Ok((Some(pack), candidate.filter(|c| c.status == "pending")))
+}
+
+// Upsert the candidate for this spec and pack size. A dismissed candidate stays dismissed
+// so the same file layout doesn't raise it again every week.
+async fn record_candidate(
+    conn: &mut SqliteConnection,
+    import_id: &str,
+    pack_size_text: &str,
+    pack: &PackSize,
+    spec: &DistributorSpec,
+) -> Result<Option<SpecChangeCandidate>, AppError> {
+    sqlx::query(
+        r#"INSERT INTO spec_change_candidates (import_id, spec_id, catalog_product_id, distributor_id,
+           distributor_item_code, pack_size_text, case_packs, pack_size, pack_unit_of_measure, total_preferred_units,
+           spec_case_packs, spec_pack_size, spec_pack_unit_of_measure, spec_total_preferred_units)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+           ON CONFLICT(spec_id, pack_size_text) DO UPDATE SET
+               import_id = excluded.import_id,
+               total_preferred_units = excluded.total_preferred_units,
+               spec_case_packs = excluded.spec_case_packs,
+               spec_pack_size = excluded.spec_pack_size,
+               spec_pack_unit_of_measure = excluded.spec_pack_unit_of_measure,
+               spec_total_preferred_units = excluded.spec_total_preferred_units,
+               detected_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(import_id)
+    .bind(&spec.spec_id)
+    .bind(&spec.catalog_product_id)
+    .bind(&spec.distributor_id)
+    .bind(&spec.distributor_item_code)
+    .bind(pack_size_text)
+    .bind(pack.case_packs)
+    .bind(pack.pack_size)
+    .bind(&pack.pack_unit_of_measure)
+    .bind(pack.total_preferred_units)
+    .bind(spec.case_packs)
+    .bind(spec.pack_size)
+    .bind(&spec.pack_unit_of_measure)
+    .bind(spec.total_preferred_units)
+    .execute(&mut *conn)
+    .await?;
+
+    let candidate = sqlx::query_as::<_, SpecChangeCandidate>(
+        "SELECT * FROM spec_change_candidates WHERE spec_id = ? AND pack_size_text = ?"
+    )
+    .bind(&spec.spec_id)
+    .bind(pack_size_text)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(candidate)
+}
+
+// Pending candidates, optionally for one distributor
+pub async fn list_candidates(
+    pool: &DbPool,
+    distributor_id: Option<&str>,
+) -> Result<Vec<SpecChangeCandidate>, AppError> {
+    let candidates = sqlx::query_as::<_, SpecChangeCandidate>(
+        r#"SELECT * FROM spec_change_candidates
+           WHERE status = 'pending' AND (? IS NULL OR distributor_id = ?)
+           ORDER BY detected_at DESC"#
+    )
+    .bind(distributor_id)
+    .bind(distributor_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(candidates)
+}
+
+pub async fn dismiss_candidate(pool: &DbPool, candidate_id: i64) -> Result<(), AppError> {
+    let updated = sqlx::query("UPDATE spec_change_candidates SET status = 'dismissed' WHERE candidate_id = ?")
+        .bind(candidate_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    if updated == 0 {
+        return Err(AppError::NotFound(format!("Spec change candidate {} not found", candidate_id)));
+    }
+
+    Ok(())
+}
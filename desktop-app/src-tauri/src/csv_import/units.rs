@@ -0,0 +1,78 @@
+// The measurement_type ('weight', 'volume' or 'count') a distributor unit belongs to.
+// Units that describe packaging rather than a measure ("CS", "BX") return None.
+pub fn measurement_type_of(unit: &str) -> Option<&'static str> {
+    let unit = unit.trim().trim_end_matches('.').to_lowercase();
+
+    match unit.as_str() {
+        "lb" | "lbs" | "#" | "pound" | "pounds" | "oz" | "ounce" | "ounces" | "kg" | "g" | "gram" | "grams" => {
+            Some("weight")
+        }
+        "gal" | "gallon" | "gallons" | "qt" | "quart" | "quarts" | "pt" | "pint" | "pints" | "l" | "ltr"
+        | "liter" | "litre" | "ml" | "floz" | "fl oz" => Some("volume"),
+        "ct" | "count" | "ea" | "each" | "pc" | "pcs" | "piece" | "pieces" | "dz" | "doz" | "dozen" => {
+            Some("count")
+        }
+        _ => None,
+    }
+}
+
+// The short name specs use for a unit ("LBS" -> "lb", "Gallon" -> "gal"); unknown units are lowercased
+pub fn canonical_unit(unit: &str) -> String {
+    let unit = unit.trim().trim_end_matches('.').to_lowercase();
+
+    let canonical = match unit.as_str() {
+        "lb" | "lbs" | "#" | "pound" | "pounds" => "lb",
+        "oz" | "ounce" | "ounces" => "oz",
+        "kg" => "kg",
+        "g" | "gram" | "grams" => "g",
+        "gal" | "gallon" | "gallons" => "gal",
+        "qt" | "quart" | "quarts" => "qt",
+        "pt" | "pint" | "pints" => "pt",
+        "l" | "ltr" | "liter" | "litre" => "l",
+        "ml" => "ml",
+        "floz" | "fl oz" => "floz",
+        "ct" | "count" | "pc" | "pcs" | "piece" | "pieces" => "ct",
+        "ea" | "each" => "ea",
+        "dz" | "doz" | "dozen" => "dz",
+        other => return other.to_string(),
+    };
+
+    canonical.to_string()
+}
+
+// How many ounces, fluid ounces or pieces one unit holds
+fn base_factor(unit: &str) -> Option<(&'static str, f64)> {
+    let factor = match canonical_unit(unit).as_str() {
+        "lb" => ("weight", 16.0),
+        "oz" => ("weight", 1.0),
+        "kg" => ("weight", 35.273_962),
+        "g" => ("weight", 0.035_273_962),
+        "gal" => ("volume", 128.0),
+        "qt" => ("volume", 32.0),
+        "pt" => ("volume", 16.0),
+        "l" => ("volume", 33.814_023),
+        "ml" => ("volume", 0.033_814_023),
+        "floz" => ("volume", 1.0),
+        "ct" | "ea" => ("count", 1.0),
+        "dz" => ("count", 12.0),
+        _ => return None,
+    };
+
+    Some(factor)
+}
+
+// Convert a quantity between units of the same measurement type. "OZ" on a case of
+// a liquid means fluid ounces, so it is read as volume when converting to a volume unit.
+pub fn convert(quantity: f64, from: &str, to: &str) -> Option<f64> {
+    let (to_type, to_factor) = base_factor(to)?;
+    let (from_type, from_factor) = match base_factor(from)? {
+        ("weight", factor) if to_type == "volume" && canonical_unit(from) == "oz" => ("volume", factor),
+        found => found,
+    };
+
+    if from_type != to_type {
+        return None;
+    }
+
+    Some(quantity * from_factor / to_factor)
+}
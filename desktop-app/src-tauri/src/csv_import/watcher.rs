@@ -0,0 +1,579 @@
+use crate::db::{
+    self,
+    models::{Distributor, FolderImportEvent, ImportOptions, ImportWatchSettings, PriceImportResult, SourceFormat},
+    DbPool,
+};
+use crate::error::AppError;
+use crate::state::AppState;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{error, info, warn};
+
+use super::{detect_format, edi, email, invoices, presets, profiles, source};
+
+const PROCESSED_DIR: &str = "processed";
+const FAILED_DIR: &str = "failed";
+const DEFAULT_INTERVAL_SECS: u64 = 30;
+
+// Price files the watcher picks up; anything else in the folder is left alone
+const WATCHED_EXTENSIONS: [&str; 13] = [
+    "csv", "txt", "xlsx", "xlsm", "xls", "edi", "x12", "832", "810", "tsv", "eml", "mbox", "mbx",
+];
+
+// import_watch_* settings, re-read on every scan so changes apply without a restart
+#[derive(Debug, Clone)]
+pub struct WatchSettings {
+    pub folder: Option<PathBuf>, // Unset or empty turns the watcher off
+    pub restaurant_id: Option<String>, // Defaults to the only restaurant on this machine
+    pub interval: Duration,
+}
+
+impl WatchSettings {
+    pub async fn load(pool: &DbPool) -> Result<Self, AppError> {
+        let setting = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        let interval = setting(db::get_app_setting(pool, "import_watch_interval_secs").await?)
+            .and_then(|secs| secs.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_INTERVAL_SECS);
+
+        Ok(WatchSettings {
+            folder: setting(db::get_app_setting(pool, "import_watch_folder").await?).map(PathBuf::from),
+            restaurant_id: setting(db::get_app_setting(pool, "import_watch_restaurant_id").await?),
+            interval: Duration::from_secs(interval),
+        })
+    }
+}
+
+// The settings with the defaults filled in, for the Settings page
+pub async fn get_watch_settings(pool: &DbPool) -> Result<ImportWatchSettings, AppError> {
+    let settings = WatchSettings::load(pool).await?;
+
+    Ok(ImportWatchSettings {
+        folder: settings.folder.map(|folder| folder.display().to_string()),
+        restaurant_id: settings.restaurant_id,
+        interval_secs: settings.interval.as_secs(),
+    })
+}
+
+// Point the watcher at a folder, or turn it off with none. The running watcher picks the
+// change up on its next scan.
+pub async fn save_watch_settings(
+    pool: &DbPool,
+    folder: Option<&str>,
+    restaurant_id: Option<&str>,
+    interval_secs: u64,
+) -> Result<ImportWatchSettings, AppError> {
+    let folder = folder.map(str::trim).filter(|folder| !folder.is_empty());
+    let restaurant_id = restaurant_id.map(str::trim).filter(|id| !id.is_empty());
+
+    if let Some(folder) = folder {
+        if !Path::new(folder).is_dir() {
+            return Err(AppError::Validation(format!("Import folder {} does not exist", folder)));
+        }
+    }
+    if interval_secs == 0 {
+        return Err(AppError::Validation("The scan interval must be at least 1 second".to_string()));
+    }
+    match restaurant_id {
+        Some(restaurant_id) => {
+            let known = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM restaurants WHERE restaurant_id = ?")
+                .bind(restaurant_id)
+                .fetch_one(pool)
+                .await?;
+            if known == 0 {
+                return Err(AppError::NotFound(format!("Restaurant {}", restaurant_id)));
+            }
+        }
+        // Catch now what the watcher would otherwise fail every file on
+        None if folder.is_some() => {
+            watch_restaurant(pool, None).await?;
+        }
+        None => {}
+    }
+
+    db::set_app_setting(pool, "import_watch_folder", folder.unwrap_or_default()).await?;
+    db::set_app_setting(pool, "import_watch_restaurant_id", restaurant_id.unwrap_or_default()).await?;
+    db::set_app_setting(pool, "import_watch_interval_secs", &interval_secs.to_string()).await?;
+    get_watch_settings(pool).await
+}
+
+// Size and modification time at the last scan; a file is imported once these stop changing
+type FileStamp = (u64, Option<SystemTime>);
+
+// Background loop: scan the watched folder, import settled files and tell the UI. Errors
+// are logged and retried on the next scan rather than stopping the watcher.
+pub async fn start_folder_watcher(app_handle: AppHandle) -> Result<(), AppError> {
+    info!("Starting import folder watcher...");
+    let mut seen: HashMap<PathBuf, FileStamp> = HashMap::new();
+    let retry = Duration::from_secs(DEFAULT_INTERVAL_SECS);
+
+    loop {
+        let pool = match app_handle.state::<AppState>().get_db().await {
+            Ok(pool) => pool,
+            Err(e) => {
+                warn!("Import folder watcher could not open the database: {}", e);
+                tokio::time::sleep(retry).await;
+                continue;
+            }
+        };
+        let settings = match WatchSettings::load(&pool).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Could not read the import folder settings: {}", e);
+                tokio::time::sleep(retry).await;
+                continue;
+            }
+        };
+
+        if let Some(folder) = &settings.folder {
+            match scan_folder(&pool, folder, settings.restaurant_id.as_deref(), &mut seen).await {
+                Ok(events) => {
+                    for event in events {
+                        let name = match event.error {
+                            None => "folder-import-completed",
+                            Some(_) => "folder-import-failed",
+                        };
+                        app_handle.emit(name, &event).ok();
+                    }
+                }
+                Err(e) => warn!("Could not scan import folder {}: {}", folder.display(), e),
+            }
+        }
+
+        tokio::time::sleep(settings.interval).await;
+    }
+}
+
+// Import every file that has stopped changing since the previous scan. Files still being
+// copied in are left for the next scan.
+pub async fn scan_folder(
+    pool: &DbPool,
+    folder: &Path,
+    restaurant_id: Option<&str>,
+    seen: &mut HashMap<PathBuf, FileStamp>,
+) -> Result<Vec<FolderImportEvent>, AppError> {
+    let mut current = HashMap::new();
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if !is_watched_file(&path) {
+            continue;
+        }
+        let metadata = std::fs::metadata(&path)?;
+        current.insert(path, (metadata.len(), metadata.modified().ok()));
+    }
+
+    let mut settled: Vec<PathBuf> = current
+        .iter()
+        .filter(|(path, stamp)| seen.get(*path) == Some(stamp))
+        .map(|(path, _)| path.clone())
+        .collect();
+    settled.sort();
+    *seen = current;
+
+    let mut events = Vec::new();
+    for path in settled {
+        seen.remove(&path);
+        events.extend(process_file(pool, &path, restaurant_id).await);
+    }
+
+    Ok(events)
+}
+
+fn is_watched_file(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+        return false;
+    };
+    // Hidden files and Excel's "~$" lock files
+    if name.starts_with('.') || name.starts_with("~$") || !path.is_file() {
+        return false;
+    }
+
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| WATCHED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+// Import one file and move it to processed/ or failed/, returning an event per import
+// (an 810 file can hold several invoices, an email several attachments)
+pub async fn process_file(pool: &DbPool, path: &Path, restaurant_id: Option<&str>) -> Vec<FolderImportEvent> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut distributor_id = None;
+    let outcome = async {
+        let restaurant_id = watch_restaurant(pool, restaurant_id).await?;
+
+        // Emails name their distributor through the sender, one per message
+        if email::is_email_file(path) {
+            let messages = email::import_email_file(pool, path, &restaurant_id, None, ImportOptions::default()).await?;
+            if messages.iter().all(|message| message.already_imported) {
+                return Ok(Vec::new());
+            }
+            let errors: Vec<String> = messages.iter().flat_map(|message| message.errors.clone()).collect();
+            let results: Vec<PriceImportResult> = messages.into_iter().flat_map(|message| message.imports).collect();
+            if results.is_empty() {
+                return Err(AppError::Validation(errors.join("; ")));
+            }
+            return Ok(results);
+        }
+
+        let distributor = identify_distributor(pool, path).await?;
+        distributor_id = Some(distributor.clone());
+
+        let contents = std::fs::read(path)?;
+        let results = match detect_format(&contents, path) {
+            SourceFormat::Edi810 => {
+                invoices::import_invoice_file(pool, path, &restaurant_id, &distributor, ImportOptions::default())
+                    .await?
+                    .into_iter()
+                    .map(|invoice| invoice.prices)
+                    .collect()
+            }
+            _ => vec![super::import_csv_file(pool, path, &restaurant_id, &distributor, ImportOptions::default()).await?],
+        };
+
+        let nothing_imported = |result: &PriceImportResult| result.successful_imports == 0 && result.quarantined_imports == 0;
+        if results.iter().all(|result| nothing_imported(result) && result.failed_imports > 0) {
+            return Err(AppError::Validation("No rows could be imported".to_string()));
+        }
+        Ok(results)
+    }
+    .await;
+
+    match outcome {
+        Ok(results) => {
+            let moved_to = move_file(path, PROCESSED_DIR).map_err(|e| error!("Could not move {}: {}", file_name, e)).ok();
+            info!("Imported {} from the watched folder ({} imports)", file_name, results.len());
+
+            results
+                .into_iter()
+                .map(|result| FolderImportEvent {
+                    file_name: file_name.clone(),
+                    moved_to: moved_to.clone(),
+                    distributor_id: Some(result.distributor_id.clone()),
+                    result: Some(result),
+                    error: None,
+                })
+                .collect()
+        }
+        Err(e) => {
+            warn!("Watched-folder import of {} failed: {}", file_name, e);
+            let moved_to = move_file(path, FAILED_DIR).map_err(|e| error!("Could not move {}: {}", file_name, e)).ok();
+
+            // Leave the reason next to the file for whoever looks in failed/
+            if let Some(moved_to) = &moved_to {
+                std::fs::write(format!("{}.error.txt", moved_to), e.to_string()).ok();
+            }
+
+            vec![FolderImportEvent {
+                file_name,
+                moved_to,
+                distributor_id,
+                result: None,
+                error: Some(e.to_string()),
+            }]
+        }
+    }
+}
+
+async fn watch_restaurant(pool: &DbPool, configured: Option<&str>) -> Result<String, AppError> {
+    if let Some(restaurant_id) = configured {
+        return Ok(restaurant_id.to_string());
+    }
+
+    let restaurants: Vec<String> = sqlx::query_scalar("SELECT restaurant_id FROM restaurants")
+        .fetch_all(pool)
+        .await?;
+
+    match restaurants.as_slice() {
+        [only] => Ok(only.clone()),
+        _ => Err(AppError::Config(
+            "Choose which restaurant watched files are imported for in the import folder settings".to_string(),
+        )),
+    }
+}
+
+// Work out which distributor sent a file: its name ("sysco_1005.csv", "US Foods prices.xlsx"),
+// the EDI sender ID, then a saved mapping profile or preset whose columns the file has
+pub async fn identify_distributor(pool: &DbPool, path: &Path) -> Result<String, AppError> {
+    let distributors = sqlx::query_as::<_, Distributor>("SELECT * FROM distributors")
+        .fetch_all(pool)
+        .await?;
+    let contents = std::fs::read(path)?;
+
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let by_name = unique(distributors.iter().filter(|d| name_mentions(&stem, d)))?;
+    if let Some(distributor) = by_name {
+        return Ok(distributor);
+    }
+
+    if edi::is_interchange(&contents) {
+        let sender = edi::parse_interchange(&String::from_utf8_lossy(&contents))?.sender_id.to_lowercase();
+        let by_sender = unique(distributors.iter().filter(|d| !sender.is_empty() && name_mentions(&sender, d)))?;
+        return by_sender.ok_or_else(|| {
+            AppError::Validation(format!("No distributor matches the EDI sender ID or the file name {}", stem))
+        });
+    }
+
+    let mut by_layout = Vec::new();
+    for distributor in &distributors {
+        let profile = profiles::get_profile(pool, &distributor.distributor_id).await?;
+        let presets = presets::presets_for_distributor(pool, &distributor.distributor_id).await?;
+        if profile.is_none() && presets.is_empty() {
+            continue;
+        }
+
+        let Ok(table) = source::load_table(pool, &contents, path, profile.as_ref(), &presets, None, None).await else {
+            continue;
+        };
+        let profile_fits = match &profile {
+            Some(profile) => profiles::apply_profile(profile, &table.headers)?
+                .1
+                .is_none_or(|drift| drift.missing_headers.is_empty()),
+            None => false,
+        };
+        if profile_fits || presets.iter().any(|preset| presets::matches_header(preset, &table.headers)) {
+            by_layout.push(distributor);
+        }
+    }
+
+    unique(by_layout.into_iter())?.ok_or_else(|| {
+        AppError::Validation(
+            "Could not tell which distributor this file is from; put the distributor's name or code in the file name"
+                .to_string(),
+        )
+    })
+}
+
+// Whole-word distributor code ("RD", "USF") or the name with spacing ignored ("usfoods")
+pub fn name_mentions(text: &str, distributor: &Distributor) -> bool {
+    let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    let compact: String = text.chars().filter(|c| c.is_alphanumeric()).collect();
+
+    let code_match = distributor
+        .distributor_code
+        .as_deref()
+        .map(str::to_lowercase)
+        .is_some_and(|code| !code.is_empty() && words.contains(&code.as_str()));
+    let name: String = distributor
+        .distributor_name
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+
+    code_match || (name.len() >= 4 && compact.contains(&name))
+}
+
+fn unique<'a>(mut matches: impl Iterator<Item = &'a Distributor>) -> Result<Option<String>, AppError> {
+    let Some(first) = matches.next() else {
+        return Ok(None);
+    };
+    if let Some(second) = matches.next() {
+        return Err(AppError::Validation(format!(
+            "The file could be from {} or {}",
+            first.distributor_name, second.distributor_name
+        )));
+    }
+
+    Ok(Some(first.distributor_id.clone()))
+}
+
+// Move a file into a subfolder of its folder, numbering it if the name is taken
+fn move_file(path: &Path, subfolder: &str) -> Result<String, AppError> {
+    let folder = path.parent().unwrap_or(Path::new(".")).join(subfolder);
+    std::fs::create_dir_all(&folder)?;
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+
+    let mut target = folder.join(&name);
+    let mut copy = 2;
+    while target.exists() {
+        target = folder.join(format!("{} ({}){}", stem, copy, extension));
+        copy += 1;
+    }
+
+    std::fs::rename(path, &target)?;
+    Ok(target.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::{ColumnMatch, CsvColumnMapping};
+    use uuid::Uuid;
+
+    async fn seeded_pool() -> DbPool {
+        let pool = db::test_pool().await;
+        for statement in [
+            "INSERT INTO restaurants (restaurant_id, restaurant_name, organization_id) VALUES ('r1', 'Bistro', 'o1')",
+            "INSERT INTO distributors (distributor_id, distributor_name, distributor_code) VALUES ('d1', 'Sysco', 'SYS')",
+            "INSERT INTO distributors (distributor_id, distributor_name, distributor_code) VALUES ('d2', 'US Foods', 'USF')",
+            "INSERT INTO products (catalog_product_id, product_name, preferred_measurement, measurement_type, category_id)
+             VALUES ('p1', 'Tomatoes', 'lb', 'weight', 'c1')",
+            "INSERT INTO distributor_specs (spec_id, catalog_product_id, distributor_id, distributor_item_code, case_packs,
+             pack_size, pack_unit_of_measure, total_preferred_units) VALUES ('s1', 'p1', 'd1', '111', 1, 25, 'lb', 25)",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    fn temp_folder() -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("watcher-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+
+    fn distributor(name: &str, code: Option<&str>) -> Distributor {
+        Distributor {
+            distributor_id: name.to_lowercase(),
+            distributor_name: name.to_string(),
+            distributor_code: code.map(str::to_string),
+            synced_at: None,
+        }
+    }
+
+    #[test]
+    fn names_match_by_whole_word_code_or_unspaced_name() {
+        let us_foods = distributor("US Foods", Some("USF"));
+        assert!(name_mentions("usf_20261005", &us_foods));
+        assert!(name_mentions("usfoods weekly", &us_foods));
+        assert!(name_mentions("us foods prices", &us_foods));
+        assert!(!name_mentions("busfare", &us_foods));
+
+        // Names under 4 letters only match through the code
+        let ace = distributor("Ace", None);
+        assert!(!name_mentions("ace prices", &ace));
+        assert!(name_mentions("rd 1005", &distributor("Restaurant Depot", Some("RD"))));
+        assert!(!name_mentions("order 1005", &distributor("Restaurant Depot", Some("RD"))));
+    }
+
+    #[tokio::test]
+    async fn identifies_the_distributor_by_file_name() {
+        let pool = seeded_pool().await;
+        let folder = temp_folder();
+        let contents = "Item Code,Description,Case Price\n111,Tomatoes,20\n";
+        for name in ["sysco_1005.csv", "USF prices.csv", "sysco and usf.csv", "prices.csv"] {
+            std::fs::write(folder.join(name), contents).unwrap();
+        }
+
+        assert_eq!(identify_distributor(&pool, &folder.join("sysco_1005.csv")).await.unwrap(), "d1");
+        assert_eq!(identify_distributor(&pool, &folder.join("USF prices.csv")).await.unwrap(), "d2");
+        let both = identify_distributor(&pool, &folder.join("sysco and usf.csv")).await.unwrap_err();
+        assert!(both.to_string().contains("Sysco or US Foods"), "{}", both);
+        assert!(identify_distributor(&pool, &folder.join("prices.csv")).await.is_err());
+
+        std::fs::remove_dir_all(&folder).ok();
+    }
+
+    #[tokio::test]
+    async fn identifies_the_distributor_by_saved_profile() {
+        let pool = seeded_pool().await;
+        let column = |column_index: usize, header: &str| {
+            Some(ColumnMatch { column_index, header: header.to_string(), confidence: 1.0, ambiguous: false })
+        };
+        let mapping = CsvColumnMapping {
+            item_code: column(0, "Code"),
+            description: column(1, "Name"),
+            case_price: column(2, "Cost"),
+            ..Default::default()
+        };
+        let headers: Vec<String> = ["Code", "Name", "Cost"].iter().map(|h| h.to_string()).collect();
+        profiles::save_profile(&pool, "d2", &mapping, &headers, None, None).await.unwrap();
+
+        let folder = temp_folder();
+        std::fs::write(folder.join("weekly.csv"), "Code,Name,Cost\n222,Onions,40\n").unwrap();
+        std::fs::write(folder.join("other.csv"), "Sku,Item,Amount\n222,Onions,40\n").unwrap();
+
+        assert_eq!(identify_distributor(&pool, &folder.join("weekly.csv")).await.unwrap(), "d2");
+        assert!(identify_distributor(&pool, &folder.join("other.csv")).await.is_err());
+
+        std::fs::remove_dir_all(&folder).ok();
+    }
+
+    #[tokio::test]
+    async fn imports_files_once_they_settle_and_moves_them() {
+        let pool = seeded_pool().await;
+        let folder = temp_folder();
+        let contents = "Item Code,Description,Case Price\n111,Tomatoes,20\n";
+        std::fs::write(folder.join("sysco 1005.csv"), contents).unwrap();
+        std::fs::write(folder.join("mystery.csv"), contents).unwrap();
+        std::fs::write(folder.join("~$sysco.xlsx"), "lock").unwrap();
+        std::fs::write(folder.join("notes.pdf"), "pdf").unwrap();
+        let mut seen = HashMap::new();
+
+        // The first scan only records the files; nothing has settled yet
+        assert!(scan_folder(&pool, &folder, None, &mut seen).await.unwrap().is_empty());
+
+        // A file still growing waits for a later scan
+        std::fs::write(folder.join("mystery.csv"), format!("{}222,Onions,40\n", contents)).unwrap();
+        let events = scan_folder(&pool, &folder, None, &mut seen).await.unwrap();
+        assert_eq!(events.len(), 1);
+        let imported = &events[0];
+        assert_eq!(imported.file_name, "sysco 1005.csv");
+        assert_eq!(imported.distributor_id.as_deref(), Some("d1"));
+        assert_eq!(imported.result.as_ref().unwrap().successful_imports, 1);
+        assert!(imported.error.is_none());
+        assert!(folder.join("processed").join("sysco 1005.csv").exists());
+        assert!(!folder.join("sysco 1005.csv").exists());
+
+        // Once it stops changing it is imported, and fails with no distributor to go on
+        let events = scan_folder(&pool, &folder, None, &mut seen).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(events[0].error.is_some());
+        assert!(folder.join("failed").join("mystery.csv").exists());
+        assert!(folder.join("failed").join("mystery.csv.error.txt").exists());
+
+        // Lock files and other documents stay where they are
+        assert!(scan_folder(&pool, &folder, None, &mut seen).await.unwrap().is_empty());
+        assert!(folder.join("~$sysco.xlsx").exists());
+        assert!(folder.join("notes.pdf").exists());
+
+        // A name already taken in failed/ gets a number
+        std::fs::write(folder.join("mystery.csv"), contents).unwrap();
+        scan_folder(&pool, &folder, None, &mut seen).await.unwrap();
+        scan_folder(&pool, &folder, None, &mut seen).await.unwrap();
+        assert!(folder.join("failed").join("mystery (2).csv").exists());
+
+        std::fs::remove_dir_all(&folder).ok();
+    }
+
+    #[tokio::test]
+    async fn saves_only_usable_watch_settings() {
+        let pool = seeded_pool().await;
+        let folder = temp_folder();
+        let path = folder.display().to_string();
+
+        let off = get_watch_settings(&pool).await.unwrap();
+        assert_eq!((off.folder, off.restaurant_id, off.interval_secs), (None, None, DEFAULT_INTERVAL_SECS));
+
+        let saved = save_watch_settings(&pool, Some(&path), None, 60).await.unwrap();
+        assert_eq!(saved.folder.as_deref(), Some(path.as_str()));
+        assert_eq!(saved.interval_secs, 60);
+        let saved = save_watch_settings(&pool, Some(&path), Some("r1"), 60).await.unwrap();
+        assert_eq!(saved.restaurant_id.as_deref(), Some("r1"));
+
+        let missing = folder.join("missing").display().to_string();
+        assert!(save_watch_settings(&pool, Some(&missing), None, 60).await.is_err());
+        assert!(save_watch_settings(&pool, Some(&path), Some("r9"), 60).await.is_err());
+        assert!(save_watch_settings(&pool, Some(&path), None, 0).await.is_err());
+
+        // With two restaurants the watcher needs to be told which one
+        sqlx::query("INSERT INTO restaurants (restaurant_id, restaurant_name, organization_id) VALUES ('r2', 'Cafe', 'o1')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(save_watch_settings(&pool, Some(&path), None, 60).await.is_err());
+        assert_eq!(save_watch_settings(&pool, None, None, 60).await.unwrap().folder, None);
+
+        std::fs::remove_dir_all(&folder).ok();
+    }
+}
@@ -119,6 +119,53 @@ pub async fn check_user_auth(pool: &DbPool, email: &str) -> Result<Option<models
     Ok(user)
 }
 
+// The signed-in user cached for offline use
+pub async fn get_cached_user(pool: &DbPool) -> Result<Option<models::AuthCache>, AppError> {
+    let user = sqlx::query_as::<_, models::AuthCache>(
+        "SELECT * FROM auth_cache ORDER BY cached_at DESC LIMIT 1"
+    )
+    .fetch_optional(pool)
+    .await?;
+    
+    Ok(user)
+}
+
+// A value from app_settings
+pub async fn get_app_setting(pool: &DbPool, key: &str) -> Result<Option<String>, AppError> {
+    let value = sqlx::query_scalar::<_, String>("SELECT value FROM app_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(value)
+}
+
+// An empty in-memory database with every migration applied
+#[cfg(test)]
+pub async fn test_pool() -> DbPool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory database");
+    MIGRATOR.run(&pool).await.expect("run migrations");
+    pool
+}
+
+// Create or replace a value in app_settings
+pub async fn set_app_setting(pool: &DbPool, key: &str, value: &str) -> Result<(), AppError> {
+    sqlx::query(
+        r#"INSERT INTO app_settings (key, value, updated_at) VALUES (?, ?, CURRENT_TIMESTAMP)
+           ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(key)
+    .bind(value)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 // Product-related database operations
 pub async fn clear_all_products(pool: &DbPool) -> Result<(), AppError> {
     sqlx::query("DELETE FROM products")
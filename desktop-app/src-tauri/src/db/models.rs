@@ -50,6 +50,19 @@ pub struct Distributor {
     pub synced_at: Option<DateTime<Utc>>,
 }
 
+// A distributor's sales rep, synced from cloud
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DistributorSalesContact {
+    pub contact_id: String,
+    pub restaurant_id: Option<String>, // None when shared by every restaurant
+    pub distributor_id: String,
+    pub contact_name: String,
+    pub email: Option<String>,
+    pub is_primary: bool,
+    pub is_active: bool,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
 // Distributor product specs
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct DistributorSpec {
@@ -73,7 +86,8 @@ pub struct Restaurant {
     pub synced_at: Option<DateTime<Utc>>,
 }
 
-// Local current prices (proprietary data)
+// Local current prices (proprietary data): one row per restaurant, product and distributor,
+// overwritten in place by newer prices
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct LocalCurrentPrice {
     pub price_id: String,
@@ -84,10 +98,11 @@ pub struct LocalCurrentPrice {
     pub total_preferred_units: f64,
     pub unit_price: f64,
     pub effective_date: NaiveDate,
-    pub source_type: String,
+    pub source_type: String, // 'csv_import', 'manual_entry', 'edi_832' or 'edi_810'
     pub source_file_name: Option<String>,
     pub source_file_hash: Option<String>,
     pub created_at: Option<DateTime<Utc>>,
+    pub pricing_mode: String, // 'case', or 'catch_weight' for a per-unit price on a nominal case weight
 }
 
 // CSV import tracking
@@ -104,7 +119,388 @@ pub struct CsvImport {
     pub failed_count: Option<i32>,
     pub status: String,
     pub error_message: Option<String>,
+    pub imported_by: Option<String>,
+    pub superseded_by: Option<String>,
     pub imported_at: Option<DateTime<Utc>>,
+    pub headers: Option<String>, // JSON array
+    pub rolled_back_at: Option<DateTime<Utc>>,
+    pub source_format: Option<String>, // 'csv', 'xlsx', 'xls', 'edi_832' or 'edi_810'
+    pub sheet_name: Option<String>, // Workbook sheet the prices were read from
+    pub quarantined_count: i32, // Prices held for review instead of written
+    pub email_message_id: Option<String>, // Set when the file was an email attachment
+}
+
+// A price an import inserted or overwrote, with the overwritten values
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CsvImportPriceChange {
+    pub change_id: i64,
+    pub import_id: String,
+    pub price_id: String,
+    pub change_type: String, // 'inserted' or 'updated'
+    pub previous_case_price: Option<f64>,
+    pub previous_total_preferred_units: Option<f64>,
+    pub previous_effective_date: Option<NaiveDate>,
+    pub previous_source_type: Option<String>,
+    pub previous_source_file_name: Option<String>,
+    pub previous_source_file_hash: Option<String>,
+    pub previous_created_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub previous_pricing_mode: Option<String>,
+}
+
+// A distributor pack size such as "4/1 GAL" split into spec fields
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackSize {
+    pub case_packs: i32,
+    pub pack_size: f64,
+    pub pack_unit_of_measure: String,
+    pub total_preferred_units: Option<f64>, // None when the unit can't be converted to the product's measurement
+}
+
+// A file's pack size that disagrees with the synced distributor spec
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SpecChangeCandidate {
+    pub candidate_id: i64,
+    pub import_id: String,
+    pub spec_id: String,
+    pub catalog_product_id: String,
+    pub distributor_id: String,
+    pub distributor_item_code: Option<String>,
+    pub pack_size_text: String,
+    pub case_packs: i32,
+    pub pack_size: f64,
+    pub pack_unit_of_measure: String,
+    pub total_preferred_units: Option<f64>,
+    pub spec_case_packs: i32,
+    pub spec_pack_size: f64,
+    pub spec_pack_unit_of_measure: String,
+    pub spec_total_preferred_units: f64,
+    pub status: String, // 'pending' or 'dismissed'
+    pub detected_at: Option<DateTime<Utc>>,
+}
+
+// An EDI 810 invoice received from a distributor
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EdiInvoice {
+    pub invoice_id: String,
+    pub import_id: String,
+    pub restaurant_id: String,
+    pub distributor_id: String,
+    pub invoice_number: String,
+    pub invoice_date: NaiveDate,
+    pub purchase_order_number: Option<String>,
+    pub seller_name: Option<String>,
+    pub ship_to_name: Option<String>,
+    pub total_amount: Option<f64>,
+    pub line_count: i32,
+    pub unmatched_count: i32,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EdiInvoiceLine {
+    pub line_id: i64,
+    pub invoice_id: String,
+    pub line_number: i32,
+    pub item_code: Option<String>,
+    pub description: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit_of_measure: Option<String>,
+    pub unit_price: Option<f64>,
+    pub pack_size: Option<String>,
+    pub catalog_product_id: Option<String>, // None when no distributor spec has this item code
+}
+
+// One invoice from an 810 file with the prices written from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceImportResult {
+    pub invoice: EdiInvoice,
+    pub lines: Vec<EdiInvoiceLine>,
+    pub unmatched_lines: Vec<EdiInvoiceLine>, // Item codes with no distributor spec, for follow-up
+    pub prices: PriceImportResult,
+}
+
+// The email a price sheet was attached to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailSource {
+    pub message_id: String,
+    pub sender: Option<String>, // From address
+    pub subject: Option<String>,
+    pub sent_date: Option<NaiveDate>,
+}
+
+// One email from an .eml or mbox file with the imports of its attachments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailImportResult {
+    pub email: EmailSource,
+    pub distributor_id: Option<String>, // Given, or worked out from the sender
+    pub already_imported: bool, // Skipped because an import from this message is still active
+    pub imports: Vec<PriceImportResult>,
+    pub errors: Vec<String>, // Per attachment, or why the email couldn't be imported
+}
+
+// Sent as `import-progress` events when a running import starts reading its file and after
+// each batch it writes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub import_id: String, // Pass to cancel_import to stop it
+    pub file_name: String,
+    pub total_rows: i32, // 0 while the file is still being read
+    pub processed_rows: i32,
+    pub matched_rows: i32, // Rows whose item code matched a distributor spec
+    pub failed_rows: i32,
+    pub quarantined_rows: i32, // Held for approval
+}
+
+// Sent to the UI when the watched import folder picks up a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderImportEvent {
+    pub file_name: String,
+    pub moved_to: Option<String>, // Path in the processed or failed subfolder
+    pub distributor_id: Option<String>,
+    pub result: Option<PriceImportResult>,
+    pub error: Option<String>,
+}
+
+// The watched import folder settings, as the Settings page shows and saves them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportWatchSettings {
+    pub folder: Option<String>, // None turns the watcher off
+    pub restaurant_id: Option<String>, // None imports for the only restaurant on this machine
+    pub interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackResult {
+    pub import_id: String,
+    pub prices_removed: i32,
+    pub prices_restored: i32,
+    pub sync_events_removed: i32,
+}
+
+// Why a row was rejected during import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportErrorType {
+    Parsing,
+    Validation,
+    ProductNotFound,
+    MeasurementMismatch,
+}
+
+impl ImportErrorType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImportErrorType::Parsing => "parsing",
+            ImportErrorType::Validation => "validation",
+            ImportErrorType::ProductNotFound => "product_not_found",
+            ImportErrorType::MeasurementMismatch => "measurement_mismatch",
+        }
+    }
+}
+
+// How a price is quoted: per case, or per unit (lb) of a catch-weight item whose cases vary in weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PricingMode {
+    Case,
+    CatchWeight,
+}
+
+impl PricingMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PricingMode::Case => "case",
+            PricingMode::CatchWeight => "catch_weight",
+        }
+    }
+
+    pub fn parse(mode: &str) -> Self {
+        match mode {
+            "catch_weight" => PricingMode::CatchWeight,
+            _ => PricingMode::Case,
+        }
+    }
+}
+
+// Why an imported price was held for approval
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceAnomalyType {
+    PriceJump,
+    PriceDrop,
+    ZeroPrice,
+    PackSizeSwing, // Unit price moved by about a whole pack multiple
+}
+
+impl PriceAnomalyType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PriceAnomalyType::PriceJump => "price_jump",
+            PriceAnomalyType::PriceDrop => "price_drop",
+            PriceAnomalyType::ZeroPrice => "zero_price",
+            PriceAnomalyType::PackSizeSwing => "pack_size_swing",
+        }
+    }
+}
+
+// An imported price held back until someone approves or rejects it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct QuarantinedPrice {
+    pub quarantine_id: String,
+    pub import_id: String,
+    pub restaurant_id: String,
+    pub catalog_product_id: String,
+    pub distributor_id: String,
+    pub spec_id: String,
+    pub row_number: i32,
+    pub case_price: f64,
+    pub total_preferred_units: f64,
+    pub unit_price: f64,
+    pub reference_unit_price: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub anomaly_type: String, // 'price_jump', 'price_drop', 'zero_price' or 'pack_size_swing'
+    pub reason: String,
+    pub effective_date: NaiveDate,
+    pub source_type: String,
+    pub source_file_name: Option<String>,
+    pub source_file_hash: Option<String>,
+    pub status: String, // 'pending', 'approved' or 'rejected'
+    pub reviewed_by: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub pricing_mode: String, // 'case' or 'catch_weight'
+}
+
+// Limits on how far a unit price may move from the product's recent prices before
+// an import holds it; category_id 'default' covers categories without their own
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PriceAnomalyThresholds {
+    pub category_id: String,
+    pub max_increase_percent: f64,
+    pub max_decrease_percent: f64,
+    pub history_days: i32, // How far back prices are compared
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// What approving or rejecting a quarantined price did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineReviewResult {
+    pub quarantined: QuarantinedPrice,
+    pub price_written: bool, // False when rejected, or a newer price already exists
+}
+
+// Kind of file a price sheet was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceFormat {
+    Csv,
+    Xlsx,
+    Xls,
+    Edi832, // X12 832 price/sales catalog
+    Edi810, // X12 810 invoice
+}
+
+impl SourceFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SourceFormat::Csv => "csv",
+            SourceFormat::Xlsx => "xlsx",
+            SourceFormat::Xls => "xls",
+            SourceFormat::Edi832 => "edi_832",
+            SourceFormat::Edi810 => "edi_810",
+        }
+    }
+
+    // local_current_prices.source_type for prices read from this kind of file
+    pub fn price_source_type(self) -> &'static str {
+        match self {
+            SourceFormat::Csv | SourceFormat::Xlsx | SourceFormat::Xls => "csv_import",
+            SourceFormat::Edi832 => "edi_832",
+            SourceFormat::Edi810 => "edi_810",
+        }
+    }
+}
+
+// Where in a file an import found its price table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSource {
+    pub format: SourceFormat,
+    pub file_format: Option<CsvFileFormat>, // Set for CSV files
+    pub sheet_name: Option<String>, // Set for workbooks
+    pub header_row: i32, // 1-based row of the header, after any preamble rows; 0 for EDI
+}
+
+// A rejected import row
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CsvImportError {
+    pub error_id: i64,
+    pub import_id: String,
+    pub row_number: i32,
+    pub error_type: String,
+    pub error_message: String,
+    pub row_data: Option<String>, // JSON array of the row's original values
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// Saved column mapping for a distributor's CSV layout
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CsvMappingProfile {
+    pub distributor_id: String,
+    pub column_mapping: String, // JSON CsvColumnMapping
+    pub headers: String, // JSON array
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub delimiter: Option<String>, // Overrides the csv_delimiter setting
+    pub encoding: Option<String>, // Overrides the csv_encoding setting
+}
+
+// Built-in layout of a distributor's standard export, read from a preset JSON file.
+// Column lists are header names to look for, in order of preference.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreset {
+    pub preset_id: String,
+    pub distributor_code: String, // Matches distributors.distributor_code, e.g. "SYSCO"
+    pub name: String,
+    #[serde(default)]
+    pub header_row: Option<i32>, // 1-based row the header is usually on
+    pub columns: PresetColumns,
+    #[serde(default)]
+    pub pack_count_columns: Vec<String>, // Separate pack count column, joined to the size as "6/#10"
+    #[serde(default)]
+    pub effective_date: PresetDateLocation,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresetColumns {
+    pub item_code: Vec<String>,
+    pub description: Vec<String>,
+    pub case_price: Vec<String>,
+    pub pack_size: Vec<String>,
+    pub unit: Vec<String>,
+}
+
+impl PresetColumns {
+    pub fn get(&self, field: CsvField) -> &[String] {
+        match field {
+            CsvField::ItemCode => &self.item_code,
+            CsvField::Description => &self.description,
+            CsvField::CasePrice => &self.case_price,
+            CsvField::PackSize => &self.pack_size,
+            CsvField::Unit => &self.unit,
+        }
+    }
+}
+
+// Where a distributor's export carries its price date
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "location", rename_all = "snake_case")]
+pub enum PresetDateLocation {
+    #[default]
+    Preamble, // Text in or above the header row
+    FileName,
+    Column { headers: Vec<String> }, // A per-row date column, e.g. "Price Date"
 }
 
 // Sync events pending Monday upload
@@ -128,26 +524,432 @@ pub struct PriceWithDetails {
     pub product_name: String,
     pub distributor_name: String,
     pub is_winner: bool,
+    pub winner_rule: Option<WinnerRule>, // How the price won, when is_winner
+    pub preferred_measurement: String, // Unit of unit_price, e.g. "lb"
+    pub price_label: String, // "$22.00/case ($0.85/lb)"
+    pub substitution: Option<SubstitutionWinner>, // Set when the product is in a substitution group
+    pub age_days: i64, // Days since effective_date
+    pub is_stale: bool, // Older than its category's max price age
+    pub excluded_reason: Option<ExclusionReason>, // Listed, but not allowed to win
+    pub tie_tolerance: Option<String>, // "within 2%" when the price won a tie-break
+}
+
+// Which member of a product's substitution group won, e.g. 5x6 tomatoes for "Tomatoes - Any Size"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstitutionWinner {
+    pub group_id: String,
+    pub group_name: String,
+    pub winning_product_id: String,
+    pub winning_product_name: String,
+    pub winning_distributor_name: String,
+}
+
+// A restaurant's distributor preference for a product
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProductPreference {
+    pub restaurant_id: String,
+    pub catalog_product_id: String,
+    pub preferred_distributor_id: Option<String>,
+    pub always_use_preferred: bool, // Use the preferred distributor regardless of price
+    pub notes: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub synced_at: Option<DateTime<Utc>>,
+    pub pending_upload: bool, // Edited here and not uploaded yet
+    pub upload_error: Option<String>, // Why the last upload failed; saving the preference again retries it
+}
+
+// Interchangeable products whose prices compete with each other
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SubstitutionGroup {
+    pub group_id: String,
+    pub restaurant_id: String,
+    pub group_name: String, // "Tomatoes - Any Size"
+    pub measurement_type: String, // Every member's measurement_type
+    pub is_active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SubstitutionGroupMember {
+    pub group_id: String,
+    pub catalog_product_id: String,
+    pub product_name: Option<String>, // None when the product is no longer in the catalog
+    pub pending_upload: bool, // Added here and not uploaded yet
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstitutionGroupWithMembers {
+    pub group: SubstitutionGroup,
+    pub members: Vec<SubstitutionGroupMember>,
+}
+
+// Which step of the winner calculation (BUSINESS_LOGIC.md) chose a price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WinnerRule {
+    HardPreference, // always_use_preferred
+    Substitution, // Cheapest across a substitution group's products
+    Cheapest, // Lowest unit_price among the product's distributors
+    TieBreak, // Tied on unit_price, the preferred distributor
+}
+
+// How a restaurant's winner calculation treats near-equal prices
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WinnerSettings {
+    pub restaurant_id: String,
+    pub tie_tolerance_type: String, // 'percent' of the lower unit price, or 'amount' per preferred unit
+    pub tie_tolerance_value: f64, // 0 ties only equal prices
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// How old a category's prices may get before they are stale ('default' for the fallback)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PriceAgeLimit {
+    pub restaurant_id: String,
+    pub category_id: String,
+    pub max_age_days: i32,
+    pub stale_action: String, // 'exclude' keeps stale prices from winning, 'flag' lets them compete marked stale
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// Why a product's winner was chosen, for "why did Sysco win?"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WinnerExplanation {
+    pub restaurant_id: String,
+    pub catalog_product_id: String,
+    pub product_name: String,
+    pub group_id: Option<String>, // Substitution group whose members competed
+    pub group_name: Option<String>,
+    pub rule: Option<WinnerRule>, // None when there was no price to choose
+    pub tie_tolerance: String, // "within 2%", or "exact" when only equal prices tie
+    pub winner: Option<WinnerCandidate>,
+    pub steps: Vec<String>, // The decision trace, in the order the rules were applied
+    pub candidates: Vec<WinnerCandidate>, // Prices that competed, cheapest first
+    pub excluded: Vec<ExcludedCandidate>, // Prices and specs left out, and why
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WinnerCandidate {
+    pub price_id: String,
+    pub catalog_product_id: String, // Another member's product when a substitution group competed
+    pub product_name: String,
+    pub distributor_id: String,
+    pub distributor_name: String,
+    pub case_price: f64,
+    pub total_preferred_units: f64,
+    pub unit_price: f64,
+    pub effective_date: NaiveDate,
+    pub price_label: String,
+    pub is_winner: bool,
+    pub is_preferred: bool, // From the product's preferred distributor
+    pub age_days: i64,
+    pub is_stale: bool, // Past its category's max price age, but allowed to compete
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludedCandidate {
+    pub catalog_product_id: String,
+    pub product_name: String,
+    pub distributor_id: String,
+    pub distributor_name: String,
+    pub price_id: Option<String>, // None when the distributor has no price at all
+    pub case_price: Option<f64>,
+    pub total_preferred_units: Option<f64>,
+    pub unit_price: Option<f64>,
+    pub effective_date: Option<NaiveDate>,
+    pub reason: ExclusionReason,
+    pub detail: String, // "PFG carries Tomatoes 6x6 but has no price for it yet"
+    pub age_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    MissingSpec, // No distributor spec, so the case can't be converted to preferred units
+    NoUsablePackSize, // No preferred units per case, so there is no unit price
+    NoPrice, // A spec but no price from the distributor yet
+    Stale, // Older than its category's max price age
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceImportResult {
+    pub import_id: String,
     pub total_rows: i32,
     pub successful_imports: i32,
+    pub skipped_imports: i32, // Rows whose existing price was already as new or newer
     pub failed_imports: i32,
+    pub quarantined_imports: i32, // Prices held for approval as likely mistakes
     pub errors: Vec<String>,
     pub distributor_id: String,
     pub effective_date: NaiveDate,
+    pub effective_date_source: EffectiveDateSource,
+    pub effective_date_candidates: Vec<EffectiveDateCandidate>, // Shown so the user can pick another date
+    pub mapping: CsvColumnMapping,
+    pub mapping_source: MappingSource,
+    pub preset_id: Option<String>, // Set when mapping_source is Preset
+    pub profile_drift: Option<MappingProfileDrift>, // Offer to update the saved profile when set
+    pub source: ImportSource,
+    pub spec_change_candidates: Vec<SpecChangeCandidate>,
 }
 
-// Column mapping for CSV imports
+// Fields an import needs to locate in a distributor file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CsvField {
+    ItemCode,
+    Description,
+    CasePrice,
+    PackSize,
+    Unit,
+}
+
+impl CsvField {
+    pub const ALL: [CsvField; 5] = [
+        CsvField::ItemCode,
+        CsvField::Description,
+        CsvField::CasePrice,
+        CsvField::PackSize,
+        CsvField::Unit,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CsvField::ItemCode => "item code",
+            CsvField::Description => "description",
+            CsvField::CasePrice => "case price",
+            CsvField::PackSize => "pack size",
+            CsvField::Unit => "unit of measure",
+        }
+    }
+}
+
+// A file column matched to a field, with how sure header detection is about it
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMatch {
+    pub column_index: usize,
+    pub header: String,
+    pub confidence: f64, // 0.0 - 1.0, user-confirmed columns are 1.0
+    pub ambiguous: bool, // Ask the user before importing
+}
+
+// Column mapping for CSV imports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CsvColumnMapping {
-    pub product_name_column: Option<String>,
-    pub product_code_column: Option<String>,
-    pub price_column: Option<String>,
-    pub pack_size_column: Option<String>,
-    pub unit_column: Option<String>,
+    pub item_code: Option<ColumnMatch>,
+    pub description: Option<ColumnMatch>,
+    pub case_price: Option<ColumnMatch>,
+    pub pack_size: Option<ColumnMatch>,
+    pub unit: Option<ColumnMatch>,
+}
+
+impl CsvColumnMapping {
+    pub fn get(&self, field: CsvField) -> Option<&ColumnMatch> {
+        match field {
+            CsvField::ItemCode => self.item_code.as_ref(),
+            CsvField::Description => self.description.as_ref(),
+            CsvField::CasePrice => self.case_price.as_ref(),
+            CsvField::PackSize => self.pack_size.as_ref(),
+            CsvField::Unit => self.unit.as_ref(),
+        }
+    }
+
+    pub fn set(&mut self, field: CsvField, column: Option<ColumnMatch>) {
+        match field {
+            CsvField::ItemCode => self.item_code = column,
+            CsvField::Description => self.description = column,
+            CsvField::CasePrice => self.case_price = column,
+            CsvField::PackSize => self.pack_size = column,
+            CsvField::Unit => self.unit = column,
+        }
+    }
+
+    pub fn column(&self, field: CsvField) -> Option<usize> {
+        self.get(field).map(|m| m.column_index)
+    }
+
+    // True when any matched column should be confirmed by the user
+    pub fn needs_review(&self) -> bool {
+        CsvField::ALL
+            .iter()
+            .filter_map(|field| self.get(*field))
+            .any(|m| m.ambiguous)
+    }
+}
+
+// Where an import's effective_date came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectiveDateSource {
+    UserOverride,
+    HeaderRow,
+    Document, // Date the document carries itself: an EDI date segment or a preset's date column
+    FileName,
+    EmailSent, // Date of the email the file was attached to
+    FileCreated,
+    FileModified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveDateCandidate {
+    pub effective_date: NaiveDate,
+    pub source: EffectiveDateSource,
+    pub detail: String, // e.g. File name "sysco_2024-01-22.csv"
+}
+
+// The chosen effective_date plus every date that was considered, best first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveDateSuggestion {
+    pub effective_date: NaiveDate,
+    pub source: EffectiveDateSource,
+    pub candidates: Vec<EffectiveDateCandidate>,
+}
+
+// Choices made on the Import page, all optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportOptions {
+    pub effective_date: Option<NaiveDate>, // Overrides the derived date
+    pub mapping: Option<CsvColumnMapping>, // Overrides the saved profile and header detection
+    pub reimport: bool, // Import a file that was already imported, superseding the earlier import
+    pub sheet: Option<String>, // Workbook sheet to read, otherwise the first with a price header
+    pub header_row: Option<i32>, // 1-based header row, otherwise found by looking for the price columns
+    #[serde(skip)]
+    pub email: Option<EmailSource>, // Set by email ingestion for each attachment
+}
+
+// What an import did, or would do, with a single row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ImportRowOutcome {
+    NewPrice {
+        unit_price: f64,
+    },
+    PriceChange {
+        old_case_price: f64,
+        new_case_price: f64,
+        old_unit_price: f64,
+        new_unit_price: f64,
+        change_percent: f64,
+        previous_effective_date: NaiveDate,
+    },
+    SkippedOlder {
+        existing_effective_date: NaiveDate,
+    },
+    UnmatchedItemCode,
+    Quarantined {
+        quarantine_id: String,
+        anomaly_type: PriceAnomalyType,
+        reason: String,
+        new_unit_price: f64,
+        reference_unit_price: Option<f64>,
+    },
+    MeasurementMismatch {
+        message: String,
+    },
+    ValidationError {
+        message: String,
+    },
+    ParseError {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowPreview {
+    pub row_number: i32,
+    pub item_code: Option<String>,
+    pub description: Option<String>,
+    pub case_price: Option<f64>,
+    pub catalog_product_id: Option<String>,
+    pub product_name: Option<String>,
+    pub pack_size: Option<PackSize>, // Parsed from the file's pack/size column
+    pub spec_change_candidate: bool, // Pack size disagrees with the synced spec
+    pub pricing_mode: PricingMode, // Catch-weight rows show their per-unit price
+    pub match_candidates: Vec<ProductMatchCandidate>, // Proposed products for unmatched rows
+    #[serde(flatten)]
+    pub outcome: ImportRowOutcome,
+}
+
+// A product proposed for a row whose item code didn't match, best first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductMatchCandidate {
+    pub spec_id: String,
+    pub catalog_product_id: String,
+    pub product_name: String,
+    pub distributor_item_code: Option<String>,
+    pub score: f64, // 0.0 - 1.0, description similarity weighted with pack-size compatibility
+    pub description_score: f64,
+    pub pack_size_compatible: Option<bool>, // None when the row has no readable pack size
+}
+
+// A confirmed match remembered for the next import
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ItemCodeAlias {
+    pub alias_id: i64,
+    pub distributor_id: String,
+    pub alias_type: String, // 'item_code' or 'description'
+    pub alias: String,
+    pub spec_id: String,
+    pub catalog_product_id: String,
+    pub confirmed_by: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// Dry-run of an import, nothing is written
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub result: PriceImportResult,
+    pub distributor_name: String,
+    pub rows: Vec<ImportRowPreview>,
+}
+
+// Where the column mapping used for an import came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MappingSource {
+    Provided, // Confirmed by the user on the Import page
+    Profile,  // Saved profile for the distributor
+    Detected, // Header detection
+    Edi, // Fixed EDI segments, nothing to map
+    Preset, // Built-in layout for the distributor's standard export
+}
+
+// A saved profile whose headers no longer match the file being imported
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappingProfileDrift {
+    pub saved_headers: Vec<String>,
+    pub current_headers: Vec<String>,
+    pub missing_headers: Vec<String>, // Mapped headers the file no longer has
+    pub suggested_mapping: CsvColumnMapping,
+}
+
+// Headers and detected mapping shown on the Import page before importing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvMappingPreview {
+    pub headers: Vec<String>,
+    pub mapping: CsvColumnMapping,
+    pub mapping_source: MappingSource,
+    pub preset_id: Option<String>,
+    pub profile_drift: Option<MappingProfileDrift>,
+    pub needs_review: bool,
+    pub source: ImportSource,
+}
+
+// Delimiter, quote character and encoding a file was read with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvFileFormat {
+    pub delimiter: String,
+    pub quote: String,
+    pub encoding: String, // e.g. "UTF-8", "UTF-16LE", "windows-1252"
+}
+
+// The csv_delimiter / csv_encoding app settings, used for files whose distributor profile
+// doesn't set its own; "auto" sniffs them from the file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvDialectSettings {
+    pub delimiter: String,
+    pub encoding: String,
 }
 
 // Legacy models (remove these after updating all references)
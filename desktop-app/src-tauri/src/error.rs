@@ -35,6 +35,9 @@ pub enum AppError {
     
     #[error("Configuration error: {0}")]
     Config(String),
+    
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
 }
 
 // Convert AppError to a serializable format for the frontend
@@ -53,6 +56,7 @@ impl AppError {
                 AppError::AlreadyExists(_) => "already_exists",
                 AppError::Internal(_) => "internal",
                 AppError::Config(_) => "config",
+                AppError::Cancelled(_) => "cancelled",
             },
             message: self.to_string(),
         }
@@ -3,6 +3,7 @@
 
 mod db;
 mod csv_import;
+mod pricing;
 mod sync;
 mod commands;
 mod state;
@@ -37,7 +38,57 @@ fn main() {
             commands::get_auth_status,
             commands::force_sync,
             commands::get_current_prices,
+            commands::get_winning_prices,
+            commands::explain_winner,
+            commands::get_winner_settings,
+            commands::save_tie_tolerance,
+            commands::get_price_age_limits,
+            commands::save_price_age_limit,
+            commands::delete_price_age_limit,
+            commands::get_product_preferences,
+            commands::save_product_preference,
+            commands::delete_product_preference,
+            commands::get_substitution_groups,
+            commands::add_substitution_group_member,
+            commands::remove_substitution_group_member,
             commands::get_distributors,
+            commands::import_csv_file,
+            commands::cancel_import,
+            commands::preview_csv_import,
+            commands::detect_effective_date,
+            commands::find_duplicate_import,
+            commands::get_import_errors,
+            commands::export_rejected_rows,
+            commands::get_import_history,
+            commands::rollback_import,
+            commands::detect_csv_columns,
+            commands::list_workbook_sheets,
+            commands::import_edi_invoice,
+            commands::get_edi_invoices,
+            commands::get_edi_invoice_lines,
+            commands::list_mapping_profiles,
+            commands::save_mapping_profile,
+            commands::delete_mapping_profile,
+            commands::get_csv_dialect_settings,
+            commands::save_csv_dialect_settings,
+            commands::list_import_presets,
+            commands::reload_import_presets,
+            commands::suggest_product_matches,
+            commands::confirm_product_match,
+            commands::get_item_code_aliases,
+            commands::delete_item_code_alias,
+            commands::get_quarantined_prices,
+            commands::approve_quarantined_price,
+            commands::reject_quarantined_price,
+            commands::get_price_anomaly_thresholds,
+            commands::save_price_anomaly_thresholds,
+            commands::delete_price_anomaly_thresholds,
+            commands::get_import_watch_settings,
+            commands::set_import_watch_settings,
+            commands::import_email_file,
+            commands::get_distributor_sales_contacts,
+            commands::get_spec_change_candidates,
+            commands::dismiss_spec_change_candidate,
             commands::init_demo_data,
         ])
         .setup(|app| {
@@ -51,10 +102,30 @@ fn main() {
                 match db::init_database(&app_handle).await {
                     Ok(pool) => {
                         info!("Database initialized successfully");
+                        
+                        // Undo imports a previous run left part-way through
+                        if let Err(e) = csv_import::rollback::recover_interrupted_imports(&pool).await {
+                            error!("Failed to recover interrupted imports: {}", e);
+                        }
+                        
+                        // Import presets are data files, read fresh on every start
+                        let preset_dirs = csv_import::presets::preset_dirs(&app_handle);
+                        if let Err(e) = csv_import::presets::load_presets(&pool, &preset_dirs).await {
+                            error!("Failed to load import presets: {}", e);
+                        }
+                        
                         let mut db_lock = state.db.lock().await;
                         *db_lock = Some(pool);
                         drop(db_lock); // Explicitly drop the lock
                         
+                        // Watch the import folder whether or not cloud sync comes up
+                        let watcher_handle = app_handle.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = csv_import::watcher::start_folder_watcher(watcher_handle).await {
+                                error!("Import folder watcher stopped: {}", e);
+                            }
+                        });
+                        
                         // Step 2: Load Supabase configuration
                         match config::SupabaseConfig::from_env() {
                             Ok(config) => {
@@ -0,0 +1,474 @@
+use crate::db::{
+    models::{
+        ExcludedCandidate, ExclusionReason, LocalCurrentPrice, WinnerCandidate, WinnerExplanation, WinnerRule,
+    },
+    DbPool,
+};
+use crate::error::AppError;
+use std::collections::HashSet;
+
+use super::{
+    decide, is_preferred, load_inputs, money, price_label, ExcludedPrice, Names, PriceIndex, TieTolerance, WinnerInputs,
+};
+
+// Why a product's winner won: the winner calculation for that one product, with every
+// price it weighed and every price or spec it left out
+pub async fn explain_winner(
+    pool: &DbPool,
+    restaurant_id: &str,
+    catalog_product_id: &str,
+) -> Result<WinnerExplanation, AppError> {
+    let names = Names::load(pool, restaurant_id).await?;
+    let product_exists = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM products WHERE catalog_product_id = ?")
+        .bind(catalog_product_id)
+        .fetch_one(pool)
+        .await?;
+    if product_exists == 0 {
+        return Err(AppError::NotFound(format!("Product {}", catalog_product_id)));
+    }
+
+    let inputs = load_inputs(pool, restaurant_id).await?;
+    let index = PriceIndex::new(&inputs);
+    let decision = decide(&inputs, &index, catalog_product_id);
+    let product_name = names.product(catalog_product_id);
+
+    let members: Vec<String> = match decision.group_id {
+        Some(group_id) => inputs.groups[group_id].clone(),
+        None => vec![catalog_product_id.to_string()],
+    };
+    let winner_id = decision.winner.map(|(price, _)| price.price_id.as_str());
+    let candidate = |price: &LocalCurrentPrice| WinnerCandidate {
+        price_id: price.price_id.clone(),
+        catalog_product_id: price.catalog_product_id.clone(),
+        product_name: names.product(&price.catalog_product_id),
+        distributor_id: price.distributor_id.clone(),
+        distributor_name: names.distributor(&price.distributor_id),
+        case_price: price.case_price,
+        total_preferred_units: price.total_preferred_units,
+        unit_price: price.unit_price,
+        effective_date: price.effective_date,
+        price_label: price_label(price, &names.measurement(&price.catalog_product_id)),
+        is_winner: Some(price.price_id.as_str()) == winner_id,
+        is_preferred: is_preferred(price, &inputs.preferences),
+        age_days: inputs.age_days(price),
+        is_stale: inputs.stale.contains_key(&price.price_id),
+    };
+
+    let mut candidates: Vec<WinnerCandidate> = decision.candidates.iter().map(|price| candidate(price)).collect();
+    candidates.sort_by(|a, b| {
+        a.unit_price
+            .total_cmp(&b.unit_price)
+            .then_with(|| b.effective_date.cmp(&a.effective_date))
+            .then_with(|| a.distributor_name.cmp(&b.distributor_name))
+    });
+
+    // The trace, one line per rule in the order BUSINESS_LOGIC.md applies them
+    let describe = |price: &LocalCurrentPrice| {
+        let mut text = format!(
+            "{} from {}",
+            price_label(price, &names.measurement(&price.catalog_product_id)),
+            names.distributor(&price.distributor_id)
+        );
+        if price.catalog_product_id != catalog_product_id {
+            text.push_str(&format!(" ({})", names.product(&price.catalog_product_id)));
+        }
+        text
+    };
+    let mut steps = Vec::new();
+
+    match (decision.hard_preference, decision.winner) {
+        (Some(preference), Some((price, WinnerRule::HardPreference))) => {
+            steps.push(format!(
+                "Hard preference: always buy {} from {}",
+                product_name,
+                names.distributor(&preference.preferred_distributor_id)
+            ));
+            steps.push(format!(
+                "{} wins regardless of price: {}, effective {}",
+                names.distributor(&price.distributor_id),
+                price_label(price, &names.measurement(&price.catalog_product_id)),
+                price.effective_date
+            ));
+        }
+        (Some(preference), _) => steps.push(format!(
+            "Hard preference for {} skipped: it has no current price for {}, so price decides",
+            names.distributor(&preference.preferred_distributor_id),
+            product_name
+        )),
+        (None, _) => steps.push(format!("No hard preference for {}, so price decides", product_name)),
+    }
+
+    if decision.winner.is_some_and(|(_, rule)| rule != WinnerRule::HardPreference) {
+        let products: HashSet<&str> = decision.candidates.iter().map(|price| price.catalog_product_id.as_str()).collect();
+        match decision.group_id {
+            Some(group_id) => steps.push(format!(
+                "{} is in substitution group {}: {} prices from {} products compete",
+                product_name,
+                names.group(group_id),
+                decision.candidates.len(),
+                products.len()
+            )),
+            None if decision.candidates.len() == 1 => steps.push("Only 1 price to choose from".to_string()),
+            None => steps.push(format!("{} prices compete", decision.candidates.len())),
+        }
+
+        let stale: Vec<String> = decision
+            .candidates
+            .iter()
+            .filter_map(|price| {
+                let max_age_days = inputs.stale.get(&price.price_id)?;
+                Some(format!(
+                    "{} is {} days old, past the {}-day limit",
+                    describe(price),
+                    inputs.age_days(price),
+                    max_age_days
+                ))
+            })
+            .collect();
+        if !stale.is_empty() {
+            steps.push(format!("Stale but still competing: {}", stale.join("; ")));
+        }
+
+        match decision.tied.as_slice() {
+            [] => {}
+            [only] => steps.push(format!("Lowest unit price: {}", describe(only))),
+            tied @ [first, rest @ ..] => {
+                let lowest = rest
+                    .iter()
+                    .copied()
+                    .fold(*first, |lowest, price| if price.unit_price < lowest.unit_price { price } else { lowest });
+                let tied: Vec<String> = tied.iter().map(|price| describe(price)).collect();
+                let within = match inputs.tie_tolerance {
+                    TieTolerance::Exact => "at".to_string(),
+                    tolerance => format!("{} of", tolerance.describe(&names.measurement(&lowest.catalog_product_id))),
+                };
+                steps.push(format!(
+                    "Tied {} {}/{}: {}",
+                    within,
+                    money(lowest.unit_price),
+                    names.measurement(&lowest.catalog_product_id),
+                    tied.join("; ")
+                ));
+            }
+        }
+
+        if let Some((price, rule)) = decision.winner {
+            steps.push(match rule {
+                WinnerRule::TieBreak => format!(
+                    "Tie broken by preference: {} is the preferred distributor for {}",
+                    names.distributor(&price.distributor_id),
+                    names.product(&price.catalog_product_id)
+                ),
+                _ if decision.tied.len() > 1 => format!(
+                    "No preferred distributor among the tied prices; the lowest wins: {}, effective {}",
+                    describe(price),
+                    price.effective_date
+                ),
+                WinnerRule::Substitution => format!("Cheapest across the group: {}", describe(price)),
+                _ => format!("Cheapest: {}", describe(price)),
+            });
+        }
+    }
+
+    if decision.winner.is_none() {
+        steps.push(format!("No current prices for {}", product_name));
+    }
+
+    Ok(WinnerExplanation {
+        restaurant_id: restaurant_id.to_string(),
+        catalog_product_id: catalog_product_id.to_string(),
+        product_name,
+        group_id: decision.group_id.map(str::to_string),
+        group_name: decision.group_id.map(|group_id| names.group(group_id)),
+        rule: decision.winner.map(|(_, rule)| rule),
+        tie_tolerance: inputs.tie_tolerance.describe(&names.measurement(catalog_product_id)),
+        winner: decision.winner.map(|(price, _)| candidate(price)),
+        steps,
+        candidates,
+        excluded: excluded_candidates(pool, restaurant_id, &members, &inputs, &names).await?,
+    })
+}
+
+// Prices and specs of the competing products that the calculation didn't weigh: prices it
+// excluded, and distributors with a spec but no price yet
+async fn excluded_candidates(
+    pool: &DbPool,
+    restaurant_id: &str,
+    members: &[String],
+    inputs: &WinnerInputs,
+    names: &Names,
+) -> Result<Vec<ExcludedCandidate>, AppError> {
+    let is_member = |product_id: &str| members.iter().any(|member| member == product_id);
+    let mut excluded = Vec::new();
+
+    for ExcludedPrice { price, reason, max_age_days } in
+        inputs.excluded.iter().filter(|e| is_member(&e.price.catalog_product_id))
+    {
+        let distributor_name = names.distributor(&price.distributor_id);
+        let product_name = names.product(&price.catalog_product_id);
+        let detail = match reason {
+            ExclusionReason::MissingSpec => format!(
+                "{} has no spec for {}, so its case can't be converted to {}",
+                distributor_name,
+                product_name,
+                names.measurement(&price.catalog_product_id)
+            ),
+            ExclusionReason::NoUsablePackSize => format!(
+                "The {} price for {} has no usable pack size: its case holds no {}, so it has no unit price",
+                distributor_name,
+                product_name,
+                names.measurement(&price.catalog_product_id)
+            ),
+            ExclusionReason::NoPrice => format!("{} has no price for {}", distributor_name, product_name),
+            ExclusionReason::Stale => format!(
+                "The {} price for {} is {} days old, past the {}-day limit",
+                distributor_name,
+                product_name,
+                inputs.age_days(price),
+                max_age_days.unwrap_or_default()
+            ),
+        };
+        excluded.push(ExcludedCandidate {
+            catalog_product_id: price.catalog_product_id.clone(),
+            product_name,
+            distributor_id: price.distributor_id.clone(),
+            distributor_name,
+            price_id: Some(price.price_id.clone()),
+            case_price: Some(price.case_price),
+            total_preferred_units: Some(price.total_preferred_units),
+            unit_price: (*reason != ExclusionReason::NoUsablePackSize).then_some(price.unit_price),
+            effective_date: Some(price.effective_date),
+            reason: *reason,
+            detail,
+            age_days: Some(inputs.age_days(price)),
+        });
+    }
+
+    let member_list = serde_json::to_string(members)?;
+
+    // Distributors that carry a competing product but haven't quoted it
+    let unpriced = sqlx::query_as::<_, (String, String)>(
+        r#"SELECT s.catalog_product_id, s.distributor_id FROM distributor_specs s
+           WHERE s.catalog_product_id IN (SELECT value FROM json_each(?))
+             AND NOT EXISTS (
+                 SELECT 1 FROM local_current_prices p
+                 WHERE p.restaurant_id = ? AND p.catalog_product_id = s.catalog_product_id
+                   AND p.distributor_id = s.distributor_id
+             )
+           ORDER BY s.catalog_product_id, s.distributor_id"#
+    )
+    .bind(&member_list)
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    for (product_id, distributor_id) in unpriced {
+        excluded.push(ExcludedCandidate {
+            product_name: names.product(&product_id),
+            distributor_name: names.distributor(&distributor_id),
+            detail: format!(
+                "{} carries {} but has no price for it yet",
+                names.distributor(&distributor_id),
+                names.product(&product_id)
+            ),
+            catalog_product_id: product_id,
+            distributor_id,
+            price_id: None,
+            case_price: None,
+            total_preferred_units: None,
+            unit_price: None,
+            effective_date: None,
+            reason: ExclusionReason::NoPrice,
+            age_days: None,
+        });
+    }
+
+    Ok(excluded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_pool;
+    use crate::pricing::{preferences, settings};
+    use chrono::{Duration, Local};
+
+    async fn seeded_pool() -> DbPool {
+        let pool = test_pool().await;
+        for statement in [
+            "INSERT INTO restaurants (restaurant_id, restaurant_name, organization_id) VALUES ('r1', 'Bistro', 'o1')",
+            "INSERT INTO distributors (distributor_id, distributor_name, distributor_code) VALUES ('d1', 'Sysco', 'SYS')",
+            "INSERT INTO distributors (distributor_id, distributor_name, distributor_code) VALUES ('d2', 'US Foods', 'USF')",
+            "INSERT INTO distributors (distributor_id, distributor_name, distributor_code) VALUES ('d3', 'PFG', 'PFG')",
+            "INSERT INTO products (catalog_product_id, product_name, preferred_measurement, measurement_type, category_id)
+             VALUES ('p1', 'Roma Tomatoes', 'lb', 'weight', 'c1')",
+            "INSERT INTO products (catalog_product_id, product_name, preferred_measurement, measurement_type, category_id)
+             VALUES ('p2', 'Vine Tomatoes', 'lb', 'weight', 'c1')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        for product_id in ["p1", "p2"] {
+            for distributor_id in ["d1", "d2", "d3"] {
+                sqlx::query(
+                    r#"INSERT INTO distributor_specs (spec_id, catalog_product_id, distributor_id, distributor_item_code,
+                       case_packs, pack_size, pack_unit_of_measure, total_preferred_units)
+                       VALUES (?, ?, ?, ?, 1, 25, 'lb', 25)"#
+                )
+                .bind(format!("{}-{}", product_id, distributor_id))
+                .bind(product_id)
+                .bind(distributor_id)
+                .bind(format!("{}-{}", distributor_id, product_id))
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+        pool
+    }
+
+    // A 25 lb case quoted `days_old` days ago
+    async fn add_price(pool: &DbPool, product_id: &str, distributor_id: &str, case_price: f64, days_old: i64) -> String {
+        let effective_date = Local::now().date_naive() - Duration::days(days_old);
+        sqlx::query(
+            r#"INSERT INTO local_current_prices (price_id, restaurant_id, catalog_product_id, distributor_id, case_price,
+               total_preferred_units, effective_date, source_type)
+               VALUES (?, 'r1', ?, ?, ?, 25, ?, 'manual_entry')"#
+        )
+        .bind(format!("{}-{}", product_id, distributor_id))
+        .bind(product_id)
+        .bind(distributor_id)
+        .bind(case_price)
+        .bind(effective_date)
+        .execute(pool)
+        .await
+        .unwrap();
+        effective_date.to_string()
+    }
+
+    async fn steps(pool: &DbPool, product_id: &str) -> Vec<String> {
+        explain_winner(pool, "r1", product_id).await.unwrap().steps
+    }
+
+    #[tokio::test]
+    async fn a_hard_preference_decides_or_is_skipped() {
+        let pool = seeded_pool().await;
+        let sysco_date = add_price(&pool, "p1", "d1", 25.00, 0).await;
+        add_price(&pool, "p1", "d2", 20.00, 0).await;
+
+        preferences::save_preference(&pool, "r1", "p1", Some("d1"), true, None).await.unwrap();
+        assert_eq!(
+            steps(&pool, "p1").await,
+            vec![
+                "Hard preference: always buy Roma Tomatoes from Sysco".to_string(),
+                format!("Sysco wins regardless of price: $25.00/case ($1.00/lb), effective {}", sysco_date),
+            ]
+        );
+
+        preferences::save_preference(&pool, "r1", "p1", Some("d3"), true, None).await.unwrap();
+        assert_eq!(
+            steps(&pool, "p1").await,
+            vec![
+                "Hard preference for PFG skipped: it has no current price for Roma Tomatoes, so price decides",
+                "2 prices compete",
+                "Lowest unit price: $20.00/case ($0.80/lb) from US Foods",
+                "Cheapest: $20.00/case ($0.80/lb) from US Foods",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_group_competes_across_its_products() {
+        let pool = seeded_pool().await;
+        add_price(&pool, "p1", "d1", 25.00, 0).await;
+        add_price(&pool, "p2", "d2", 20.00, 0).await;
+        for statement in [
+            "INSERT INTO substitution_groups (group_id, restaurant_id, group_name, measurement_type)
+             VALUES ('g1', 'r1', 'Tomatoes - Any Size', 'weight')",
+            "INSERT INTO substitution_group_members (group_id, catalog_product_id) VALUES ('g1', 'p1')",
+            "INSERT INTO substitution_group_members (group_id, catalog_product_id) VALUES ('g1', 'p2')",
+        ] {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+
+        let explanation = explain_winner(&pool, "r1", "p1").await.unwrap();
+        assert_eq!(explanation.rule, Some(WinnerRule::Substitution));
+        assert_eq!(explanation.group_name.as_deref(), Some("Tomatoes - Any Size"));
+        assert_eq!(
+            explanation.steps,
+            vec![
+                "No hard preference for Roma Tomatoes, so price decides",
+                "Roma Tomatoes is in substitution group Tomatoes - Any Size: 2 prices from 2 products compete",
+                "Lowest unit price: $20.00/case ($0.80/lb) from US Foods (Vine Tomatoes)",
+                "Cheapest across the group: $20.00/case ($0.80/lb) from US Foods (Vine Tomatoes)",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stale_prices_that_still_compete_are_called_out() {
+        let pool = seeded_pool().await;
+        sqlx::query(
+            "INSERT INTO price_age_limits (restaurant_id, category_id, max_age_days, stale_action) VALUES ('r1', 'default', 30, 'flag')"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        add_price(&pool, "p1", "d1", 20.00, 100).await;
+        add_price(&pool, "p1", "d2", 25.00, 0).await;
+
+        assert_eq!(
+            steps(&pool, "p1").await,
+            vec![
+                "No hard preference for Roma Tomatoes, so price decides",
+                "2 prices compete",
+                "Stale but still competing: $20.00/case ($0.80/lb) from Sysco is 100 days old, past the 30-day limit",
+                "Lowest unit price: $20.00/case ($0.80/lb) from Sysco",
+                "Cheapest: $20.00/case ($0.80/lb) from Sysco",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_tie_goes_to_the_preferred_distributor_or_the_lowest_price() {
+        let pool = seeded_pool().await;
+        settings::save_tie_tolerance(&pool, "r1", "percent", 2.0).await.unwrap();
+        let sysco_date = add_price(&pool, "p1", "d1", 20.00, 1).await;
+        add_price(&pool, "p1", "d2", 20.25, 0).await;
+        let tied = "Tied within 2% of $0.80/lb: $20.25/case ($0.81/lb) from US Foods; $20.00/case ($0.80/lb) from Sysco";
+
+        assert_eq!(
+            steps(&pool, "p1").await,
+            vec![
+                "No hard preference for Roma Tomatoes, so price decides".to_string(),
+                "2 prices compete".to_string(),
+                tied.to_string(),
+                format!(
+                    "No preferred distributor among the tied prices; the lowest wins: $20.00/case ($0.80/lb) from Sysco, effective {}",
+                    sysco_date
+                ),
+            ]
+        );
+
+        preferences::save_preference(&pool, "r1", "p1", Some("d2"), false, None).await.unwrap();
+        let explanation = explain_winner(&pool, "r1", "p1").await.unwrap();
+        assert_eq!(explanation.rule, Some(WinnerRule::TieBreak));
+        assert_eq!(
+            explanation.steps[2..],
+            [tied, "Tie broken by preference: US Foods is the preferred distributor for Roma Tomatoes"]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_product_without_prices_has_no_winner() {
+        let pool = seeded_pool().await;
+
+        let explanation = explain_winner(&pool, "r1", "p1").await.unwrap();
+        assert_eq!(explanation.rule, None);
+        assert!(explanation.winner.is_none());
+        assert_eq!(
+            explanation.steps,
+            vec!["No hard preference for Roma Tomatoes, so price decides", "No current prices for Roma Tomatoes"]
+        );
+        assert!(explain_winner(&pool, "r1", "p9").await.is_err());
+    }
+}
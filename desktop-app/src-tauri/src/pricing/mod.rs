@@ -0,0 +1,677 @@
+use crate::db::{
+    models::{ExclusionReason, LocalCurrentPrice, PriceWithDetails, SubstitutionWinner, WinnerRule, WinnerSettings},
+    DbPool,
+};
+use crate::error::AppError;
+use chrono::{Local, NaiveDate};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+pub mod explain;
+pub mod preferences;
+pub mod settings;
+pub mod substitutions;
+
+// Unit prices closer than this are the same price
+const TIE_EPSILON: f64 = 1e-9;
+
+// How far above the lowest unit price another price can be and still tie with it
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TieTolerance {
+    #[default]
+    Exact,
+    Percent(f64), // Of the lowest unit price
+    PerUnit(f64), // Dollars per preferred unit
+}
+
+impl TieTolerance {
+    pub fn from_settings(settings: &WinnerSettings) -> Self {
+        match (settings.tie_tolerance_type.as_str(), settings.tie_tolerance_value) {
+            (_, value) if value <= 0.0 => TieTolerance::Exact,
+            ("amount", value) => TieTolerance::PerUnit(value),
+            (_, value) => TieTolerance::Percent(value),
+        }
+    }
+
+    fn ties(self, lowest: f64, unit_price: f64) -> bool {
+        let gap = unit_price - lowest;
+        match self {
+            TieTolerance::Exact => gap < TIE_EPSILON,
+            TieTolerance::Percent(percent) => gap <= lowest * percent / 100.0 + TIE_EPSILON,
+            TieTolerance::PerUnit(amount) => gap <= amount + TIE_EPSILON,
+        }
+    }
+
+    // "within 2%", "within $0.01/lb", or "exact"
+    pub fn describe(self, preferred_measurement: &str) -> String {
+        match self {
+            TieTolerance::Exact => "exact".to_string(),
+            TieTolerance::Percent(percent) => format!("within {}%", percent),
+            // A tolerance in whole cents reads as cents, not to the 4 places of a small unit price
+            TieTolerance::PerUnit(amount) if ((amount * 100.0).round() - amount * 100.0).abs() < TIE_EPSILON => {
+                format!("within ${:.2}/{}", amount, preferred_measurement)
+            }
+            TieTolerance::PerUnit(amount) => format!("within {}/{}", money(amount), preferred_measurement),
+        }
+    }
+}
+
+// A product's distributor preference: a tie-breaker, or with always_use_preferred the only choice
+#[derive(Debug, Clone)]
+pub struct Preference {
+    pub preferred_distributor_id: String,
+    pub always_use_preferred: bool,
+}
+
+// Everything the winner calculation looks at for one restaurant
+#[derive(Debug, Clone, Default)]
+pub struct WinnerInputs {
+    pub prices: Vec<LocalCurrentPrice>, // Latest price per product and distributor
+    pub preferences: HashMap<String, Preference>, // By catalog_product_id
+    pub groups: HashMap<String, Vec<String>>, // Substitution group_id to its member products
+    pub excluded: Vec<ExcludedPrice>, // Latest prices that can't compete
+    pub tie_tolerance: TieTolerance,
+    pub stale: HashMap<String, i32>, // price_id of stale prices that still compete, to the max age they passed
+    pub as_of: NaiveDate, // The day price ages are counted to
+}
+
+impl WinnerInputs {
+    pub fn age_days(&self, price: &LocalCurrentPrice) -> i64 {
+        (self.as_of - price.effective_date).num_days()
+    }
+}
+
+// A latest price left out of the calculation
+#[derive(Debug, Clone)]
+pub struct ExcludedPrice {
+    pub price: LocalCurrentPrice,
+    pub reason: ExclusionReason,
+    pub max_age_days: Option<i32>, // The limit a stale price passed
+}
+
+// The price chosen for one product
+#[derive(Debug, Clone)]
+pub struct Winner {
+    pub catalog_product_id: String,
+    pub price: LocalCurrentPrice, // May be another product's price when a substitution group won
+    pub rule: WinnerRule,
+    pub group_id: Option<String>,
+}
+
+// How one product's winner was decided; explain_winner reports it step by step
+#[derive(Debug, Clone)]
+pub struct Decision<'a> {
+    pub group_id: Option<&'a str>,
+    pub hard_preference: Option<&'a Preference>, // always_use_preferred, whether or not it decided
+    pub candidates: Vec<&'a LocalCurrentPrice>, // The prices that competed
+    pub tied: Vec<&'a LocalCurrentPrice>, // Within the tie tolerance of the lowest unit_price, in tie-break order
+    pub winner: Option<(&'a LocalCurrentPrice, WinnerRule)>,
+}
+
+// The inputs' prices by product, and the group each product is in
+pub struct PriceIndex<'a> {
+    by_product: HashMap<&'a str, Vec<&'a LocalCurrentPrice>>,
+    group_of: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> PriceIndex<'a> {
+    pub fn new(inputs: &'a WinnerInputs) -> Self {
+        let mut by_product: HashMap<&str, Vec<&LocalCurrentPrice>> = HashMap::new();
+        for price in &inputs.prices {
+            by_product.entry(price.catalog_product_id.as_str()).or_default().push(price);
+        }
+
+        let mut group_of: HashMap<&str, &str> = HashMap::new();
+        for (group_id, members) in &inputs.groups {
+            for member in members {
+                group_of.insert(member.as_str(), group_id.as_str());
+            }
+        }
+
+        PriceIndex { by_product, group_of }
+    }
+
+    // Products with prices of their own or in a group, which can win through the group
+    fn products(&self) -> BTreeSet<&'a str> {
+        self.by_product.keys().copied().chain(self.group_of.keys().copied()).collect()
+    }
+}
+
+// Pick a winning price for every product that has one to choose from
+pub fn choose_winners(inputs: &WinnerInputs) -> Vec<Winner> {
+    let index = PriceIndex::new(inputs);
+
+    index
+        .products()
+        .into_iter()
+        .filter_map(|product_id| {
+            let decision = decide(inputs, &index, product_id);
+            let (price, rule) = decision.winner?;
+            Some(Winner {
+                catalog_product_id: product_id.to_string(),
+                price: price.clone(),
+                rule,
+                group_id: decision.group_id.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+// Follow BUSINESS_LOGIC.md for one product: a hard preference decides outright; otherwise
+// the lowest unit_price wins, across the whole substitution group when the product is in
+// one, and a tie goes to the preferred distributor.
+pub fn decide<'a>(inputs: &'a WinnerInputs, index: &PriceIndex<'a>, product_id: &str) -> Decision<'a> {
+    let own_prices = index.by_product.get(product_id).cloned().unwrap_or_default();
+    let group_id = index.group_of.get(product_id).copied();
+    let hard_preference = inputs.preferences.get(product_id).filter(|p| p.always_use_preferred);
+
+    // 1. Hard preference, when that distributor has quoted the product
+    if let Some(preference) = hard_preference {
+        if let Some(price) = own_prices.iter().find(|p| p.distributor_id == preference.preferred_distributor_id) {
+            return Decision {
+                group_id,
+                hard_preference,
+                winner: Some((*price, WinnerRule::HardPreference)),
+                candidates: own_prices.clone(),
+                tied: Vec::new(),
+            };
+        }
+    }
+
+    // 2. Substitution group: every member product's prices compete
+    let candidates: Vec<&LocalCurrentPrice> = match group_id {
+        Some(group_id) => inputs.groups[group_id]
+            .iter()
+            .filter_map(|member| index.by_product.get(member.as_str()))
+            .flatten()
+            .copied()
+            .collect(),
+        // 3. Standard single product
+        None => own_prices,
+    };
+
+    let tied = tied_for_lowest(&candidates, inputs.tie_tolerance);
+    let winner = break_tie(&tied, &inputs.preferences, group_id.is_some());
+
+    Decision {
+        group_id,
+        hard_preference,
+        candidates,
+        tied,
+        winner,
+    }
+}
+
+// The prices tied with the lowest unit_price, most recent quote first
+fn tied_for_lowest<'a>(candidates: &[&'a LocalCurrentPrice], tolerance: TieTolerance) -> Vec<&'a LocalCurrentPrice> {
+    let Some(lowest) = candidates.iter().map(|price| price.unit_price).min_by(|a, b| a.total_cmp(b)) else {
+        return Vec::new();
+    };
+
+    let mut tied: Vec<&LocalCurrentPrice> = candidates
+        .iter()
+        .copied()
+        .filter(|price| tolerance.ties(lowest, price.unit_price))
+        .collect();
+    tied.sort_by(|a, b| {
+        b.effective_date
+            .cmp(&a.effective_date)
+            .then_with(|| a.distributor_id.cmp(&b.distributor_id))
+            .then_with(|| a.catalog_product_id.cmp(&b.catalog_product_id))
+    });
+    tied
+}
+
+// A tie goes to a price from its product's preferred distributor, and failing that to the
+// lowest price, the most recent quote if several are equal
+fn break_tie<'a>(
+    tied: &[&'a LocalCurrentPrice],
+    preferences: &HashMap<String, Preference>,
+    in_group: bool,
+) -> Option<(&'a LocalCurrentPrice, WinnerRule)> {
+    let first = *tied.first()?;
+    let outright = if in_group { WinnerRule::Substitution } else { WinnerRule::Cheapest };
+    if tied.len() == 1 {
+        return Some((first, outright));
+    }
+
+    if let Some(price) = tied.iter().find(|price| is_preferred(price, preferences)) {
+        return Some((price, WinnerRule::TieBreak));
+    }
+
+    let lowest = tied.iter().map(|price| price.unit_price).fold(f64::INFINITY, f64::min);
+    tied.iter()
+        .find(|price| price.unit_price - lowest < TIE_EPSILON)
+        .map(|price| (*price, outright))
+}
+
+// Whether a price is from its product's preferred distributor
+pub fn is_preferred(price: &LocalCurrentPrice, preferences: &HashMap<String, Preference>) -> bool {
+    preferences
+        .get(&price.catalog_product_id)
+        .is_some_and(|preference| preference.preferred_distributor_id == price.distributor_id)
+}
+
+// The latest price of every product from every distributor, with what the winner
+// calculation needs to compare them. A price whose distributor spec is gone can't be
+// trusted to be in preferred units, and one with no units per case has no unit price at
+// all, so both are excluded. A price past its category's max age is excluded or flagged stale.
+pub async fn load_inputs(pool: &DbPool, restaurant_id: &str) -> Result<WinnerInputs, AppError> {
+    // unit_price is NULL without units per case, so it reads as 0 for those
+    let latest = sqlx::query_as::<_, LocalCurrentPrice>(
+        r#"SELECT p.price_id, p.restaurant_id, p.catalog_product_id, p.distributor_id, p.case_price,
+               p.total_preferred_units, COALESCE(p.unit_price, 0.0) AS unit_price, p.effective_date, p.source_type,
+               p.source_file_name, p.source_file_hash, p.created_at, p.pricing_mode
+           FROM local_current_prices p
+           WHERE p.restaurant_id = ?
+             AND p.effective_date = (
+                 SELECT MAX(l.effective_date) FROM local_current_prices l
+                 WHERE l.restaurant_id = p.restaurant_id
+                   AND l.catalog_product_id = p.catalog_product_id
+                   AND l.distributor_id = p.distributor_id
+             )"#
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    let specs: HashSet<(String, String)> =
+        sqlx::query_as::<_, (String, String)>("SELECT catalog_product_id, distributor_id FROM distributor_specs")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    let categories: HashMap<String, Option<String>> =
+        sqlx::query_as::<_, (String, Option<String>)>("SELECT catalog_product_id, category_id FROM products")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+    let age_limits = settings::age_limits_by_category(pool, restaurant_id).await?;
+    let settings = settings::get_settings(pool, restaurant_id).await?;
+
+    let mut inputs = WinnerInputs {
+        preferences: preferences::preferences_for_winner(pool, restaurant_id).await?,
+        groups: substitutions::groups_for_winner(pool, restaurant_id).await?,
+        tie_tolerance: TieTolerance::from_settings(&settings),
+        as_of: Local::now().date_naive(),
+        ..Default::default()
+    };
+
+    for price in latest {
+        if !specs.contains(&(price.catalog_product_id.clone(), price.distributor_id.clone())) {
+            inputs.excluded.push(ExcludedPrice { price, reason: ExclusionReason::MissingSpec, max_age_days: None });
+            continue;
+        }
+        if price.total_preferred_units <= 0.0 {
+            inputs.excluded.push(ExcludedPrice { price, reason: ExclusionReason::NoUsablePackSize, max_age_days: None });
+            continue;
+        }
+
+        let age_limit = categories
+            .get(&price.catalog_product_id)
+            .cloned()
+            .flatten()
+            .and_then(|category_id| age_limits.get(&category_id))
+            .or_else(|| age_limits.get("default"))
+            .filter(|limit| inputs.age_days(&price) > limit.max_age_days as i64);
+        match age_limit {
+            Some(limit) if limit.stale_action == "exclude" => inputs.excluded.push(ExcludedPrice {
+                price,
+                reason: ExclusionReason::Stale,
+                max_age_days: Some(limit.max_age_days),
+            }),
+            Some(limit) => {
+                inputs.stale.insert(price.price_id.clone(), limit.max_age_days);
+                inputs.prices.push(price);
+            }
+            None => inputs.prices.push(price),
+        }
+    }
+
+    Ok(inputs)
+}
+
+// Every current price for a restaurant, marking the ones the winner calculation chose and
+// the ones it couldn't use. An import still running has its written batches counted already.
+pub async fn winning_prices(pool: &DbPool, restaurant_id: &str) -> Result<Vec<PriceWithDetails>, AppError> {
+    let inputs = load_inputs(pool, restaurant_id).await?;
+    let winners = choose_winners(&inputs);
+
+    let mut won_by: HashMap<&str, WinnerRule> = HashMap::new();
+    for winner in &winners {
+        won_by.entry(winner.price.price_id.as_str()).or_insert(winner.rule);
+    }
+    let group_winners: HashMap<&str, &Winner> = winners
+        .iter()
+        .filter(|winner| winner.group_id.is_some())
+        .map(|winner| (winner.catalog_product_id.as_str(), winner))
+        .collect();
+
+    let names = Names::load(pool, restaurant_id).await?;
+
+    let competing = inputs.prices.iter().map(|price| (price, None));
+    let excluded = inputs.excluded.iter().map(|excluded| (&excluded.price, Some(excluded.reason)));
+
+    let mut prices: Vec<PriceWithDetails> = competing
+        .chain(excluded)
+        .map(|(price, excluded_reason)| {
+            let preferred_measurement = names.measurement(&price.catalog_product_id);
+            let winner_rule = won_by.get(price.price_id.as_str()).copied();
+            let substitution = group_winners.get(price.catalog_product_id.as_str()).and_then(|winner| {
+                let group_id = winner.group_id.clone()?;
+                Some(SubstitutionWinner {
+                    group_name: names.group(&group_id),
+                    group_id,
+                    winning_product_id: winner.price.catalog_product_id.clone(),
+                    winning_product_name: names.product(&winner.price.catalog_product_id),
+                    winning_distributor_name: names.distributor(&winner.price.distributor_id),
+                })
+            });
+
+            PriceWithDetails {
+                price_label: price_label(price, &preferred_measurement),
+                price: price.clone(),
+                product_name: names.product(&price.catalog_product_id),
+                distributor_name: names.distributor(&price.distributor_id),
+                is_winner: winner_rule.is_some(),
+                tie_tolerance: (winner_rule == Some(WinnerRule::TieBreak))
+                    .then(|| inputs.tie_tolerance.describe(&preferred_measurement)),
+                winner_rule,
+                preferred_measurement,
+                substitution,
+                age_days: inputs.age_days(price),
+                is_stale: inputs.stale.contains_key(&price.price_id)
+                    || excluded_reason == Some(ExclusionReason::Stale),
+                excluded_reason,
+            }
+        })
+        .collect();
+
+    prices.sort_by(|a, b| {
+        a.product_name
+            .cmp(&b.product_name)
+            .then_with(|| b.is_winner.cmp(&a.is_winner))
+            .then_with(|| a.excluded_reason.is_some().cmp(&b.excluded_reason.is_some()))
+            .then_with(|| a.price.unit_price.partial_cmp(&b.price.unit_price).unwrap_or(Ordering::Equal))
+    });
+
+    Ok(prices)
+}
+
+// Display names for a restaurant's prices, falling back to ids for anything not synced
+pub struct Names {
+    products: HashMap<String, (String, String)>, // Name and preferred_measurement
+    distributors: HashMap<String, String>,
+    groups: HashMap<String, String>,
+}
+
+impl Names {
+    pub async fn load(pool: &DbPool, restaurant_id: &str) -> Result<Self, AppError> {
+        let products = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT catalog_product_id, product_name, preferred_measurement FROM products"
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|(product_id, name, measurement)| (product_id, (name, measurement)))
+        .collect();
+        let distributors = sqlx::query_as::<_, (String, String)>("SELECT distributor_id, distributor_name FROM distributors")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+        let groups = sqlx::query_as::<_, (String, String)>(
+            "SELECT group_id, group_name FROM substitution_groups WHERE restaurant_id = ?"
+        )
+        .bind(restaurant_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        Ok(Names { products, distributors, groups })
+    }
+
+    pub fn product(&self, product_id: &str) -> String {
+        self.products
+            .get(product_id)
+            .map(|(name, _)| name.clone())
+            .unwrap_or_else(|| product_id.to_string())
+    }
+
+    pub fn measurement(&self, product_id: &str) -> String {
+        self.products
+            .get(product_id)
+            .map(|(_, measurement)| measurement.clone())
+            .unwrap_or_else(|| "unit".to_string())
+    }
+
+    pub fn distributor(&self, distributor_id: &str) -> String {
+        self.distributors
+            .get(distributor_id)
+            .cloned()
+            .unwrap_or_else(|| distributor_id.to_string())
+    }
+
+    pub fn group(&self, group_id: &str) -> String {
+        self.groups.get(group_id).cloned().unwrap_or_else(|| group_id.to_string())
+    }
+}
+
+// Case and unit pricing together: "$22.00/case ($0.85/lb)". Catch-weight items are quoted
+// per unit, so that leads: "$4.25/lb (catch weight, ~40 lb case)".
+pub fn price_label(price: &LocalCurrentPrice, preferred_measurement: &str) -> String {
+    if price.total_preferred_units <= 0.0 {
+        return format!("{}/case (no {} per case)", money(price.case_price), preferred_measurement);
+    }
+    if price.pricing_mode == "catch_weight" {
+        return format!(
+            "{}/{} (catch weight, ~{} {} case)",
+            money(price.unit_price),
+            preferred_measurement,
+            price.total_preferred_units,
+            preferred_measurement
+        );
+    }
+
+    format!(
+        "{}/case ({}/{})",
+        money(price.case_price),
+        money(price.unit_price),
+        preferred_measurement
+    )
+}
+
+// Cents, or four places for fractions of a cent per unit ($0.0125/oz)
+pub fn money(amount: f64) -> String {
+    if amount.abs() < 0.10 && amount != 0.0 {
+        format!("${:.4}", amount)
+    } else {
+        format!("${:.2}", amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(
+        price_id: &str,
+        product_id: &str,
+        distributor_id: &str,
+        case_price: f64,
+        units: f64,
+        day: u32,
+    ) -> LocalCurrentPrice {
+        LocalCurrentPrice {
+            price_id: price_id.to_string(),
+            restaurant_id: "r1".to_string(),
+            catalog_product_id: product_id.to_string(),
+            distributor_id: distributor_id.to_string(),
+            case_price,
+            total_preferred_units: units,
+            unit_price: case_price / units,
+            effective_date: NaiveDate::from_ymd_opt(2026, 10, day).unwrap(),
+            source_type: "csv_import".to_string(),
+            source_file_name: None,
+            source_file_hash: None,
+            created_at: None,
+            pricing_mode: "case".to_string(),
+        }
+    }
+
+    fn winner(inputs: &WinnerInputs, product_id: &str) -> Option<(String, WinnerRule)> {
+        choose_winners(inputs)
+            .into_iter()
+            .find(|winner| winner.catalog_product_id == product_id)
+            .map(|winner| (winner.price.price_id, winner.rule))
+    }
+
+    fn won(price_id: &str, rule: WinnerRule) -> Option<(String, WinnerRule)> {
+        Some((price_id.to_string(), rule))
+    }
+
+    fn prefer(inputs: &mut WinnerInputs, product_id: &str, distributor_id: &str, always_use_preferred: bool) {
+        inputs.preferences.insert(
+            product_id.to_string(),
+            Preference { preferred_distributor_id: distributor_id.to_string(), always_use_preferred },
+        );
+    }
+
+    #[test]
+    fn lowest_unit_price_wins_whatever_the_case_price() {
+        // $22.00 for 25 lb beats $18.50 for 20 lb
+        let inputs = WinnerInputs {
+            prices: vec![price("sysco", "p1", "d1", 22.00, 25.0, 1), price("usf", "p1", "d2", 18.50, 20.0, 1)],
+            ..Default::default()
+        };
+        assert_eq!(winner(&inputs, "p1"), won("sysco", WinnerRule::Cheapest));
+    }
+
+    #[test]
+    fn a_hard_preference_wins_when_its_distributor_has_a_price() {
+        let mut inputs = WinnerInputs {
+            prices: vec![price("sysco", "p1", "d1", 25.00, 25.0, 1), price("usf", "p1", "d2", 20.00, 25.0, 1)],
+            ..Default::default()
+        };
+        prefer(&mut inputs, "p1", "d1", true);
+        assert_eq!(winner(&inputs, "p1"), won("sysco", WinnerRule::HardPreference));
+
+        prefer(&mut inputs, "p1", "d3", true);
+        assert_eq!(winner(&inputs, "p1"), won("usf", WinnerRule::Cheapest));
+    }
+
+    #[test]
+    fn an_exact_tie_goes_to_the_preferred_distributor_then_the_latest_quote() {
+        let mut inputs = WinnerInputs {
+            prices: vec![price("sysco", "p1", "d1", 20.00, 25.0, 1), price("usf", "p1", "d2", 20.00, 25.0, 2)],
+            ..Default::default()
+        };
+        assert_eq!(winner(&inputs, "p1"), won("usf", WinnerRule::Cheapest));
+
+        prefer(&mut inputs, "p1", "d1", false);
+        assert_eq!(winner(&inputs, "p1"), won("sysco", WinnerRule::TieBreak));
+    }
+
+    #[test]
+    fn a_substitution_group_competes_across_its_products() {
+        let mut inputs = WinnerInputs {
+            prices: vec![price("roma", "p1", "d1", 20.00, 25.0, 1), price("vine", "p2", "d2", 18.00, 25.0, 1)],
+            ..Default::default()
+        };
+        inputs.groups.insert("g1".to_string(), vec!["p1".to_string(), "p2".to_string(), "p3".to_string()]);
+
+        assert_eq!(winner(&inputs, "p1"), won("vine", WinnerRule::Substitution));
+        assert_eq!(winner(&inputs, "p3"), won("vine", WinnerRule::Substitution));
+    }
+
+    #[test]
+    fn only_prices_at_the_lowest_unit_price_tie_without_a_tolerance() {
+        let prices = [
+            price("a", "p1", "d1", 20.00, 25.0, 1),
+            price("b", "p1", "d2", 20.00, 25.0, 3),
+            price("c", "p1", "d3", 20.01, 25.0, 2),
+        ];
+        let candidates: Vec<&LocalCurrentPrice> = prices.iter().collect();
+
+        let tied: Vec<&str> = tied_for_lowest(&candidates, TieTolerance::Exact)
+            .iter()
+            .map(|price| price.price_id.as_str())
+            .collect();
+        assert_eq!(tied, vec!["b", "a"]);
+        assert!(tied_for_lowest(&[], TieTolerance::Exact).is_empty());
+    }
+
+    fn tied(prices: &[LocalCurrentPrice], tolerance: TieTolerance) -> Vec<String> {
+        let candidates: Vec<&LocalCurrentPrice> = prices.iter().collect();
+        tied_for_lowest(&candidates, tolerance).iter().map(|price| price.price_id.clone()).collect()
+    }
+
+    #[test]
+    fn a_percent_tolerance_ties_prices_within_that_share_of_the_lowest() {
+        // $1.00/lb against $1.02/lb (2% above) and $1.0201/lb (just over)
+        let prices = [
+            price("low", "p1", "d1", 100.00, 100.0, 1),
+            price("within", "p1", "d2", 102.00, 100.0, 2),
+            price("over", "p1", "d3", 102.01, 100.0, 3),
+        ];
+        assert_eq!(tied(&prices, TieTolerance::Percent(2.0)), vec!["within", "low"]);
+        assert_eq!(tied(&prices, TieTolerance::Percent(1.0)), vec!["low"]);
+    }
+
+    #[test]
+    fn a_per_unit_tolerance_ties_prices_within_that_amount_of_the_lowest() {
+        // $0.80/lb against $0.81/lb (a cent above) and $0.8104/lb (just over)
+        let prices = [
+            price("low", "p1", "d1", 20.00, 25.0, 1),
+            price("within", "p1", "d2", 20.25, 25.0, 2),
+            price("over", "p1", "d3", 20.26, 25.0, 3),
+        ];
+        assert_eq!(tied(&prices, TieTolerance::PerUnit(0.01)), vec!["within", "low"]);
+        assert_eq!(tied(&prices, TieTolerance::Exact), vec!["low"]);
+    }
+
+    #[test]
+    fn a_tolerance_lets_the_preferred_distributor_win_a_near_tie() {
+        let mut inputs = WinnerInputs {
+            prices: vec![price("sysco", "p1", "d1", 100.00, 100.0, 1), price("usf", "p1", "d2", 101.50, 100.0, 1)],
+            tie_tolerance: TieTolerance::Percent(2.0),
+            ..Default::default()
+        };
+        assert_eq!(winner(&inputs, "p1"), won("sysco", WinnerRule::Cheapest));
+
+        prefer(&mut inputs, "p1", "d2", false);
+        assert_eq!(winner(&inputs, "p1"), won("usf", WinnerRule::TieBreak));
+
+        inputs.tie_tolerance = TieTolerance::PerUnit(0.01);
+        assert_eq!(winner(&inputs, "p1"), won("sysco", WinnerRule::Cheapest));
+    }
+
+    #[test]
+    fn reads_and_describes_tolerance_settings() {
+        let settings = |tie_tolerance_type: &str, tie_tolerance_value: f64| WinnerSettings {
+            restaurant_id: "r1".to_string(),
+            tie_tolerance_type: tie_tolerance_type.to_string(),
+            tie_tolerance_value,
+            updated_at: None,
+        };
+        assert_eq!(TieTolerance::from_settings(&settings("percent", 2.0)), TieTolerance::Percent(2.0));
+        assert_eq!(TieTolerance::from_settings(&settings("amount", 0.01)), TieTolerance::PerUnit(0.01));
+        assert_eq!(TieTolerance::from_settings(&settings("amount", 0.0)), TieTolerance::Exact);
+
+        assert_eq!(TieTolerance::Percent(2.0).describe("lb"), "within 2%");
+        assert_eq!(TieTolerance::PerUnit(0.01).describe("lb"), "within $0.01/lb");
+        assert_eq!(TieTolerance::PerUnit(0.29).describe("lb"), "within $0.29/lb");
+        assert_eq!(TieTolerance::PerUnit(0.005).describe("oz"), "within $0.0050/oz");
+        assert_eq!(TieTolerance::Exact.describe("lb"), "exact");
+    }
+
+    #[test]
+    fn labels_case_and_unit_prices() {
+        assert_eq!(price_label(&price("a", "p1", "d1", 22.00, 25.0, 1), "lb"), "$22.00/case ($0.88/lb)");
+        assert_eq!(price_label(&price("a", "p1", "d1", 12.00, 960.0, 1), "oz"), "$12.00/case ($0.0125/oz)");
+        assert_eq!(money(0.0), "$0.00");
+    }
+}
@@ -0,0 +1,225 @@
+use crate::db::{models::ProductPreference, DbPool};
+use crate::error::AppError;
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqliteConnection;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use super::Preference;
+
+// Preference rows plus whether an edit of them is still waiting in the upload queue, and
+// why its last upload failed
+const PREFERENCE_QUERY: &str = r#"SELECT p.*, EXISTS (
+        SELECT 1 FROM pending_sync_events e
+        WHERE e.event_type = 'preference_update'
+          AND e.restaurant_id = p.restaurant_id
+          AND json_extract(e.payload, '$.catalog_product_id') = p.catalog_product_id
+    ) AS pending_upload, (
+        SELECT e.last_error FROM pending_sync_events e
+        WHERE e.event_type = 'preference_update'
+          AND e.restaurant_id = p.restaurant_id
+          AND json_extract(e.payload, '$.catalog_product_id') = p.catalog_product_id
+    ) AS upload_error
+    FROM product_preferences p"#;
+
+pub async fn list_preferences(pool: &DbPool, restaurant_id: &str) -> Result<Vec<ProductPreference>, AppError> {
+    let preferences = sqlx::query_as::<_, ProductPreference>(&format!(
+        "{} WHERE p.restaurant_id = ? ORDER BY p.catalog_product_id",
+        PREFERENCE_QUERY
+    ))
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(preferences)
+}
+
+async fn get_preference(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    catalog_product_id: &str,
+) -> Result<Option<ProductPreference>, AppError> {
+    let preference = sqlx::query_as::<_, ProductPreference>(&format!(
+        "{} WHERE p.restaurant_id = ? AND p.catalog_product_id = ?",
+        PREFERENCE_QUERY
+    ))
+    .bind(restaurant_id)
+    .bind(catalog_product_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(preference)
+}
+
+// The preferences the winner calculation applies, by product
+pub async fn preferences_for_winner(pool: &DbPool, restaurant_id: &str) -> Result<HashMap<String, Preference>, AppError> {
+    let rows = sqlx::query_as::<_, (String, String, bool)>(
+        r#"SELECT catalog_product_id, preferred_distributor_id, always_use_preferred FROM product_preferences
+           WHERE restaurant_id = ? AND preferred_distributor_id IS NOT NULL"#
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(product_id, preferred_distributor_id, always_use_preferred)| {
+            (product_id, Preference { preferred_distributor_id, always_use_preferred })
+        })
+        .collect())
+}
+
+// Set a product's preferred distributor and queue the change for upload
+pub async fn save_preference(
+    pool: &DbPool,
+    restaurant_id: &str,
+    catalog_product_id: &str,
+    preferred_distributor_id: Option<&str>,
+    always_use_preferred: bool,
+    notes: Option<&str>,
+) -> Result<ProductPreference, AppError> {
+    if always_use_preferred && preferred_distributor_id.is_none() {
+        return Err(AppError::Validation(
+            "Choose a preferred distributor to always use".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let product_exists = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM products WHERE catalog_product_id = ?")
+        .bind(catalog_product_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if product_exists == 0 {
+        return Err(AppError::NotFound(format!("Product {}", catalog_product_id)));
+    }
+    if let Some(distributor_id) = preferred_distributor_id {
+        let distributor_exists =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM distributors WHERE distributor_id = ?")
+                .bind(distributor_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        if distributor_exists == 0 {
+            return Err(AppError::NotFound(format!("Distributor {}", distributor_id)));
+        }
+    }
+
+    let notes = notes.map(str::trim).filter(|notes| !notes.is_empty());
+    sqlx::query(
+        r#"INSERT INTO product_preferences (restaurant_id, catalog_product_id, preferred_distributor_id,
+           always_use_preferred, notes, updated_at)
+           VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+           ON CONFLICT(restaurant_id, catalog_product_id) DO UPDATE SET
+               preferred_distributor_id = excluded.preferred_distributor_id,
+               always_use_preferred = excluded.always_use_preferred,
+               notes = excluded.notes,
+               updated_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(restaurant_id)
+    .bind(catalog_product_id)
+    .bind(preferred_distributor_id)
+    .bind(always_use_preferred)
+    .bind(notes)
+    .execute(&mut *tx)
+    .await?;
+
+    queue_preference_upload(
+        &mut tx,
+        restaurant_id,
+        catalog_product_id,
+        json!({
+            "action": "upsert",
+            "restaurant_id": restaurant_id,
+            "catalog_product_id": catalog_product_id,
+            "preferred_distributor_id": preferred_distributor_id,
+            "always_use_preferred": always_use_preferred,
+            "notes": notes,
+            "updated_at": Utc::now(),
+        }),
+    )
+    .await?;
+
+    let preference = get_preference(&mut tx, restaurant_id, catalog_product_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Preference for product {}", catalog_product_id)))?;
+    tx.commit().await?;
+
+    Ok(preference)
+}
+
+// Remove a product's preference, letting price alone decide, and queue the removal for upload
+pub async fn delete_preference(pool: &DbPool, restaurant_id: &str, catalog_product_id: &str) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    let deleted = sqlx::query("DELETE FROM product_preferences WHERE restaurant_id = ? AND catalog_product_id = ?")
+        .bind(restaurant_id)
+        .bind(catalog_product_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Preference for product {}", catalog_product_id)));
+    }
+
+    queue_preference_upload(
+        &mut tx,
+        restaurant_id,
+        catalog_product_id,
+        json!({
+            "action": "delete",
+            "restaurant_id": restaurant_id,
+            "catalog_product_id": catalog_product_id,
+            "updated_at": Utc::now(),
+        }),
+    )
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// Queue a preference edit, replacing an earlier one for the same product that hasn't gone
+// up yet. Preferences aren't price data, so they aren't held for the Monday upload.
+async fn queue_preference_upload(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    catalog_product_id: &str,
+    payload: serde_json::Value,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"DELETE FROM pending_sync_events
+           WHERE event_type = 'preference_update' AND restaurant_id = ?
+             AND json_extract(payload, '$.catalog_product_id') = ?"#
+    )
+    .bind(restaurant_id)
+    .bind(catalog_product_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"INSERT INTO pending_sync_events (event_id, event_type, restaurant_id, payload, scheduled_for)
+           VALUES (?, 'preference_update', ?, ?, ?)"#
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(restaurant_id)
+    .bind(payload.to_string())
+    .bind(Utc::now())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// (restaurant_id, catalog_product_id) of preferences edited here and not uploaded yet;
+// a sync leaves these alone so it doesn't undo the edit
+pub async fn pending_preference_keys(conn: &mut SqliteConnection) -> Result<HashSet<(String, String)>, AppError> {
+    let keys = sqlx::query_as::<_, (String, String)>(
+        r#"SELECT restaurant_id, json_extract(payload, '$.catalog_product_id') FROM pending_sync_events
+           WHERE event_type = 'preference_update'"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(keys.into_iter().collect())
+}
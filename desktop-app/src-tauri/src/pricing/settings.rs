@@ -0,0 +1,145 @@
+use crate::db::{
+    models::{PriceAgeLimit, WinnerSettings},
+    DbPool,
+};
+use crate::error::AppError;
+use std::collections::HashMap;
+
+// A restaurant's tie tolerance; without saved settings only equal prices tie
+pub async fn get_settings(pool: &DbPool, restaurant_id: &str) -> Result<WinnerSettings, AppError> {
+    let settings = sqlx::query_as::<_, WinnerSettings>("SELECT * FROM winner_settings WHERE restaurant_id = ?")
+        .bind(restaurant_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(settings.unwrap_or_else(|| WinnerSettings {
+        restaurant_id: restaurant_id.to_string(),
+        tie_tolerance_type: "percent".to_string(),
+        tie_tolerance_value: 0.0,
+        updated_at: None,
+    }))
+}
+
+// Set how close two unit prices must be to tie: a percentage of the lower one, or an
+// amount per preferred unit
+pub async fn save_tie_tolerance(
+    pool: &DbPool,
+    restaurant_id: &str,
+    tie_tolerance_type: &str,
+    tie_tolerance_value: f64,
+) -> Result<WinnerSettings, AppError> {
+    if !matches!(tie_tolerance_type, "percent" | "amount") {
+        return Err(AppError::Validation(format!(
+            "Unknown tie tolerance type '{}'; use 'percent' or 'amount'",
+            tie_tolerance_type
+        )));
+    }
+    if !tie_tolerance_value.is_finite() || tie_tolerance_value < 0.0 {
+        return Err(AppError::Validation("The tie tolerance can't be negative".to_string()));
+    }
+    if tie_tolerance_type == "percent" && tie_tolerance_value >= 100.0 {
+        return Err(AppError::Validation("The tie tolerance must be under 100%".to_string()));
+    }
+
+    sqlx::query(
+        r#"INSERT INTO winner_settings (restaurant_id, tie_tolerance_type, tie_tolerance_value)
+           VALUES (?, ?, ?)
+           ON CONFLICT(restaurant_id) DO UPDATE SET
+               tie_tolerance_type = excluded.tie_tolerance_type,
+               tie_tolerance_value = excluded.tie_tolerance_value,
+               updated_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(restaurant_id)
+    .bind(tie_tolerance_type)
+    .bind(tie_tolerance_value)
+    .execute(pool)
+    .await?;
+
+    get_settings(pool, restaurant_id).await
+}
+
+pub async fn list_age_limits(pool: &DbPool, restaurant_id: &str) -> Result<Vec<PriceAgeLimit>, AppError> {
+    let limits = sqlx::query_as::<_, PriceAgeLimit>(
+        "SELECT * FROM price_age_limits WHERE restaurant_id = ? ORDER BY category_id = 'default' DESC, category_id"
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(limits)
+}
+
+// A restaurant's age limits by category, for the winner calculation
+pub async fn age_limits_by_category(
+    pool: &DbPool,
+    restaurant_id: &str,
+) -> Result<HashMap<String, PriceAgeLimit>, AppError> {
+    Ok(list_age_limits(pool, restaurant_id)
+        .await?
+        .into_iter()
+        .map(|limit| (limit.category_id.clone(), limit))
+        .collect())
+}
+
+// Set the max price age for a category ('default' for the fallback), and whether a stale
+// price is excluded from winning or only flagged
+pub async fn save_age_limit(
+    pool: &DbPool,
+    restaurant_id: &str,
+    category_id: &str,
+    max_age_days: i32,
+    stale_action: &str,
+) -> Result<PriceAgeLimit, AppError> {
+    if category_id.trim().is_empty() {
+        return Err(AppError::Validation("A category is required".to_string()));
+    }
+    if max_age_days <= 0 {
+        return Err(AppError::Validation("The max price age must be at least one day".to_string()));
+    }
+    if !matches!(stale_action, "exclude" | "flag") {
+        return Err(AppError::Validation(format!(
+            "Unknown stale price action '{}'; use 'exclude' or 'flag'",
+            stale_action
+        )));
+    }
+
+    sqlx::query(
+        r#"INSERT INTO price_age_limits (restaurant_id, category_id, max_age_days, stale_action)
+           VALUES (?, ?, ?, ?)
+           ON CONFLICT(restaurant_id, category_id) DO UPDATE SET
+               max_age_days = excluded.max_age_days,
+               stale_action = excluded.stale_action,
+               updated_at = CURRENT_TIMESTAMP"#
+    )
+    .bind(restaurant_id)
+    .bind(category_id)
+    .bind(max_age_days)
+    .bind(stale_action)
+    .execute(pool)
+    .await?;
+
+    let limit = sqlx::query_as::<_, PriceAgeLimit>(
+        "SELECT * FROM price_age_limits WHERE restaurant_id = ? AND category_id = ?"
+    )
+    .bind(restaurant_id)
+    .bind(category_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(limit)
+}
+
+// Drop a category's age limit so it falls back to the default one, if any
+pub async fn delete_age_limit(pool: &DbPool, restaurant_id: &str, category_id: &str) -> Result<(), AppError> {
+    let deleted = sqlx::query("DELETE FROM price_age_limits WHERE restaurant_id = ? AND category_id = ?")
+        .bind(restaurant_id)
+        .bind(category_id)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound(format!("Price age limit for category {}", category_id)));
+    }
+    Ok(())
+}
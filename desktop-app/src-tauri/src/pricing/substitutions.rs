@@ -0,0 +1,247 @@
+use crate::db::{
+    models::{SubstitutionGroup, SubstitutionGroupMember, SubstitutionGroupWithMembers},
+    DbPool,
+};
+use crate::error::AppError;
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqliteConnection;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+// Members with their product names, flagging additions still waiting in the upload queue
+const MEMBER_QUERY: &str = r#"SELECT m.group_id, m.catalog_product_id, p.product_name, EXISTS (
+        SELECT 1 FROM pending_sync_events e
+        WHERE e.event_type = 'substitution_update'
+          AND json_extract(e.payload, '$.group_id') = m.group_id
+          AND json_extract(e.payload, '$.catalog_product_id') = m.catalog_product_id
+    ) AS pending_upload
+    FROM substitution_group_members m
+    LEFT JOIN products p ON p.catalog_product_id = m.catalog_product_id"#;
+
+// A restaurant's active substitution groups and their members
+pub async fn list_groups(pool: &DbPool, restaurant_id: &str) -> Result<Vec<SubstitutionGroupWithMembers>, AppError> {
+    let groups = sqlx::query_as::<_, SubstitutionGroup>(
+        "SELECT * FROM substitution_groups WHERE restaurant_id = ? AND is_active = 1 ORDER BY group_name"
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut conn = pool.acquire().await?;
+    let mut result = Vec::new();
+    for group in groups {
+        let members = group_members(&mut conn, &group.group_id).await?;
+        result.push(SubstitutionGroupWithMembers { group, members });
+    }
+
+    Ok(result)
+}
+
+async fn group_members(conn: &mut SqliteConnection, group_id: &str) -> Result<Vec<SubstitutionGroupMember>, AppError> {
+    let members = sqlx::query_as::<_, SubstitutionGroupMember>(&format!(
+        "{} WHERE m.group_id = ? ORDER BY p.product_name, m.catalog_product_id",
+        MEMBER_QUERY
+    ))
+    .bind(group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(members)
+}
+
+async fn get_group(
+    conn: &mut SqliteConnection,
+    restaurant_id: &str,
+    group_id: &str,
+) -> Result<SubstitutionGroup, AppError> {
+    sqlx::query_as::<_, SubstitutionGroup>(
+        "SELECT * FROM substitution_groups WHERE group_id = ? AND restaurant_id = ? AND is_active = 1"
+    )
+    .bind(group_id)
+    .bind(restaurant_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Substitution group {}", group_id)))
+}
+
+// The member products of each active group, as the winner calculation uses them
+pub async fn groups_for_winner(pool: &DbPool, restaurant_id: &str) -> Result<HashMap<String, Vec<String>>, AppError> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        r#"SELECT m.group_id, m.catalog_product_id FROM substitution_group_members m
+           JOIN substitution_groups g ON g.group_id = m.group_id
+           WHERE g.restaurant_id = ? AND g.is_active = 1
+           ORDER BY m.group_id, m.catalog_product_id"#
+    )
+    .bind(restaurant_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for (group_id, product_id) in rows {
+        groups.entry(group_id).or_default().push(product_id);
+    }
+
+    Ok(groups)
+}
+
+// Why a product can't join a group, if it can't: its unit prices have to compare with the
+// other members', and a product can only compete in one of a restaurant's groups
+pub async fn membership_problem(
+    conn: &mut SqliteConnection,
+    group: &SubstitutionGroup,
+    catalog_product_id: &str,
+) -> Result<Option<String>, AppError> {
+    let product = sqlx::query_as::<_, (String, String)>(
+        "SELECT product_name, measurement_type FROM products WHERE catalog_product_id = ?"
+    )
+    .bind(catalog_product_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some((product_name, measurement_type)) = product else {
+        return Ok(Some(format!("Product {} is not in the catalog", catalog_product_id)));
+    };
+    if !measurement_type.eq_ignore_ascii_case(&group.measurement_type) {
+        return Ok(Some(format!(
+            "{} is measured by {}, but every product in {} is measured by {}",
+            product_name, measurement_type, group.group_name, group.measurement_type
+        )));
+    }
+
+    let other_group = sqlx::query_scalar::<_, String>(
+        r#"SELECT g.group_name FROM substitution_group_members m
+           JOIN substitution_groups g ON g.group_id = m.group_id
+           WHERE m.catalog_product_id = ? AND g.restaurant_id = ? AND g.group_id != ? AND g.is_active = 1"#
+    )
+    .bind(catalog_product_id)
+    .bind(&group.restaurant_id)
+    .bind(&group.group_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if let Some(other_group) = other_group {
+        return Ok(Some(format!("{} is already in {}", product_name, other_group)));
+    }
+
+    Ok(None)
+}
+
+// Add a product to a group and queue the change for upload
+pub async fn add_member(
+    pool: &DbPool,
+    restaurant_id: &str,
+    group_id: &str,
+    catalog_product_id: &str,
+) -> Result<SubstitutionGroupWithMembers, AppError> {
+    let mut tx = pool.begin().await?;
+    let group = get_group(&mut tx, restaurant_id, group_id).await?;
+
+    if let Some(problem) = membership_problem(&mut tx, &group, catalog_product_id).await? {
+        return Err(AppError::Validation(problem));
+    }
+
+    let added = sqlx::query(
+        "INSERT OR IGNORE INTO substitution_group_members (group_id, catalog_product_id) VALUES (?, ?)"
+    )
+    .bind(group_id)
+    .bind(catalog_product_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if added == 0 {
+        return Err(AppError::AlreadyExists(format!(
+            "Product {} is already in {}",
+            catalog_product_id, group.group_name
+        )));
+    }
+
+    queue_membership_upload(&mut tx, &group, catalog_product_id, "add_member").await?;
+
+    let members = group_members(&mut tx, group_id).await?;
+    tx.commit().await?;
+
+    Ok(SubstitutionGroupWithMembers { group, members })
+}
+
+// Take a product out of a group and queue the change for upload
+pub async fn remove_member(
+    pool: &DbPool,
+    restaurant_id: &str,
+    group_id: &str,
+    catalog_product_id: &str,
+) -> Result<SubstitutionGroupWithMembers, AppError> {
+    let mut tx = pool.begin().await?;
+    let group = get_group(&mut tx, restaurant_id, group_id).await?;
+
+    let removed = sqlx::query("DELETE FROM substitution_group_members WHERE group_id = ? AND catalog_product_id = ?")
+        .bind(group_id)
+        .bind(catalog_product_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if removed == 0 {
+        return Err(AppError::NotFound(format!(
+            "Product {} in {}",
+            catalog_product_id, group.group_name
+        )));
+    }
+
+    queue_membership_upload(&mut tx, &group, catalog_product_id, "remove_member").await?;
+
+    let members = group_members(&mut tx, group_id).await?;
+    tx.commit().await?;
+
+    Ok(SubstitutionGroupWithMembers { group, members })
+}
+
+// Queue a membership change, replacing an earlier one for the same product and group that
+// hasn't gone up yet
+async fn queue_membership_upload(
+    conn: &mut SqliteConnection,
+    group: &SubstitutionGroup,
+    catalog_product_id: &str,
+    action: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"DELETE FROM pending_sync_events
+           WHERE event_type = 'substitution_update'
+             AND json_extract(payload, '$.group_id') = ?
+             AND json_extract(payload, '$.catalog_product_id') = ?"#
+    )
+    .bind(&group.group_id)
+    .bind(catalog_product_id)
+    .execute(&mut *conn)
+    .await?;
+
+    let payload = json!({
+        "action": action,
+        "group_id": group.group_id,
+        "catalog_product_id": catalog_product_id,
+        "updated_at": Utc::now(),
+    });
+    sqlx::query(
+        r#"INSERT INTO pending_sync_events (event_id, event_type, restaurant_id, payload, scheduled_for)
+           VALUES (?, 'substitution_update', ?, ?, ?)"#
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(&group.restaurant_id)
+    .bind(payload.to_string())
+    .bind(Utc::now())
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Groups with membership changes made here and not uploaded yet; a sync leaves their
+// members alone so it doesn't undo the change
+pub async fn pending_group_ids(conn: &mut SqliteConnection) -> Result<HashSet<String>, AppError> {
+    let group_ids = sqlx::query_scalar::<_, String>(
+        r#"SELECT DISTINCT json_extract(payload, '$.group_id') FROM pending_sync_events
+           WHERE event_type = 'substitution_update'"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(group_ids.into_iter().collect())
+}
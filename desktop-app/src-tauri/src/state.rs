@@ -4,6 +4,8 @@ use tokio::sync::Mutex;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+use crate::csv_import::ImportControl;
+
 #[derive(Default)]
 pub struct AppState {
     pub db: Arc<Mutex<Option<Pool<Sqlite>>>>,
@@ -11,6 +13,7 @@ pub struct AppState {
     pub sync_status: Arc<Mutex<SyncStatus>>,
     pub product_sync_state: Arc<Mutex<ProductSyncState>>,
     pub auth_state: Arc<Mutex<AuthState>>,
+    pub running_imports: Arc<Mutex<Vec<ImportControl>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -140,4 +143,26 @@ impl AppState {
         let state = self.auth_state.lock().await;
         state.clone()
     }
+    
+    pub async fn add_running_import(&self, control: ImportControl) {
+        let mut imports = self.running_imports.lock().await;
+        imports.push(control);
+    }
+    
+    pub async fn remove_running_import(&self, control: &ImportControl) {
+        let mut imports = self.running_imports.lock().await;
+        imports.retain(|running| !running.same_as(control));
+    }
+    
+    // Ask a running import to stop; false when no import with this id is running
+    pub async fn cancel_running_import(&self, import_id: &str) -> bool {
+        let imports = self.running_imports.lock().await;
+        match imports.iter().find(|running| running.import_id() == Some(import_id)) {
+            Some(control) => {
+                control.cancel();
+                true
+            }
+            None => false,
+        }
+    }
 }
\ No newline at end of file
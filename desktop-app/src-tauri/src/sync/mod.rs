@@ -7,7 +7,11 @@ use postgrest::Postgrest;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{AppHandle, Emitter, Manager};
-use tracing::{error, info};
+use tracing::{error, info, warn};
+
+// An edit the cloud refuses this many times stops being retried. It stays queued, keeping
+// the local value and its last_error, until it is edited again.
+const MAX_UPLOAD_ATTEMPTS: i32 = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncStatus {
@@ -37,6 +41,41 @@ struct SupabaseProduct {
     updated_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct SupabaseSalesContact {
+    contact_id: String,
+    restaurant_id: Option<String>,
+    distributor_id: String,
+    contact_name: String,
+    email: Option<String>,
+    is_primary: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SupabaseProductPreference {
+    restaurant_id: String,
+    catalog_product_id: String,
+    preferred_distributor_id: Option<String>,
+    always_use_preferred: Option<bool>,
+    notes: Option<String>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SupabaseSubstitutionGroup {
+    group_id: String,
+    restaurant_id: String,
+    group_name: String,
+    measurement_type: String,
+    created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SupabaseSubstitutionMember {
+    group_id: String,
+    catalog_product_id: String,
+}
+
 // Verify authentication is working
 pub async fn verify_authentication(postgrest: &Postgrest) -> Result<bool, AppError> {
     info!("Verifying authentication with Supabase...");
@@ -250,6 +289,30 @@ pub async fn smart_sync(app_handle: &AppHandle, postgrest: &Postgrest) -> Result
     // Step 8: Commit transaction
     transaction.commit().await?;
     
+    // Sales contacts only identify emailed price sheets, so they don't fail the product sync
+    match sync_sales_contacts(&pool, postgrest).await {
+        Ok(count) => info!("Synced {} distributor sales contacts", count),
+        Err(e) => warn!("Could not sync distributor sales contacts: {}", e),
+    }
+    
+    // Edits made here go up before preferences and groups come down, so the download includes them
+    match upload_pending_edits(&pool, postgrest).await {
+        Ok(count) if count > 0 => info!("Uploaded {} preference and substitution group edits", count),
+        Ok(_) => {}
+        Err(e) => warn!("Could not upload preference and substitution group edits: {}", e),
+    }
+    
+    // Preferences only steer the winner calculation, so they don't fail the product sync either
+    match sync_product_preferences(&pool, postgrest, sync_start_time).await {
+        Ok(count) => info!("Synced {} product preferences", count),
+        Err(e) => warn!("Could not sync product preferences: {}", e),
+    }
+    
+    match sync_substitution_groups(&pool, postgrest, sync_start_time).await {
+        Ok(count) => info!("Synced {} substitution groups", count),
+        Err(e) => warn!("Could not sync substitution groups: {}", e),
+    }
+    
     // Step 9: Update state and emit events
     let count = db::get_product_count(&pool).await?;
     let duration_ms = start_instant.elapsed().as_millis() as u64;
@@ -272,6 +335,405 @@ pub async fn smart_sync(app_handle: &AppHandle, postgrest: &Postgrest) -> Result
     Ok(())
 }
 
+// Replace the local sales contacts with the active ones in Supabase
+async fn sync_sales_contacts(pool: &db::DbPool, postgrest: &Postgrest) -> Result<usize, AppError> {
+    let contacts: Vec<SupabaseSalesContact> = fetch_rows(
+        postgrest
+            .from("distributor_sales_contacts")
+            .select("contact_id,restaurant_id,distributor_id,contact_name,email,is_primary")
+            .eq("is_active", "true"),
+        "sales contacts",
+    )
+    .await?;
+    
+    let mut transaction = pool.begin().await?;
+    sqlx::query("DELETE FROM distributor_sales_contacts")
+        .execute(&mut *transaction)
+        .await?;
+    
+    for contact in &contacts {
+        sqlx::query(
+            r#"INSERT INTO distributor_sales_contacts (contact_id, restaurant_id, distributor_id, contact_name,
+               email, is_primary, is_active, synced_at)
+               VALUES (?, ?, ?, ?, ?, ?, 1, CURRENT_TIMESTAMP)"#
+        )
+        .bind(&contact.contact_id)
+        .bind(&contact.restaurant_id)
+        .bind(&contact.distributor_id)
+        .bind(&contact.contact_name)
+        .bind(&contact.email)
+        .bind(contact.is_primary.unwrap_or(false))
+        .execute(&mut *transaction)
+        .await?;
+    }
+    
+    transaction.commit().await?;
+    Ok(contacts.len())
+}
+
+// Send the preference and substitution group edits queued here to the cloud. An uploaded
+// edit leaves the queue; one the cloud refuses is retried on later syncs, up to
+// MAX_UPLOAD_ATTEMPTS times.
+async fn upload_pending_edits(pool: &db::DbPool, postgrest: &Postgrest) -> Result<usize, AppError> {
+    let events = sqlx::query_as::<_, (String, String, String, i32)>(
+        r#"SELECT event_id, event_type, payload, COALESCE(sync_attempts, 0) FROM pending_sync_events
+           WHERE event_type IN ('preference_update', 'substitution_update') AND scheduled_for <= ?
+             AND COALESCE(sync_attempts, 0) < ?
+           ORDER BY created_at"#
+    )
+    .bind(Utc::now())
+    .bind(MAX_UPLOAD_ATTEMPTS)
+    .fetch_all(pool)
+    .await?;
+    
+    let mut uploaded = 0;
+    for (event_id, event_type, payload, attempts) in events {
+        let payload: serde_json::Value = serde_json::from_str(&payload)?;
+        let sent = match event_type.as_str() {
+            "preference_update" => upload_preference(postgrest, &payload).await,
+            "substitution_update" => upload_membership(postgrest, &payload).await,
+            _ => continue,
+        };
+        
+        match sent {
+            Ok(()) => {
+                // An edit made while this one was uploading has replaced its event and stays queued
+                sqlx::query("DELETE FROM pending_sync_events WHERE event_id = ?")
+                    .bind(&event_id)
+                    .execute(pool)
+                    .await?;
+                uploaded += 1;
+            }
+            Err(e) => {
+                if attempts + 1 >= MAX_UPLOAD_ATTEMPTS {
+                    warn!(
+                        "Stopped uploading {} after {} attempts; it stays queued until edited again: {}",
+                        event_type,
+                        attempts + 1,
+                        e
+                    );
+                } else {
+                    warn!("Could not upload {}, will retry: {}", event_type, e);
+                }
+                sqlx::query(
+                    r#"UPDATE pending_sync_events
+                       SET sync_attempts = COALESCE(sync_attempts, 0) + 1, last_attempt_at = CURRENT_TIMESTAMP, last_error = ?
+                       WHERE event_id = ?"#
+                )
+                .bind(e.to_string())
+                .bind(&event_id)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    
+    Ok(uploaded)
+}
+
+// Apply one queued preference edit to the cloud's product_preferences
+async fn upload_preference(postgrest: &Postgrest, payload: &serde_json::Value) -> Result<(), AppError> {
+    let text = |key: &str| payload.get(key).and_then(|value| value.as_str()).unwrap_or_default().to_string();
+    let (restaurant_id, catalog_product_id) = (text("restaurant_id"), text("catalog_product_id"));
+    
+    let builder = match payload.get("action").and_then(|action| action.as_str()) {
+        Some("delete") => postgrest
+            .from("product_preferences")
+            .eq("restaurant_id", &restaurant_id)
+            .eq("catalog_product_id", &catalog_product_id)
+            .delete(),
+        _ => postgrest
+            .from("product_preferences")
+            .upsert(
+                json!({
+                    "restaurant_id": restaurant_id,
+                    "catalog_product_id": catalog_product_id,
+                    "preferred_distributor_id": payload.get("preferred_distributor_id"),
+                    "always_use_preferred": payload.get("always_use_preferred"),
+                    "notes": payload.get("notes"),
+                    "updated_at": payload.get("updated_at"),
+                })
+                .to_string(),
+            )
+            .on_conflict("restaurant_id,catalog_product_id"),
+    };
+    
+    send_write(builder, "product preference").await
+}
+
+// Apply one queued membership change to the cloud's substitution_group_members
+async fn upload_membership(postgrest: &Postgrest, payload: &serde_json::Value) -> Result<(), AppError> {
+    let text = |key: &str| payload.get(key).and_then(|value| value.as_str()).unwrap_or_default().to_string();
+    let (group_id, catalog_product_id) = (text("group_id"), text("catalog_product_id"));
+    
+    let builder = match payload.get("action").and_then(|action| action.as_str()) {
+        Some("remove_member") => postgrest
+            .from("substitution_group_members")
+            .eq("group_id", &group_id)
+            .eq("catalog_product_id", &catalog_product_id)
+            .delete(),
+        _ => postgrest
+            .from("substitution_group_members")
+            .upsert(json!({ "group_id": group_id, "catalog_product_id": catalog_product_id }).to_string())
+            .on_conflict("group_id,catalog_product_id"),
+    };
+    
+    send_write(builder, "substitution group member").await
+}
+
+// Bring down the product preferences of this device's restaurants. A preference edited
+// here and still waiting to upload is left as it is, so the sync doesn't undo the edit.
+async fn sync_product_preferences(
+    pool: &db::DbPool,
+    postgrest: &Postgrest,
+    sync_start_time: DateTime<Utc>,
+) -> Result<usize, AppError> {
+    let restaurant_ids: Vec<String> = sqlx::query_scalar("SELECT restaurant_id FROM restaurants")
+        .fetch_all(pool)
+        .await?;
+    if restaurant_ids.is_empty() {
+        return Ok(0);
+    }
+    
+    let preferences: Vec<SupabaseProductPreference> = fetch_rows(
+        postgrest
+            .from("product_preferences")
+            .select("restaurant_id,catalog_product_id,preferred_distributor_id,always_use_preferred,notes,updated_at")
+            .in_("restaurant_id", &restaurant_ids),
+        "product preferences",
+    )
+    .await?;
+    
+    let mut transaction = pool.begin().await?;
+    let pending = crate::pricing::preferences::pending_preference_keys(&mut transaction).await?;
+    
+    let mut synced = 0;
+    for preference in &preferences {
+        let key = (preference.restaurant_id.clone(), preference.catalog_product_id.clone());
+        if pending.contains(&key) {
+            continue;
+        }
+        
+        // A hard preference needs a distributor; without one the row can only break ties
+        let always_use_preferred =
+            preference.always_use_preferred.unwrap_or(false) && preference.preferred_distributor_id.is_some();
+        sqlx::query(
+            r#"INSERT INTO product_preferences (restaurant_id, catalog_product_id, preferred_distributor_id,
+               always_use_preferred, notes, updated_at, synced_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, COALESCE(?6, CURRENT_TIMESTAMP), ?7)
+               ON CONFLICT(restaurant_id, catalog_product_id) DO UPDATE SET
+                   preferred_distributor_id = excluded.preferred_distributor_id,
+                   always_use_preferred = excluded.always_use_preferred,
+                   notes = excluded.notes,
+                   updated_at = excluded.updated_at,
+                   synced_at = excluded.synced_at"#
+        )
+        .bind(&preference.restaurant_id)
+        .bind(&preference.catalog_product_id)
+        .bind(&preference.preferred_distributor_id)
+        .bind(always_use_preferred)
+        .bind(&preference.notes)
+        .bind(&preference.updated_at)
+        .bind(&sync_start_time)
+        .execute(&mut *transaction)
+        .await?;
+        
+        synced += 1;
+    }
+    
+    // Preferences removed in the cloud, unless removing them would drop an edit made here
+    let deleted = sqlx::query(
+        r#"DELETE FROM product_preferences
+           WHERE (synced_at < ?1 OR synced_at IS NULL)
+             AND NOT EXISTS (
+                 SELECT 1 FROM pending_sync_events e
+                 WHERE e.event_type = 'preference_update'
+                   AND e.restaurant_id = product_preferences.restaurant_id
+                   AND json_extract(e.payload, '$.catalog_product_id') = product_preferences.catalog_product_id
+             )"#
+    )
+    .bind(&sync_start_time)
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected();
+    
+    if deleted > 0 {
+        info!("Removed {} product preferences no longer in the cloud", deleted);
+    }
+    
+    transaction.commit().await?;
+    Ok(synced)
+}
+
+// Bring down the active substitution groups of this device's restaurants with their members.
+// A member whose measurement_type differs from its group's is left out, since its unit
+// prices can't be compared; a group with membership changes waiting to upload keeps its
+// local members.
+async fn sync_substitution_groups(
+    pool: &db::DbPool,
+    postgrest: &Postgrest,
+    sync_start_time: DateTime<Utc>,
+) -> Result<usize, AppError> {
+    let restaurant_ids: Vec<String> = sqlx::query_scalar("SELECT restaurant_id FROM restaurants")
+        .fetch_all(pool)
+        .await?;
+    if restaurant_ids.is_empty() {
+        return Ok(0);
+    }
+    
+    let groups: Vec<SupabaseSubstitutionGroup> = fetch_rows(
+        postgrest
+            .from("substitution_groups")
+            .select("group_id,restaurant_id,group_name,measurement_type,created_at")
+            .in_("restaurant_id", &restaurant_ids)
+            .eq("is_active", "true"),
+        "substitution groups",
+    )
+    .await?;
+    
+    let group_ids: Vec<&str> = groups.iter().map(|group| group.group_id.as_str()).collect();
+    let members: Vec<SupabaseSubstitutionMember> = if group_ids.is_empty() {
+        Vec::new()
+    } else {
+        fetch_rows(
+            postgrest
+                .from("substitution_group_members")
+                .select("group_id,catalog_product_id")
+                .in_("group_id", &group_ids),
+            "substitution group members",
+        )
+        .await?
+    };
+    
+    let mut transaction = pool.begin().await?;
+    let pending = crate::pricing::substitutions::pending_group_ids(&mut transaction).await?;
+    
+    for group in &groups {
+        sqlx::query(
+            r#"INSERT INTO substitution_groups (group_id, restaurant_id, group_name, measurement_type, is_active,
+               created_at, synced_at)
+               VALUES (?1, ?2, ?3, ?4, 1, COALESCE(?5, CURRENT_TIMESTAMP), ?6)
+               ON CONFLICT(group_id) DO UPDATE SET
+                   restaurant_id = excluded.restaurant_id,
+                   group_name = excluded.group_name,
+                   measurement_type = excluded.measurement_type,
+                   is_active = 1,
+                   synced_at = excluded.synced_at"#
+        )
+        .bind(&group.group_id)
+        .bind(&group.restaurant_id)
+        .bind(&group.group_name)
+        .bind(&group.measurement_type)
+        .bind(&group.created_at)
+        .bind(&sync_start_time)
+        .execute(&mut *transaction)
+        .await?;
+    }
+    
+    // Groups deleted or deactivated in the cloud, and their members, unless changed here
+    let deleted = sqlx::query(
+        r#"DELETE FROM substitution_groups
+           WHERE (synced_at < ?1 OR synced_at IS NULL)
+             AND NOT EXISTS (
+                 SELECT 1 FROM pending_sync_events e
+                 WHERE e.event_type = 'substitution_update'
+                   AND json_extract(e.payload, '$.group_id') = substitution_groups.group_id
+             )"#
+    )
+    .bind(&sync_start_time)
+    .execute(&mut *transaction)
+    .await?
+    .rows_affected();
+    
+    if deleted > 0 {
+        info!("Removed {} substitution groups no longer in the cloud", deleted);
+    }
+    
+    for group_id in &group_ids {
+        if pending.contains(*group_id) {
+            continue;
+        }
+        sqlx::query("DELETE FROM substitution_group_members WHERE group_id = ?")
+            .bind(group_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    
+    for member in &members {
+        if pending.contains(&member.group_id) {
+            continue;
+        }
+        let Some(group) = sqlx::query_as::<_, db::models::SubstitutionGroup>(
+            "SELECT * FROM substitution_groups WHERE group_id = ?"
+        )
+        .bind(&member.group_id)
+        .fetch_optional(&mut *transaction)
+        .await? else {
+            continue;
+        };
+        
+        if let Some(problem) =
+            crate::pricing::substitutions::membership_problem(&mut transaction, &group, &member.catalog_product_id).await?
+        {
+            warn!("Leaving {} out of substitution group {}: {}", member.catalog_product_id, group.group_id, problem);
+            continue;
+        }
+        
+        sqlx::query("INSERT OR IGNORE INTO substitution_group_members (group_id, catalog_product_id) VALUES (?, ?)")
+            .bind(&member.group_id)
+            .bind(&member.catalog_product_id)
+            .execute(&mut *transaction)
+            .await?;
+    }
+    
+    transaction.commit().await?;
+    Ok(groups.len())
+}
+
+// Run a PostgREST query and parse the rows, for the secondary tables synced alongside products
+async fn fetch_rows<T: serde::de::DeserializeOwned>(
+    builder: postgrest::Builder,
+    what: &str,
+) -> Result<Vec<T>, AppError> {
+    let response = builder
+        .execute()
+        .await
+        .map_err(|e| AppError::Sync(format!("Network request to fetch {} failed: {}", what, e)))?;
+    
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_body = response.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
+        return Err(AppError::Sync(format!(
+            "Supabase returned a non-success status for {}: {}. Body: {}",
+            what, status, error_body
+        )));
+    }
+    
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::Sync(format!("Failed to parse {} from Supabase: {}", what, e)))
+}
+
+// Run a PostgREST insert, upsert or delete, failing on a non-success status
+async fn send_write(builder: postgrest::Builder, what: &str) -> Result<(), AppError> {
+    let response = builder
+        .execute()
+        .await
+        .map_err(|e| AppError::Sync(format!("Network request to upload {} failed: {}", what, e)))?;
+    
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_body = response.text().await.unwrap_or_else(|_| "Could not read error body".to_string());
+        return Err(AppError::Sync(format!(
+            "Supabase returned a non-success status for {}: {}. Body: {}",
+            what, status, error_body
+        )));
+    }
+    
+    Ok(())
+}
+
 // Helper to emit products updated event
 async fn emit_products_updated(app_handle: &AppHandle) {
     app_handle.emit("products-updated", json!({})).ok();